@@ -1,17 +1,36 @@
 use crate::models::alert;
+use crate::models::cgroup;
+use crate::models::chart;
+use crate::models::container;
 use crate::models::cpu;
+use crate::models::disk;
+use crate::models::event;
+use crate::models::history;
 use crate::models::info;
+use crate::models::inspector;
+use crate::models::load;
 use crate::models::memory;
+use crate::models::network;
+use crate::models::output;
+use crate::models::power;
 use crate::models::process;
+use crate::models::sensor;
+use crate::models::socket;
+use crate::models::stack;
+use crate::models::thread;
+use crate::models::unit;
+use crate::models::user;
+use crate::supervisor::Supervisor;
 use ratatui::{
     layout::{Constraint, Direction, Flex, Layout},
     prelude::Rect,
-    style::{Color, Style},
+    style::{Color, Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph},
-    DefaultTerminal,
+    widgets::{Block, Borders, Clear, Paragraph, Tabs},
+    DefaultTerminal, Frame,
 };
 use std::io;
+use strum::IntoEnumIterator;
 
 pub struct PTop {
     terminal: DefaultTerminal,
@@ -19,31 +38,112 @@ pub struct PTop {
     pub state: AppState,
 }
 
+#[derive(Clone, Copy, PartialEq, strum::Display, strum::EnumIter)]
+pub enum Page {
+    Overview,
+    #[strum(serialize = "CPU")]
+    Cpu,
+    Memory,
+    Storage,
+    Network,
+    Cgroups,
+    Containers,
+    Units,
+    Users,
+    Hardware,
+    History,
+}
+
+impl Page {
+    pub fn next(self) -> Self {
+        Page::iter()
+            .cycle()
+            .skip_while(|p| *p != self)
+            .nth(1)
+            .unwrap_or(Page::Overview)
+    }
+}
+
 pub enum Mode {
     Normal,
     ProcessFilter,
     AlertCpuThreshold,
     AlertMemoryThreshold,
+    AlertWriteRateThreshold,
+    AlertDiskThreshold,
+    AlertTemperatureThreshold,
+    AlertBatteryThreshold,
+    AlertPressureThreshold,
+    UnitConfirm,
+    SocketView,
+    Inspector,
+    InspectorSearch,
+    ThreadView,
+    ThreadFilter,
+    StackView,
 }
 
 pub struct AppState {
     pub mode: Mode,
+    pub page: Page,
+    // Time span shown by the charts
+    pub chart_window: chart::Window,
     pub filter: String,
     pub alert_cpu_threshold: String,
     pub alert_memory_threshold: String,
+    pub alert_write_rate_threshold: String,
+    pub alert_disk_threshold: String,
+    pub alert_temperature_threshold: String,
+    pub alert_battery_threshold: String,
+    pub alert_pressure_threshold: String,
     pub process_table_state: process::ProcessTableState,
     pub alert_table_state: alert::AlertTableState,
+    pub event_log_state: event::EventLogState,
+    pub pressure_table_state: load::PressureTableState,
+    pub filesystem_table_state: disk::FilesystemTableState,
+    pub network_table_state: network::NetworkTableState,
+    pub cgroup_table_state: cgroup::CgroupTableState,
+    pub container_table_state: container::ContainerTableState,
+    pub unit_table_state: unit::UnitTableState,
+    pub user_table_state: user::UserTableState,
+    pub sensor_table_state: sensor::SensorTableState,
+    pub history_state: history::HistoryState,
+    pub socket_table_state: socket::SocketTableState,
+    pub inspector_state: inspector::InspectorState,
+    pub thread_table_state: thread::ThreadTableState,
+    pub stack_view_state: stack::StackViewState,
 }
 
 impl AppState {
     pub fn new() -> Self {
         AppState {
             mode: Mode::Normal,
+            page: Page::Overview,
+            chart_window: chart::Window::OneMinute,
             filter: String::new(),
             alert_cpu_threshold: String::new(),
             alert_memory_threshold: String::new(),
+            alert_write_rate_threshold: String::new(),
+            alert_disk_threshold: String::new(),
+            alert_temperature_threshold: String::new(),
+            alert_battery_threshold: String::new(),
+            alert_pressure_threshold: String::new(),
             process_table_state: process::ProcessTableState::new(),
             alert_table_state: alert::AlertTableState::new(),
+            event_log_state: event::EventLogState::new(),
+            pressure_table_state: load::PressureTableState::new(),
+            filesystem_table_state: disk::FilesystemTableState::new(),
+            network_table_state: network::NetworkTableState::new(),
+            cgroup_table_state: cgroup::CgroupTableState::new(),
+            container_table_state: container::ContainerTableState::new(),
+            unit_table_state: unit::UnitTableState::new(),
+            user_table_state: user::UserTableState::new(),
+            sensor_table_state: sensor::SensorTableState::new(),
+            history_state: history::HistoryState::new(),
+            socket_table_state: socket::SocketTableState::new(),
+            inspector_state: inspector::InspectorState::new(),
+            thread_table_state: thread::ThreadTableState::new(),
+            stack_view_state: stack::StackViewState::new(),
         }
     }
 
@@ -68,9 +168,38 @@ pub struct Widgets {
     pub cpu: cpu::CpuHistogram,
     pub info: info::Info,
     pub memory: memory::Memory,
+    pub memory_breakdown: memory::MemoryBreakdown,
+    pub memory_breakdown_history: memory::MemoryBreakdownHistogram,
+    pub disk_io: disk::DiskIo,
+    pub filesystem_table: disk::FilesystemTable,
+    pub network: network::NetworkHistogram,
+    pub network_table: network::NetworkTable,
+    pub cgroup_table: cgroup::CgroupTable,
+    pub container_table: container::ContainerTable,
+    pub unit_table: unit::UnitTable,
+    pub session_table: user::SessionTable,
+    pub user_table: user::UserTable,
+    pub sensors: sensor::SensorHistogram,
+    pub sensor_table: sensor::SensorTable,
+    pub power: power::PowerHistogram,
+    pub power_table: power::PowerTable,
+    pub history: history::HistoryBrowser,
     pub cpu_per_core: cpu::CpuPerCore,
+    pub cpu_breakdown: cpu::CpuBreakdownHistogram,
+    pub cpu_heatmap: cpu::CpuHeatmap,
+    pub cpu_frequency: cpu::CpuFrequencyTable,
+    pub load: load::LoadHistogram,
+    pub pressure: load::PressureHistogram,
+    pub pressure_table: load::PressureTable,
     pub process_table: process::ProcessTable,
     pub alert_table: alert::AlertTable,
+    pub event_log: event::EventLog,
+    pub socket_table: socket::SocketTable,
+    pub inspector: inspector::Inspector,
+    pub thread_table: thread::ThreadTable,
+    pub stack_view: stack::StackView,
+    // Only present when ptop supervises a command
+    pub output: Option<output::OutputPanel>,
 }
 
 impl PTop {
@@ -81,48 +210,83 @@ impl PTop {
                 cpu: cpu::CpuHistogram::new(),
                 info: info::Info::new(),
                 memory: memory::Memory::new(),
+                memory_breakdown: memory::MemoryBreakdown::new(),
+                memory_breakdown_history: memory::MemoryBreakdownHistogram::new(),
+                disk_io: disk::DiskIo::new(),
+                filesystem_table: disk::FilesystemTable::new(),
+                network: network::NetworkHistogram::new(),
+                network_table: network::NetworkTable::new(),
+                cgroup_table: cgroup::CgroupTable::new(),
+                container_table: container::ContainerTable::new(),
+                unit_table: unit::UnitTable::new(),
+                session_table: user::SessionTable::new(),
+                user_table: user::UserTable::new(),
+                sensors: sensor::SensorHistogram::new(),
+                sensor_table: sensor::SensorTable::new(),
+                power: power::PowerHistogram::new(),
+                power_table: power::PowerTable::new(),
+                history: history::HistoryBrowser::new(),
                 cpu_per_core: cpu::CpuPerCore::new(),
+                cpu_breakdown: cpu::CpuBreakdownHistogram::new(),
+                cpu_heatmap: cpu::CpuHeatmap::new(),
+                cpu_frequency: cpu::CpuFrequencyTable::new(),
+                load: load::LoadHistogram::new(),
+                pressure: load::PressureHistogram::new(),
+                pressure_table: load::PressureTable::new(),
                 process_table: process::ProcessTable::new(),
                 alert_table: alert::AlertTable::new(),
+                event_log: event::EventLog::new(),
+                socket_table: socket::SocketTable::new(),
+                inspector: inspector::Inspector::new(),
+                thread_table: thread::ThreadTable::new(),
+                stack_view: stack::StackView::new(),
+                output: None,
             },
             state: AppState::new(),
         }
     }
 
+    // Pins the process table to the process tree of a supervised command, watches
+    // it for exit and shows its output
+    pub fn supervise(&mut self, supervisor: &Supervisor) {
+        self.state.process_table_state.root_pid = Some(supervisor.pid);
+        self.widgets
+            .alert_table
+            .add_exit_code_alert(supervisor.pid, supervisor.name.clone());
+        self.widgets.output = Some(output::OutputPanel::new(
+            &supervisor.name,
+            supervisor.output.clone(),
+        ));
+    }
+
     pub fn draw(&mut self) -> Result<(), io::Error> {
         self.terminal.draw(|f| {
             // Overall Layout
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .margin(1)
-                .constraints([Constraint::Percentage(20), Constraint::Percentage(80)].as_ref())
+                .constraints(
+                    [
+                        Constraint::Length(1),
+                        Constraint::Percentage(20),
+                        Constraint::Percentage(80),
+                    ]
+                    .as_ref(),
+                )
                 .split(f.area());
 
+            // Page tabs
+            let tabs = Tabs::new(Page::iter().map(|p| p.to_string()))
+                .select(Page::iter().position(|p| p == self.state.page))
+                .highlight_style(Style::new().bold().reversed());
+            f.render_widget(tabs, chunks[0]);
+
             // Top half layout
             let top_chunks = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
-                .split(chunks[0]);
-
-            // Bottom half layout
-            let bottom_chunks = Layout::default()
                 .direction(Direction::Horizontal)
                 .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
                 .split(chunks[1]);
 
-            // Bottom-left nested layout
-            let bottom_left_chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints(
-                    [
-                        Constraint::Percentage(30),
-                        Constraint::Percentage(30),
-                        Constraint::Percentage(40),
-                    ]
-                    .as_ref(),
-                )
-                .split(bottom_chunks[0]);
-
             // Draw blocks for all sections
             let block_style = Style::default().fg(Color::White).bg(Color::Black);
 
@@ -136,21 +300,24 @@ impl PTop {
                 &mut self.state.alert_table_state.state,
             );
 
-            // Bottom-left top: CPU Histogram
-            f.render_widget(&self.widgets.cpu, bottom_left_chunks[0]);
-
-            // Bottom-left bottom: Memory Histogram
-            f.render_widget(&self.widgets.memory, bottom_left_chunks[1]);
-
-            // Bottom-left middle: CPU Per Core
-            f.render_widget(&self.widgets.cpu_per_core, bottom_left_chunks[2]);
-
-            // Bottom-right bottom: Process Table
-            f.render_stateful_widget(
-                &self.widgets.process_table,
-                bottom_chunks[1],
-                &mut self.state.process_table_state.state,
-            );
+            // Bottom half: page content
+            match self.state.page {
+                Page::Overview => draw_overview(f, chunks[2], &self.widgets, &mut self.state),
+                Page::Cpu => draw_cpu(f, chunks[2], &self.widgets, &mut self.state),
+                Page::Memory => draw_memory(f, chunks[2], &self.widgets),
+                Page::Storage => draw_storage(f, chunks[2], &self.widgets, &mut self.state),
+                Page::Network => draw_network(f, chunks[2], &self.widgets, &mut self.state),
+                Page::Cgroups => f.render_stateful_widget(
+                    &self.widgets.cgroup_table,
+                    chunks[2],
+                    &mut self.state.cgroup_table_state.state,
+                ),
+                Page::Containers => draw_containers(f, chunks[2], &self.widgets, &mut self.state),
+                Page::Units => draw_units(f, chunks[2], &self.widgets, &mut self.state),
+                Page::Users => draw_users(f, chunks[2], &self.widgets, &mut self.state),
+                Page::Hardware => draw_hardware(f, chunks[2], &self.widgets, &mut self.state),
+                Page::History => draw_history(f, chunks[2], &self.widgets, &mut self.state),
+            }
 
             // Popup
             let popup_area = |area: Rect, percent_x: u16, percent_y: u16| {
@@ -202,6 +369,137 @@ impl PTop {
                     f.render_widget(Clear, area);
                     f.render_widget(popup, area);
                 }
+                Mode::AlertWriteRateThreshold => {
+                    let popup = Paragraph::new(vec![Line::from(vec![Span::from(
+                        &self.state.alert_write_rate_threshold,
+                    )])])
+                    .block(
+                        Block::new()
+                            .borders(Borders::ALL)
+                            .title("Alert Write Rate Threshold (MiB/s)")
+                            .style(block_style),
+                    );
+                    f.render_widget(Clear, area);
+                    f.render_widget(popup, area);
+                }
+                Mode::AlertDiskThreshold => {
+                    let popup = Paragraph::new(vec![Line::from(vec![Span::from(
+                        &self.state.alert_disk_threshold,
+                    )])])
+                    .block(
+                        Block::new()
+                            .borders(Borders::ALL)
+                            .title("Alert Disk Usage Threshold")
+                            .style(block_style),
+                    );
+                    f.render_widget(Clear, area);
+                    f.render_widget(popup, area);
+                }
+                Mode::AlertTemperatureThreshold => {
+                    let popup = Paragraph::new(vec![Line::from(vec![Span::from(
+                        &self.state.alert_temperature_threshold,
+                    )])])
+                    .block(
+                        Block::new()
+                            .borders(Borders::ALL)
+                            .title("Alert Temperature Threshold (°C)")
+                            .style(block_style),
+                    );
+                    f.render_widget(Clear, area);
+                    f.render_widget(popup, area);
+                }
+                Mode::AlertBatteryThreshold => {
+                    let popup = Paragraph::new(vec![Line::from(vec![Span::from(
+                        &self.state.alert_battery_threshold,
+                    )])])
+                    .block(
+                        Block::new()
+                            .borders(Borders::ALL)
+                            .title("Alert Battery Charge Threshold (%)")
+                            .style(block_style),
+                    );
+                    f.render_widget(Clear, area);
+                    f.render_widget(popup, area);
+                }
+                Mode::AlertPressureThreshold => {
+                    let popup = Paragraph::new(vec![Line::from(vec![Span::from(
+                        &self.state.alert_pressure_threshold,
+                    )])])
+                    .block(
+                        Block::new()
+                            .borders(Borders::ALL)
+                            .title("Alert Pressure Threshold (%)")
+                            .style(block_style),
+                    );
+                    f.render_widget(Clear, area);
+                    f.render_widget(popup, area);
+                }
+                Mode::UnitConfirm => {
+                    let question = match &self.state.unit_table_state.pending {
+                        Some((unit::UnitAction::Restart, name)) => format!("Restart {}?", name),
+                        Some((unit::UnitAction::Stop, name)) => format!("Stop {}?", name),
+                        None => String::new(),
+                    };
+                    let area = popup_area(f.area(), 40, 10);
+                    let popup = Paragraph::new(vec![Line::from(vec![Span::from(question)])]).block(
+                        Block::new()
+                            .borders(Borders::ALL)
+                            .title("Confirm (y/n)")
+                            .style(block_style),
+                    );
+                    f.render_widget(Clear, area);
+                    f.render_widget(popup, area);
+                }
+                Mode::SocketView => {
+                    let area = popup_area(f.area(), 80, 60);
+                    f.render_widget(Clear, area);
+                    f.render_stateful_widget(
+                        &self.widgets.socket_table,
+                        area,
+                        &mut self.state.socket_table_state.state,
+                    );
+                }
+                Mode::Inspector | Mode::InspectorSearch => {
+                    let area = popup_area(f.area(), 90, 70);
+                    f.render_widget(Clear, area);
+                    f.render_stateful_widget(
+                        &self.widgets.inspector,
+                        area,
+                        &mut self.state.inspector_state.state,
+                    );
+                }
+                Mode::ThreadView | Mode::ThreadFilter => {
+                    let area = popup_area(f.area(), 70, 60);
+                    f.render_widget(Clear, area);
+                    f.render_stateful_widget(
+                        &self.widgets.thread_table,
+                        area,
+                        &mut self.state.thread_table_state.state,
+                    );
+                    if let Mode::ThreadFilter = self.state.mode {
+                        let filter_area = popup_area(f.area(), 20, 10);
+                        let popup = Paragraph::new(vec![Line::from(vec![Span::from(
+                            &self.state.thread_table_state.filter,
+                        )])])
+                        .block(
+                            Block::new()
+                                .borders(Borders::ALL)
+                                .title("Thread Filter")
+                                .style(block_style),
+                        );
+                        f.render_widget(Clear, filter_area);
+                        f.render_widget(popup, filter_area);
+                    }
+                }
+                Mode::StackView => {
+                    let area = popup_area(f.area(), 80, 80);
+                    f.render_widget(Clear, area);
+                    f.render_stateful_widget(
+                        &self.widgets.stack_view,
+                        area,
+                        &mut self.state.stack_view_state.state,
+                    );
+                }
                 _ => {}
             }
         })?;
@@ -212,3 +510,287 @@ impl PTop {
         ratatui::restore();
     }
 }
+
+fn draw_overview(f: &mut Frame, area: Rect, widgets: &Widgets, state: &mut AppState) {
+    // Bottom half layout
+    let bottom_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+        .split(area);
+
+    // Bottom-left nested layout
+    let bottom_left_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage(25),
+                Constraint::Percentage(25),
+                Constraint::Percentage(20),
+                Constraint::Percentage(30),
+            ]
+            .as_ref(),
+        )
+        .split(bottom_chunks[0]);
+
+    // Bottom-right nested layout
+    let bottom_right_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
+        .split(bottom_chunks[1]);
+
+    // Bottom-left top: CPU Histogram
+    f.render_widget(&widgets.cpu, bottom_left_chunks[0]);
+
+    // Bottom-left second: Memory Histogram
+    f.render_widget(&widgets.memory, bottom_left_chunks[1]);
+
+    // Bottom-left third: Disk I/O Histogram
+    f.render_widget(&widgets.disk_io, bottom_left_chunks[2]);
+
+    // Bottom-left bottom: CPU Per Core
+    f.render_widget(&widgets.cpu_per_core, bottom_left_chunks[3]);
+
+    // Bottom-right top: Process Table
+    f.render_stateful_widget(
+        &widgets.process_table,
+        bottom_right_chunks[0],
+        &mut state.process_table_state.state,
+    );
+
+    // Bottom-right bottom: Process Events, next to the supervised command's output
+    let events_area = match &widgets.output {
+        Some(output) => {
+            let [events_area, output_area] =
+                Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .areas(bottom_right_chunks[1]);
+            f.render_widget(output, output_area);
+            events_area
+        }
+        None => bottom_right_chunks[1],
+    };
+    f.render_stateful_widget(
+        &widgets.event_log,
+        events_area,
+        &mut state.event_log_state.state,
+    );
+}
+
+fn draw_cpu(f: &mut Frame, area: Rect, widgets: &Widgets, state: &mut AppState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage(50),
+                Constraint::Min(0),
+                Constraint::Length(6),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+
+    let top_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(chunks[0]);
+
+    let middle_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage(35),
+                Constraint::Percentage(35),
+                Constraint::Percentage(30),
+            ]
+            .as_ref(),
+        )
+        .split(chunks[1]);
+
+    // Top-left: CPU Time Breakdown
+    f.render_widget(&widgets.cpu_breakdown, top_chunks[0]);
+
+    // Top-right: CPU Per Core History
+    f.render_widget(&widgets.cpu_heatmap, top_chunks[1]);
+
+    // Middle-left: Load Average
+    f.render_widget(&widgets.load, middle_chunks[0]);
+
+    // Middle: Pressure Histogram
+    f.render_widget(&widgets.pressure, middle_chunks[1]);
+
+    // Middle-right: CPU Frequency
+    f.render_widget(&widgets.cpu_frequency, middle_chunks[2]);
+
+    // Bottom: Pressure Stall Information
+    f.render_stateful_widget(
+        &widgets.pressure_table,
+        chunks[2],
+        &mut state.pressure_table_state.state,
+    );
+}
+
+fn draw_memory(f: &mut Frame, area: Rect, widgets: &Widgets) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(10), Constraint::Min(0)].as_ref())
+        .split(area);
+
+    let bottom_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(chunks[1]);
+
+    // Top: Memory Breakdown
+    f.render_widget(&widgets.memory_breakdown, chunks[0]);
+
+    // Bottom-left: Memory Breakdown Histogram
+    f.render_widget(&widgets.memory_breakdown_history, bottom_chunks[0]);
+
+    // Bottom-right: Memory Histogram
+    f.render_widget(&widgets.memory, bottom_chunks[1]);
+}
+
+fn draw_storage(f: &mut Frame, area: Rect, widgets: &Widgets, state: &mut AppState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+        .split(area);
+
+    // Top: Filesystems
+    f.render_stateful_widget(
+        &widgets.filesystem_table,
+        chunks[0],
+        &mut state.filesystem_table_state.state,
+    );
+
+    // Bottom: Disk I/O Histogram
+    f.render_widget(&widgets.disk_io, chunks[1]);
+}
+
+fn draw_network(f: &mut Frame, area: Rect, widgets: &Widgets, state: &mut AppState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(area);
+
+    // Top: Network Histogram
+    f.render_widget(&widgets.network, chunks[0]);
+
+    // Bottom: Network Interfaces
+    f.render_stateful_widget(
+        &widgets.network_table,
+        chunks[1],
+        &mut state.network_table_state.state,
+    );
+}
+
+fn draw_containers(f: &mut Frame, area: Rect, widgets: &Widgets, state: &mut AppState) {
+    if !widgets.container_table.is_available() {
+        let message = Paragraph::new("No Docker or Podman socket found").block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title("Containers")
+                .style(Style::default()),
+        );
+        f.render_widget(message, area);
+        return;
+    }
+
+    f.render_stateful_widget(
+        &widgets.container_table,
+        area,
+        &mut state.container_table_state.state,
+    );
+}
+
+fn draw_units(f: &mut Frame, area: Rect, widgets: &Widgets, state: &mut AppState) {
+    if !widgets.unit_table.is_available() {
+        let message = Paragraph::new("systemd is not running").block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title("Units")
+                .style(Style::default()),
+        );
+        f.render_widget(message, area);
+        return;
+    }
+
+    f.render_stateful_widget(&widgets.unit_table, area, &mut state.unit_table_state.state);
+}
+
+fn draw_users(f: &mut Frame, area: Rect, widgets: &Widgets, state: &mut AppState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(area);
+
+    f.render_widget(&widgets.session_table, chunks[0]);
+    f.render_stateful_widget(
+        &widgets.user_table,
+        chunks[1],
+        &mut state.user_table_state.state,
+    );
+}
+
+fn draw_hardware(f: &mut Frame, area: Rect, widgets: &Widgets, state: &mut AppState) {
+    // The power row is left out on machines without batteries or RAPL counters
+    let (area, power_area) = if widgets.power_table.is_available() {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+            .split(area);
+        (chunks[0], Some(chunks[1]))
+    } else {
+        (area, None)
+    };
+
+    if let Some(power_area) = power_area {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(power_area);
+        f.render_widget(&widgets.power, chunks[0]);
+        f.render_widget(&widgets.power_table, chunks[1]);
+    }
+
+    // Virtual machines and containers often expose no sensors at all
+    if widgets.sensor_table.is_empty() {
+        let message = Paragraph::new("No temperature sensors found").block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title("Sensors")
+                .style(Style::default()),
+        );
+        f.render_widget(message, area);
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(area);
+
+    // Top: Temperature Histogram
+    f.render_widget(&widgets.sensors, chunks[0]);
+
+    // Bottom: Sensors
+    f.render_stateful_widget(
+        &widgets.sensor_table,
+        chunks[1],
+        &mut state.sensor_table_state.state,
+    );
+}
+
+fn draw_history(f: &mut Frame, area: Rect, widgets: &Widgets, state: &mut AppState) {
+    if !widgets.history.is_available() {
+        let message = Paragraph::new("History is off, start ptop with --history <dir>").block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title("History")
+                .style(Style::default()),
+        );
+        f.render_widget(message, area);
+        return;
+    }
+
+    f.render_stateful_widget(&widgets.history, area, &mut state.history_state.state);
+}
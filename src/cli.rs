@@ -0,0 +1,221 @@
+use crate::logger;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "Usage: ptop [options] [-- <command> [args...]]
+
+Options:
+  -- <command> [args...]         Launch a command and supervise it
+  --metrics <address>            Serve Prometheus metrics on http://<address>/metrics
+  --metrics-processes <pattern>  Export the processes whose name contains <pattern>
+  --log <path>                   Append the system metrics and top processes to <path> every tick
+  --log-format <csv|jsonl>       Format of the log (default: jsonl for .jsonl/.json paths, else csv)
+  --log-fields <fields>          Comma separated fields to log (default: all), out of
+                                 cpu, memory, swap, load, disk_read, disk_write, net_rx, net_tx,
+                                 process.name, process.user, process.cpu, process.memory,
+                                 process.disk_read, process.disk_write
+  --log-top <n>                  Number of processes to log, in the process table order (default: 5)
+  --log-max-size <size>          Rotate the log before it grows past <size>, e.g. 10M
+  --log-max-age <duration>       Rotate the log after writing to it for <duration>, e.g. 1h
+  --log-keep <n>                 Number of rotated logs to keep (default: 5)
+  --history <dir>                Keep a day of history in <dir>, to browse on the History page
+  --headless                     Run without the UI, e.g. only to serve metrics or log
+  --agent <address>              Serve this host to ptop clients on <address>, without the UI
+  --connect <address>            Show the host of the agent at <address> instead of this one
+  --token-file <path>            Secret shared by the agent and its clients, required by both
+  --tls-cert <path>              Certificate chain of the agent in PEM, to serve clients over TLS
+  --tls-key <path>               Private key of the agent in PEM
+  --tls-ca <path>                Certificate in PEM to verify the agent with, to connect over TLS
+  -h, --help                     Show this help";
+
+pub struct Args {
+    // Command to launch and supervise, everything after "--"
+    pub command: Vec<String>,
+    // Address to serve metrics on, e.g. "127.0.0.1:9184"
+    pub metrics_address: Option<String>,
+    pub metrics_processes: Option<String>,
+    pub log: Option<logger::Config>,
+    // Directory of the history store
+    pub history: Option<PathBuf>,
+    pub headless: bool,
+    // Address to serve agent clients on, e.g. "0.0.0.0:9185"
+    pub agent: Option<String>,
+    // Address of the agent to connect to, e.g. "db-1.example.com:9185"
+    pub connect: Option<String>,
+    pub token_file: Option<PathBuf>,
+    pub tls_cert: Option<PathBuf>,
+    pub tls_key: Option<PathBuf>,
+    pub tls_ca: Option<PathBuf>,
+}
+
+impl Args {
+    pub fn parse() -> Result<Args, String> {
+        Args::parse_from(std::env::args().skip(1))
+    }
+
+    pub fn parse_from(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+        let mut parsed = Args {
+            command: Vec::new(),
+            metrics_address: None,
+            metrics_processes: None,
+            log: None,
+            history: None,
+            headless: false,
+            agent: None,
+            connect: None,
+            token_file: None,
+            tls_cert: None,
+            tls_key: None,
+            tls_ca: None,
+        };
+        // The log options may come before --log itself
+        let mut log_path = None;
+        let mut log_options = Vec::new();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--" => {
+                    parsed.command = args.by_ref().collect();
+                    if parsed.command.is_empty() {
+                        return Err(format!("missing command after \"--\"\n\n{}", USAGE));
+                    }
+                }
+                "--metrics" => parsed.metrics_address = Some(value(&arg, args.next())?),
+                "--metrics-processes" => parsed.metrics_processes = Some(value(&arg, args.next())?),
+                "--log" => log_path = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--log-format" | "--log-fields" | "--log-top" | "--log-max-size"
+                | "--log-max-age" | "--log-keep" => {
+                    let value = value(&arg, args.next())?;
+                    log_options.push((arg, value));
+                }
+                "--history" => parsed.history = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--headless" => parsed.headless = true,
+                "--agent" => parsed.agent = Some(value(&arg, args.next())?),
+                "--connect" => parsed.connect = Some(value(&arg, args.next())?),
+                "--token-file" => {
+                    parsed.token_file = Some(PathBuf::from(value(&arg, args.next())?))
+                }
+                "--tls-cert" => parsed.tls_cert = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--tls-key" => parsed.tls_key = Some(PathBuf::from(value(&arg, args.next())?)),
+                "--tls-ca" => parsed.tls_ca = Some(PathBuf::from(value(&arg, args.next())?)),
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                _ => return Err(format!("unknown argument \"{}\"\n\n{}", arg, USAGE)),
+            }
+        }
+
+        match log_path {
+            Some(path) => {
+                let mut config = logger::Config::new(path);
+                for (option, value) in log_options {
+                    apply_log_option(&mut config, &option, &value).map_err(|e| {
+                        format!("invalid {} \"{}\": {}\n\n{}", option, value, e, USAGE)
+                    })?;
+                }
+                parsed.log = Some(config);
+            }
+            None if !log_options.is_empty() => {
+                return Err(format!("{} requires --log\n\n{}", log_options[0].0, USAGE));
+            }
+            None => {}
+        }
+
+        // Without the UI, the metrics, the log, the history and the agent are
+        // the only way to see anything
+        if parsed.headless
+            && parsed.metrics_address.is_none()
+            && parsed.log.is_none()
+            && parsed.history.is_none()
+            && parsed.agent.is_none()
+        {
+            return Err(format!(
+                "--headless requires --metrics, --log, --history or --agent\n\n{}",
+                USAGE
+            ));
+        }
+        check_remote_options(&parsed).map_err(|e| format!("{}\n\n{}", e, USAGE))?;
+        Ok(parsed)
+    }
+}
+
+fn check_remote_options(args: &Args) -> Result<(), &'static str> {
+    if args.agent.is_some() && args.connect.is_some() {
+        return Err("--agent and --connect cannot be used together");
+    }
+    if (args.agent.is_some() || args.connect.is_some()) && args.token_file.is_none() {
+        return Err("--agent and --connect require --token-file");
+    }
+    if args.connect.is_some() && !args.command.is_empty() {
+        return Err("a command cannot be supervised on the host of an agent");
+    }
+    if args.tls_cert.is_some() != args.tls_key.is_some() {
+        return Err("--tls-cert and --tls-key must be given together");
+    }
+    if args.tls_cert.is_some() && args.agent.is_none() {
+        return Err("--tls-cert requires --agent");
+    }
+    if args.tls_ca.is_some() && args.connect.is_none() {
+        return Err("--tls-ca requires --connect");
+    }
+    if args.token_file.is_some() && args.agent.is_none() && args.connect.is_none() {
+        return Err("--token-file requires --agent or --connect");
+    }
+    Ok(())
+}
+
+fn value(option: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("missing value for \"{}\"\n\n{}", option, USAGE))
+}
+
+fn apply_log_option(config: &mut logger::Config, option: &str, value: &str) -> Result<(), String> {
+    match option {
+        "--log-format" => config.format = value.parse().map_err(|_| "expected csv or jsonl")?,
+        "--log-fields" => {
+            config.fields = value
+                .split(',')
+                .map(|field| {
+                    field
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("unknown field {}", field))
+                })
+                .collect::<Result<_, _>>()?
+        }
+        "--log-top" => config.top = value.parse().map_err(|_| "expected a number")?,
+        "--log-max-size" => config.max_size = Some(parse_size(value)?),
+        "--log-max-age" => {
+            config.max_age = Some(humantime::parse_duration(value).map_err(|e| e.to_string())?)
+        }
+        "--log-keep" => config.keep = value.parse().map_err(|_| "expected a number")?,
+        _ => {}
+    }
+    Ok(())
+}
+
+// Parses a size in bytes with an optional binary K, M or G suffix, e.g. "10M"
+fn parse_size(value: &str) -> Result<u64, String> {
+    let (number, multiplier) = match value.char_indices().last() {
+        Some((i, 'K' | 'k')) => (&value[..i], 1024),
+        Some((i, 'M' | 'm')) => (&value[..i], 1024 * 1024),
+        Some((i, 'G' | 'g')) => (&value[..i], 1024 * 1024 * 1024),
+        _ => (value, 1),
+    };
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .ok_or_else(|| "expected a size such as 10M".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("10k"), Ok(10 * 1024));
+        assert_eq!(parse_size("10M"), Ok(10 * 1024 * 1024));
+        assert_eq!(parse_size("2G"), Ok(2 * 1024 * 1024 * 1024));
+        for size in ["", "M", "-1M", "1.5M", "10T", "18446744073709551615K"] {
+            assert!(parse_size(size).is_err(), "{}", size);
+        }
+    }
+}
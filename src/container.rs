@@ -0,0 +1,228 @@
+pub mod json;
+
+use std::env;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// Requests to the container runtime are made on every refresh while the
+// Containers page is shown, so a stuck daemon must not hold up the UI for long
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+// Largest response body accepted, far more than a listing of thousands of
+// containers takes, so that a misbehaving daemon cannot exhaust memory
+const MAX_BODY: usize = 16 * 1024 * 1024;
+
+#[derive(Clone)]
+pub struct Container {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    // e.g. "running" or "exited"
+    pub state: String,
+    // e.g. "Up 2 hours"
+    pub status: String,
+}
+
+// Client of the Docker-compatible Engine API, which Podman also serves
+pub struct Client {
+    socket: PathBuf,
+}
+
+impl Client {
+    pub fn new(socket: &Path) -> Self {
+        Client {
+            socket: socket.to_path_buf(),
+        }
+    }
+
+    // Finds the API socket from DOCKER_HOST or the usual Docker and Podman locations
+    pub fn discover() -> Option<Self> {
+        let mut candidates = Vec::new();
+        if let Some(path) = env::var("DOCKER_HOST")
+            .ok()
+            .and_then(|host| host.strip_prefix("unix://").map(PathBuf::from))
+        {
+            candidates.push(path);
+        }
+        candidates.push(PathBuf::from("/var/run/docker.sock"));
+        if let Ok(runtime_dir) = env::var("XDG_RUNTIME_DIR") {
+            candidates.push(Path::new(&runtime_dir).join("podman/podman.sock"));
+        }
+        candidates.push(PathBuf::from("/run/podman/podman.sock"));
+        candidates
+            .into_iter()
+            .find(|path| path.exists())
+            .map(|path| Client::new(&path))
+    }
+
+    pub fn list_containers(&self) -> io::Result<Vec<Container>> {
+        let body = self.get("/containers/json?all=true")?;
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "unexpected response");
+        let value = json::parse(&body).ok_or_else(invalid)?;
+        let containers = value.as_array().ok_or_else(invalid)?;
+        Ok(containers
+            .iter()
+            .filter_map(|c| {
+                let field = |key: &str| c.get(key).and_then(|v| v.as_str()).unwrap_or_default();
+                Some(Container {
+                    id: c.get("Id")?.as_str()?.to_string(),
+                    // Names carry a leading slash, e.g. "/web"
+                    name: c
+                        .get("Names")
+                        .and_then(|names| names.as_array()?.first()?.as_str())
+                        .map(|name| name.trim_start_matches('/').to_string())
+                        .unwrap_or_default(),
+                    image: field("Image").to_string(),
+                    state: field("State").to_string(),
+                    status: field("Status").to_string(),
+                })
+            })
+            .collect())
+    }
+
+    // Sends a GET request and returns the body of a successful response
+    fn get(&self, path: &str) -> io::Result<String> {
+        let mut stream = UnixStream::connect(&self.socket)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        // HTTP/1.0 makes the daemon close the connection after the response
+        let request = format!("GET {} HTTP/1.0\r\nHost: localhost\r\n\r\n", path);
+        stream.write_all(request.as_bytes())?;
+
+        let mut reader = BufReader::new(stream);
+        let mut status = String::new();
+        reader.read_line(&mut status)?;
+        if status.split_whitespace().nth(1) != Some("200") {
+            return Err(io::Error::other(format!(
+                "request failed: {}",
+                status.trim()
+            )));
+        }
+        let mut chunked = false;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                chunked |= name.eq_ignore_ascii_case("transfer-encoding")
+                    && value.trim().eq_ignore_ascii_case("chunked");
+            }
+        }
+
+        let mut body = String::new();
+        if chunked {
+            read_chunked(&mut reader, &mut body)?;
+        } else {
+            reader.take(MAX_BODY as u64 + 1).read_to_string(&mut body)?;
+            if body.len() > MAX_BODY {
+                return Err(too_large());
+            }
+        }
+        Ok(body)
+    }
+}
+
+// Decodes a chunked body, which some daemons send even to HTTP/1.0 clients
+fn read_chunked(reader: &mut impl BufRead, body: &mut String) -> io::Result<()> {
+    loop {
+        let mut size = String::new();
+        reader.read_line(&mut size)?;
+        let size = size.split(';').next().unwrap_or_default();
+        let size = usize::from_str_radix(size.trim(), 16)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        if size == 0 {
+            return Ok(());
+        }
+        // The size comes from the daemon, check it before allocating the chunk
+        if size > MAX_BODY.saturating_sub(body.len()) {
+            return Err(too_large());
+        }
+        let mut chunk = vec![0; size];
+        reader.read_exact(&mut chunk)?;
+        body.push_str(&String::from_utf8_lossy(&chunk));
+        // Each chunk ends with a CRLF
+        reader.read_line(&mut String::new())?;
+    }
+}
+
+fn too_large() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "response too large")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+
+    // Answers a single request on a socket in the fixture with a canned response
+    fn serve(fixture: &Fixture, response: impl Into<String>) -> Client {
+        let socket = fixture.path().join("docker.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+        let response = response.into();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 && line != "\r\n" {
+                line.clear();
+            }
+            (&stream).write_all(response.as_bytes()).unwrap();
+        });
+        Client::new(&socket)
+    }
+
+    const LISTING: &str = r#"[{"Id":"4f0c9e1a2b3c4d5e","Names":["/web"],"Image":"nginx:1.27","State":"running","Status":"Up 2 hours"},{"Id":"9a8b7c6d5e4f","Names":["/db"],"Image":"postgres","State":"exited","Status":"Exited (0) 3 days ago"}]"#;
+
+    #[test]
+    fn lists_containers() {
+        let fixture = Fixture::new();
+        let response = format!(
+            "HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n{}",
+            LISTING
+        );
+        let client = serve(&fixture, response);
+        let containers = client.list_containers().unwrap();
+        assert_eq!(containers.len(), 2);
+        assert_eq!(containers[0].id, "4f0c9e1a2b3c4d5e");
+        assert_eq!(containers[0].name, "web");
+        assert_eq!(containers[0].image, "nginx:1.27");
+        assert_eq!(containers[0].state, "running");
+        assert_eq!(containers[1].status, "Exited (0) 3 days ago");
+    }
+
+    #[test]
+    fn decodes_chunked_responses() {
+        let fixture = Fixture::new();
+        let client = serve(
+            &fixture,
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+             a\r\n[{\"Id\":\"ab\r\n\
+             f;ext=1\r\nc\",\"Names\":[]}]\r\n\
+             0\r\n\r\n",
+        );
+        let containers = client.list_containers().unwrap();
+        assert_eq!(containers.len(), 1);
+        assert_eq!(containers[0].id, "abc");
+        assert_eq!(containers[0].name, "");
+    }
+
+    #[test]
+    fn rejects_failures_and_oversized_chunks() {
+        let fixture = Fixture::new();
+        let client = serve(&fixture, "HTTP/1.0 500 Internal Server Error\r\n\r\n");
+        assert!(client.list_containers().is_err());
+
+        let fixture = Fixture::new();
+        let client = serve(
+            &fixture,
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nffffffffffff\r\n",
+        );
+        let result = client.list_containers();
+        assert!(matches!(result, Err(e) if e.kind() == io::ErrorKind::InvalidData));
+    }
+}
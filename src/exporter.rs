@@ -0,0 +1,361 @@
+use crate::models::alert::AlertTable;
+use crate::system_monitor;
+use std::fmt::Write as _;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// Scrapers that stop sending halfway must not hold up the other scrapes
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+// Latest rendering of the metrics, refreshed by the main loop on every update
+// and served as is to every scrape in between
+pub type MetricsBuffer = Arc<Mutex<String>>;
+
+// Serves GET /metrics on the given address, e.g. "127.0.0.1:9184", from a
+// background thread
+pub fn serve(address: &str) -> io::Result<MetricsBuffer> {
+    Ok(serve_listener(TcpListener::bind(address)?))
+}
+
+fn serve_listener(listener: TcpListener) -> MetricsBuffer {
+    let metrics: MetricsBuffer = Arc::new(Mutex::new(String::new()));
+    let buffer = metrics.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // A scraper that goes away mid-request is not worth reporting
+            let _ = respond(stream, &buffer);
+        }
+    });
+    metrics
+}
+
+fn respond(stream: TcpStream, metrics: &MetricsBuffer) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let mut reader = BufReader::new(stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // Skip the headers, the body of a GET is empty
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path)) if path.split('?').next() == Some("/metrics") => (
+            "200 OK",
+            "text/plain; version=0.0.4; charset=utf-8",
+            metrics.lock().unwrap().clone(),
+        ),
+        (Some("GET"), Some(_)) => (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "Not Found\n".to_string(),
+        ),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "Method Not Allowed\n".to_string(),
+        ),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    reader.get_mut().write_all(response.as_bytes())
+}
+
+pub fn publish(metrics: &MetricsBuffer, rendered: String) {
+    *metrics.lock().unwrap() = rendered;
+}
+
+// Renders the host metrics, the CPU and memory of the processes whose name
+// contains process_filter, and the state of the alerts in the Prometheus text
+// exposition format. Every metric is a gauge.
+pub fn render(
+    monitor: &system_monitor::Monitor,
+    alerts: &AlertTable,
+    process_filter: Option<&str>,
+) -> String {
+    let mut out = String::new();
+
+    family(
+        &mut out,
+        "ptop_cpu_usage_percent",
+        "CPU usage over all cores.",
+    );
+    sample(
+        &mut out,
+        "ptop_cpu_usage_percent",
+        &[],
+        monitor.get_global_cpu_usage() as f64,
+    );
+
+    family(
+        &mut out,
+        "ptop_cpu_core_usage_percent",
+        "CPU usage of each core.",
+    );
+    for (core, usage) in monitor.get_per_cpu_usage().into_iter().enumerate() {
+        let core = core.to_string();
+        sample(
+            &mut out,
+            "ptop_cpu_core_usage_percent",
+            &[("core", &core)],
+            usage as f64,
+        );
+    }
+
+    if let Some(breakdown) = monitor.get_cpu_breakdown() {
+        family(
+            &mut out,
+            "ptop_cpu_time_percent",
+            "Share of CPU time spent in each state over all cores.",
+        );
+        for (mode, share) in [
+            ("user", breakdown.user),
+            ("nice", breakdown.nice),
+            ("system", breakdown.system),
+            ("idle", breakdown.idle),
+            ("iowait", breakdown.iowait),
+            ("irq", breakdown.irq),
+            ("softirq", breakdown.softirq),
+            ("steal", breakdown.steal),
+            ("guest", breakdown.guest),
+        ] {
+            sample(&mut out, "ptop_cpu_time_percent", &[("mode", mode)], share);
+        }
+    }
+
+    if let Some(load) = monitor.get_load_average() {
+        family(&mut out, "ptop_load_average", "System load average.");
+        for (period, value) in [("1m", load.one), ("5m", load.five), ("15m", load.fifteen)] {
+            sample(&mut out, "ptop_load_average", &[("period", period)], value);
+        }
+    }
+
+    let (total_memory, total_swap) = monitor.get_total_memory();
+    let (used_memory, used_swap) = monitor.get_used_memory();
+    for (name, help, value) in [
+        ("ptop_memory_total_bytes", "Total memory.", total_memory),
+        ("ptop_memory_used_bytes", "Memory in use.", used_memory),
+        ("ptop_swap_total_bytes", "Total swap space.", total_swap),
+        ("ptop_swap_used_bytes", "Swap space in use.", used_swap),
+    ] {
+        family(&mut out, name, help);
+        sample(&mut out, name, &[], value as f64);
+    }
+
+    // Exporting every process would make the number of series unbounded
+    if let Some(filter) = process_filter {
+        let processes: Vec<_> = monitor
+            .get_processes()
+            .into_iter()
+            .filter(|p| !p.thread && p.name.contains(filter))
+            .collect();
+        family(
+            &mut out,
+            "ptop_process_cpu_usage_percent",
+            "CPU usage of a process, 100 per fully used core.",
+        );
+        for p in &processes {
+            let pid = p.pid.to_string();
+            sample(
+                &mut out,
+                "ptop_process_cpu_usage_percent",
+                &[("pid", &pid), ("name", &p.name)],
+                p.cpu_usage as f64,
+            );
+        }
+        family(
+            &mut out,
+            "ptop_process_resident_memory_bytes",
+            "Resident memory of a process.",
+        );
+        for p in &processes {
+            let pid = p.pid.to_string();
+            sample(
+                &mut out,
+                "ptop_process_resident_memory_bytes",
+                &[("pid", &pid), ("name", &p.name)],
+                p.memory as f64,
+            );
+        }
+    }
+
+    family(
+        &mut out,
+        "ptop_alert_triggered",
+        "Whether an alert has triggered, 1 if so.",
+    );
+    for (pid, name, condition, triggered) in alerts.get_states() {
+        let pid = pid.map(|pid| pid.to_string()).unwrap_or_default();
+        // Alerts on the same target and condition differ by their threshold
+        let threshold = condition
+            .threshold()
+            .map(|t| t.to_string())
+            .unwrap_or_default();
+        sample(
+            &mut out,
+            "ptop_alert_triggered",
+            &[
+                ("pid", &pid),
+                ("name", name),
+                ("condition", &condition.to_string()),
+                ("threshold", &threshold),
+            ],
+            if triggered { 1.0 } else { 0.0 },
+        );
+    }
+
+    out
+}
+
+fn family(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: f64) {
+    out.push_str(name);
+    if !labels.is_empty() {
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|(key, value)| format!("{}=\"{}\"", key, escape(value)))
+            .collect();
+        let _ = write!(out, "{{{}}}", labels.join(","));
+    }
+    // Infinities are spelled +Inf and -Inf in the exposition format
+    if value.is_infinite() {
+        let _ = writeln!(out, " {}Inf", if value > 0.0 { '+' } else { '-' });
+    } else {
+        let _ = writeln!(out, " {}", value);
+    }
+}
+
+// Label values escape backslashes, double quotes and line feeds
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::io::Read;
+    use std::net::SocketAddr;
+
+    fn get(address: SocketAddr, path: &str) -> (String, String) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        (head.to_string(), body.to_string())
+    }
+
+    // Checks a line such as `name{key="value",...} 1.5` and returns its series
+    fn parse_sample(line: &str) -> (String, String) {
+        let is_name = |s: &str| {
+            !s.is_empty()
+                && !s.starts_with(|c: char| c.is_ascii_digit())
+                && s.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+        };
+        let (series, value) = line.rsplit_once(' ').unwrap();
+        assert!(
+            value.parse::<f64>().is_ok() || ["+Inf", "-Inf", "NaN"].contains(&value),
+            "bad value in {}",
+            line
+        );
+        let Some((name, labels)) = series.split_once('{') else {
+            assert!(is_name(series), "bad name in {}", line);
+            return (series.to_string(), series.to_string());
+        };
+        assert!(is_name(name), "bad name in {}", line);
+        let mut labels = labels.strip_suffix('}').unwrap().chars().peekable();
+        let mut keys = HashSet::new();
+        while labels.peek().is_some() {
+            let key: String = labels.by_ref().take_while(|c| *c != '=').collect();
+            assert!(is_name(&key) && keys.insert(key), "bad label in {}", line);
+            assert_eq!(labels.next(), Some('"'), "unquoted label in {}", line);
+            loop {
+                match labels.next() {
+                    Some('"') => break,
+                    Some('\\') => assert!(matches!(labels.next(), Some('\\' | '"' | 'n'))),
+                    Some(c) => assert_ne!(c, '\n'),
+                    None => panic!("unterminated label in {}", line),
+                }
+            }
+            match labels.next() {
+                Some(',') | None => {}
+                Some(c) => panic!("unexpected {} in {}", c, line),
+            }
+        }
+        (name.to_string(), series.to_string())
+    }
+
+    #[test]
+    fn serves_valid_exposition_text() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let metrics = serve_listener(listener);
+
+        let mut monitor = system_monitor::Monitor::new();
+        monitor.update();
+        let mut alerts = AlertTable::new();
+        alerts.add_cpu_alert(1, "init \"quoted\"\n".to_string(), 50.0);
+        alerts.add_cpu_alert(1, "init \"quoted\"\n".to_string(), 80.0);
+        // Defined twice, reported once
+        alerts.add_cpu_alert(1, "init \"quoted\"\n".to_string(), 80.0);
+        alerts.add_exit_code_alert(1, "init \"quoted\"\n".to_string());
+        alerts.add_disk_usage_alert("/".to_string(), 90.0);
+        alerts.add_disk_usage_alert("/".to_string(), 90.0);
+        assert_eq!(alerts.take_added().len(), 4);
+        publish(&metrics, render(&monitor, &alerts, Some("")));
+
+        let (head, body) = get(address, "/metrics");
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(head.contains("Content-Type: text/plain; version=0.0.4"));
+        assert!(head.contains(&format!("Content-Length: {}\r\n", body.len())));
+
+        let mut family = None;
+        let mut families = HashSet::new();
+        let mut series = HashSet::new();
+        for line in body.lines() {
+            if let Some(help) = line.strip_prefix("# HELP ") {
+                let (name, _) = help.split_once(' ').unwrap();
+                assert!(families.insert(name.to_string()), "{} declared twice", name);
+                family = Some(name.to_string());
+            } else if let Some(kind) = line.strip_prefix("# TYPE ") {
+                assert_eq!(Some(kind), family.map(|f| f + " gauge").as_deref());
+                family = Some(kind.trim_end_matches(" gauge").to_string());
+            } else {
+                let (name, key) = parse_sample(line);
+                assert_eq!(Some(&name), family.as_ref(), "{} outside its family", line);
+                assert!(series.insert(key), "duplicate series {}", line);
+            }
+        }
+        assert!(families.contains("ptop_cpu_usage_percent"));
+        let alert_lines = body
+            .lines()
+            .filter(|l| l.starts_with("ptop_alert_triggered{"))
+            .count();
+        assert_eq!(alert_lines, 4);
+
+        let (head, _) = get(address, "/");
+        assert!(head.starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+}
@@ -0,0 +1,527 @@
+use crate::system_monitor;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// First line of every history file, bumped whenever the line format changes
+const HEADER: &str = "# ptop history 1";
+
+// Number of processes kept in each sample, by CPU usage
+const TOP: usize = 10;
+
+// Every sample is kept for an hour, then averaged per minute for a day and per
+// hour for a month
+const LEVELS: [(&str, u64, u64); 3] = [
+    ("1s", 1, 60 * 60),
+    ("1m", 60, 24 * 60 * 60),
+    ("1h", 60 * 60, 30 * 24 * 60 * 60),
+];
+
+#[derive(Clone)]
+pub struct ProcessSample {
+    pub pid: u32,
+    pub name: String,
+    // Percent, 100 per fully used core
+    pub cpu: f32,
+    // Resident bytes
+    pub memory: u64,
+}
+
+#[derive(Clone)]
+pub struct Sample {
+    // Seconds since the Unix epoch, the start of the period for averaged samples
+    pub time: u64,
+    // Percent of all cores, of the memory and of the swap space
+    pub cpu: f32,
+    pub memory: f32,
+    pub swap: f32,
+    // Top processes by CPU usage
+    pub processes: Vec<ProcessSample>,
+}
+
+impl Sample {
+    pub fn collect(monitor: &system_monitor::Monitor) -> Sample {
+        let (total_memory, total_swap) = monitor.get_total_memory();
+        let (used_memory, used_swap) = monitor.get_used_memory();
+        let mut processes: Vec<ProcessSample> = monitor
+            .get_processes()
+            .into_iter()
+            .filter(|p| !p.thread)
+            .map(|p| ProcessSample {
+                pid: p.pid,
+                name: p.name,
+                cpu: p.cpu_usage,
+                memory: p.memory,
+            })
+            .collect();
+        processes.sort_by(|a, b| b.cpu.total_cmp(&a.cpu));
+        processes.truncate(TOP);
+        Sample {
+            time: now(),
+            cpu: monitor.get_global_cpu_usage(),
+            memory: used_memory as f32 / total_memory.max(1) as f32 * 100.0,
+            swap: used_swap as f32 / total_swap.max(1) as f32 * 100.0,
+            processes,
+        }
+    }
+}
+
+// One resolution of the store, backed by its own append-only file
+struct Level {
+    // Seconds per sample and seconds kept
+    resolution: u64,
+    retention: u64,
+    path: PathBuf,
+    file: File,
+    oldest: Option<u64>,
+    latest: Option<u64>,
+    // Samples of the finer level within the period being filled, averaged
+    // into one sample of this level once a later period starts
+    pending: Vec<Sample>,
+    // The samples in the file, read on the first query and kept in step with
+    // it after that, so that browsing does not re-read the file
+    cached: Option<Vec<Sample>>,
+}
+
+impl Level {
+    fn append(&mut self, sample: &Sample) -> io::Result<()> {
+        self.file.write_all(format_line(sample).as_bytes())?;
+        self.oldest.get_or_insert(sample.time);
+        self.latest = Some(sample.time);
+        if let Some(cached) = &mut self.cached {
+            cached.push(sample.clone());
+        }
+        Ok(())
+    }
+
+    // Adds a sample of the finer level, returning the averaged sample of the
+    // previous period when this one starts a new period
+    fn add(&mut self, sample: Sample) -> io::Result<Option<Sample>> {
+        let period = sample.time - sample.time % self.resolution;
+        let completed = match self.pending.first() {
+            Some(first) if first.time - first.time % self.resolution != period => {
+                let average = average(&self.pending, self.resolution);
+                self.append(&average)?;
+                self.pending.clear();
+                Some(average)
+            }
+            _ => None,
+        };
+        self.pending.push(sample);
+        Ok(completed)
+    }
+
+    // Drops the samples past the retention once the file holds twice as much,
+    // so that it is only rewritten once per retention period
+    fn compact(&mut self, now: u64) -> io::Result<()> {
+        let cutoff = now.saturating_sub(self.retention);
+        if self
+            .oldest
+            .is_none_or(|oldest| oldest >= now.saturating_sub(2 * self.retention))
+        {
+            return Ok(());
+        }
+        let loaded = self.cached.is_some();
+        let mut samples = match self.cached.take() {
+            Some(cached) => cached,
+            None => read_samples(&self.path)?,
+        };
+        samples.retain(|s| s.time >= cutoff);
+        self.file = rewrite(&self.path, &samples)?;
+        self.oldest = samples.first().map(|s| s.time);
+        if loaded {
+            self.cached = Some(samples);
+        }
+        Ok(())
+    }
+
+    // The samples in the file, read from it on the first call only
+    fn samples(&mut self) -> io::Result<&[Sample]> {
+        if self.cached.is_none() {
+            self.cached = Some(read_samples(&self.path)?);
+        }
+        Ok(self.cached.as_deref().unwrap_or_default())
+    }
+}
+
+// Persists a sample per second to a directory, averaged down to a sample per
+// minute and a sample per hour, so that a day of history can be browsed across
+// restarts. The files are plain text, one sample per line.
+pub struct Store {
+    levels: Vec<Level>,
+}
+
+impl Store {
+    pub fn open(dir: &Path) -> io::Result<Store> {
+        fs::create_dir_all(dir)?;
+        let now = now();
+        let mut levels = Vec::new();
+        let mut loaded = Vec::new();
+        for (name, resolution, retention) in LEVELS {
+            let path = dir.join(format!("{}.tsv", name));
+            let mut samples = read_samples(&path)?;
+            let cutoff = now.saturating_sub(retention);
+            let file = if samples.first().is_some_and(|s| s.time < cutoff) {
+                samples.retain(|s| s.time >= cutoff);
+                rewrite(&path, &samples)?
+            } else {
+                open(&path)?
+            };
+            levels.push(Level {
+                resolution,
+                retention,
+                path,
+                file,
+                oldest: samples.first().map(|s| s.time),
+                latest: samples.last().map(|s| s.time),
+                pending: Vec::new(),
+                cached: None,
+            });
+            loaded.push(samples);
+        }
+
+        // Refill the periods that were being filled when ptop last exited, and
+        // average the ones that completed since
+        for i in 1..levels.len() {
+            let start = levels[i]
+                .latest
+                .map_or(0, |latest| latest + levels[i].resolution);
+            let finer: Vec<Sample> = loaded[i - 1]
+                .iter()
+                .filter(|s| s.time >= start)
+                .cloned()
+                .collect();
+            for sample in finer {
+                if let Some(average) = levels[i].add(sample)? {
+                    loaded[i].push(average);
+                }
+            }
+        }
+        Ok(Store { levels })
+    }
+
+    pub fn record(&mut self, sample: Sample) -> io::Result<()> {
+        // The UI refreshes on every key press, keep a sample per second at most
+        if self.levels[0]
+            .latest
+            .is_some_and(|latest| sample.time <= latest)
+        {
+            return Ok(());
+        }
+        let now = sample.time;
+        self.levels[0].append(&sample)?;
+        let mut finer = Some(sample);
+        for level in &mut self.levels[1..] {
+            match finer.take() {
+                Some(sample) => finer = level.add(sample)?,
+                None => break,
+            }
+        }
+        for level in &mut self.levels {
+            level.compact(now)?;
+        }
+        Ok(())
+    }
+
+    // Samples between from and to, in seconds since the Unix epoch, at the
+    // finest resolution still holding from. Returns the resolution in seconds
+    // along with the samples.
+    pub fn query(&mut self, from: u64, to: u64) -> io::Result<(u64, Vec<Sample>)> {
+        let now = now();
+        let index = self
+            .levels
+            .iter()
+            .position(|level| from >= now.saturating_sub(level.retention))
+            .unwrap_or(self.levels.len() - 1);
+        let level = &mut self.levels[index];
+        // Samples are appended in time order
+        let cached = level.samples()?;
+        let start = cached.partition_point(|s| s.time < from);
+        let end = cached.partition_point(|s| s.time <= to);
+        let mut samples = cached[start..end.max(start)].to_vec();
+        // The period being filled is not on disk yet
+        if !level.pending.is_empty() {
+            let average = average(&level.pending, level.resolution);
+            if average.time >= from && average.time <= to {
+                samples.push(average);
+            }
+        }
+        Ok((level.resolution, samples))
+    }
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+// Averages samples of the same period. A process missing from some of the
+// samples counts as idle in those, and keeps its peak memory.
+fn average(samples: &[Sample], resolution: u64) -> Sample {
+    let count = samples.len().max(1) as f32;
+    let mut processes: Vec<ProcessSample> = Vec::new();
+    for p in samples.iter().flat_map(|s| &s.processes) {
+        match processes
+            .iter_mut()
+            .find(|q| q.pid == p.pid && q.name == p.name)
+        {
+            Some(q) => {
+                q.cpu += p.cpu;
+                q.memory = q.memory.max(p.memory);
+            }
+            None => processes.push(p.clone()),
+        }
+    }
+    for p in &mut processes {
+        p.cpu /= count;
+    }
+    processes.sort_by(|a, b| b.cpu.total_cmp(&a.cpu));
+    processes.truncate(TOP);
+
+    let time = samples.first().map_or(0, |s| s.time);
+    Sample {
+        time: time - time % resolution,
+        cpu: samples.iter().map(|s| s.cpu).sum::<f32>() / count,
+        memory: samples.iter().map(|s| s.memory).sum::<f32>() / count,
+        swap: samples.iter().map(|s| s.swap).sum::<f32>() / count,
+        processes,
+    }
+}
+
+// Opens a history file for appending, writing the header into a new file
+fn open(path: &Path) -> io::Result<File> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .read(true)
+        .open(path)?;
+    if file.metadata()?.len() == 0 {
+        writeln!(file, "{}", HEADER)?;
+        return Ok(file);
+    }
+    // End a line cut short by a crash, or the next sample would be appended to it
+    let mut last = [0];
+    file.seek(SeekFrom::End(-1))?;
+    file.read_exact(&mut last)?;
+    if last[0] != b'\n' {
+        file.write_all(b"\n")?;
+    }
+    Ok(file)
+}
+
+// Replaces a history file with the given samples, through a temporary file so
+// that a crash never leaves it half written
+fn rewrite(path: &Path, samples: &[Sample]) -> io::Result<File> {
+    let temporary = path.with_extension("tmp");
+    let mut content = format!("{}\n", HEADER);
+    for sample in samples {
+        content.push_str(&format_line(sample));
+    }
+    fs::write(&temporary, content)?;
+    fs::rename(&temporary, path)?;
+    open(path)
+}
+
+fn read_samples(path: &Path) -> io::Result<Vec<Sample>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut samples = Vec::new();
+    for line in BufReader::new(file).lines() {
+        // A line cut short by a crash is skipped along with the comments
+        if let Some(sample) = parse_line(&line?) {
+            samples.push(sample);
+        }
+    }
+    Ok(samples)
+}
+
+// Tab separated time, CPU, memory and swap, followed by one
+// "pid,cpu,memory,name" column per process
+fn format_line(sample: &Sample) -> String {
+    let mut line = format!(
+        "{}\t{:.1}\t{:.1}\t{:.1}",
+        sample.time, sample.cpu, sample.memory, sample.swap
+    );
+    for p in &sample.processes {
+        let name = p.name.replace(['\t', '\n', '\r'], " ");
+        line.push_str(&format!("\t{},{:.1},{},{}", p.pid, p.cpu, p.memory, name));
+    }
+    line.push('\n');
+    line
+}
+
+fn parse_line(line: &str) -> Option<Sample> {
+    if line.starts_with('#') {
+        return None;
+    }
+    let mut columns = line.split('\t');
+    let time = columns.next()?.parse().ok()?;
+    let cpu = columns.next()?.parse().ok()?;
+    let memory = columns.next()?.parse().ok()?;
+    let swap = columns.next()?.parse().ok()?;
+    let processes = columns
+        .map(|column| {
+            // The name goes last, as it may contain commas
+            let mut parts = column.splitn(4, ',');
+            Some(ProcessSample {
+                pid: parts.next()?.parse().ok()?,
+                cpu: parts.next()?.parse().ok()?,
+                memory: parts.next()?.parse().ok()?,
+                name: parts.next()?.to_string(),
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(Sample {
+        time,
+        cpu,
+        memory,
+        swap,
+        processes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    fn sample(time: u64, cpu: f32) -> Sample {
+        Sample {
+            time,
+            cpu,
+            memory: 50.0,
+            swap: 0.0,
+            processes: vec![ProcessSample {
+                pid: 1,
+                name: "init, the first".to_string(),
+                cpu,
+                memory: 4096,
+            }],
+        }
+    }
+
+    // Start of a minute a while ago, well within the retention of every level
+    fn minutes_ago(minutes: u64) -> u64 {
+        let now = now();
+        now - now % 60 - minutes * 60
+    }
+
+    #[test]
+    fn round_trips_lines() {
+        let line = format_line(&sample(1700000000, 12.5));
+        assert_eq!(
+            line,
+            "1700000000\t12.5\t50.0\t0.0\t1,12.5,4096,init, the first\n"
+        );
+        let parsed = parse_line(line.trim_end()).unwrap();
+        assert_eq!(parsed.time, 1700000000);
+        assert_eq!(parsed.processes[0].name, "init, the first");
+        assert!(parse_line(HEADER).is_none());
+        assert!(parse_line("1700000000\t12.5").is_none());
+    }
+
+    #[test]
+    fn downsamples_per_minute() {
+        let fixture = Fixture::new();
+        let mut store = Store::open(fixture.path()).unwrap();
+        let start = minutes_ago(10);
+        // Two full minutes and the start of a third
+        for i in 0..150 {
+            store.record(sample(start + i, (i % 60) as f32)).unwrap();
+        }
+
+        let (resolution, samples) = store.query(start, start + 149).unwrap();
+        assert_eq!(resolution, 1);
+        assert_eq!(samples.len(), 150);
+
+        let (resolution, samples) = store.query(start - 2 * 60 * 60, start + 600).unwrap();
+        assert_eq!(resolution, 60);
+        let times: Vec<u64> = samples.iter().map(|s| s.time).collect();
+        assert_eq!(times, vec![start, start + 60, start + 120]);
+        assert_eq!(samples[0].cpu, 29.5);
+        assert_eq!(samples[0].processes[0].cpu, 29.5);
+        assert_eq!(samples[0].processes[0].memory, 4096);
+        // The minute being filled comes from the pending samples
+        assert_eq!(samples[2].cpu, 14.5);
+        assert_eq!(
+            read_samples(&fixture.path().join("1m.tsv")).unwrap().len(),
+            2
+        );
+
+        // Later samples show up in cached queries as well
+        store.record(sample(start + 180, 0.0)).unwrap();
+        let (_, samples) = store.query(start - 2 * 60 * 60, start + 600).unwrap();
+        assert_eq!(samples.len(), 4);
+        assert_eq!(samples[2].cpu, 14.5);
+        let (_, samples) = store.query(start + 149, start + 180).unwrap();
+        assert_eq!(samples.len(), 2);
+    }
+
+    #[test]
+    fn compacts_past_retention() {
+        let fixture = Fixture::new();
+        let mut store = Store::open(fixture.path()).unwrap();
+        let now = now();
+        store.record(sample(now - 3 * 60 * 60, 10.0)).unwrap();
+        store.record(sample(now - 30 * 60, 20.0)).unwrap();
+        // Read into the cache, which compaction must keep in step
+        assert_eq!(store.query(now - 3600, now).unwrap().1.len(), 1);
+        store.record(sample(now - 60, 30.0)).unwrap();
+
+        // Once the file held more than twice the hour kept, the older samples are dropped
+        let on_disk = read_samples(&fixture.path().join("1s.tsv")).unwrap();
+        let times: Vec<u64> = on_disk.iter().map(|s| s.time).collect();
+        assert_eq!(times, vec![now - 30 * 60, now - 60]);
+        let cached = store.levels[0].cached.as_ref().unwrap();
+        assert_eq!(cached.len(), 2);
+        assert_eq!(
+            fs::read_to_string(fixture.path().join("1s.tsv"))
+                .unwrap()
+                .lines()
+                .next(),
+            Some(HEADER)
+        );
+    }
+
+    #[test]
+    fn recovers_after_restart() {
+        let fixture = Fixture::new();
+        let start = minutes_ago(10);
+        let mut store = Store::open(fixture.path()).unwrap();
+        // A full minute and half of the next one
+        for i in 0..90 {
+            store.record(sample(start + i, 10.0)).unwrap();
+        }
+        drop(store);
+        // A line cut short by a crash
+        OpenOptions::new()
+            .append(true)
+            .open(fixture.path().join("1s.tsv"))
+            .unwrap()
+            .write_all(b"12345\t1")
+            .unwrap();
+
+        let mut store = Store::open(fixture.path()).unwrap();
+        // Samples older than the latest kept are ignored
+        store.record(sample(start + 30, 99.0)).unwrap();
+        for i in 90..120 {
+            store.record(sample(start + i, 20.0)).unwrap();
+        }
+        store.record(sample(start + 120, 0.0)).unwrap();
+
+        // The minute interrupted by the restart averages the samples from both runs
+        let minutes = read_samples(&fixture.path().join("1m.tsv")).unwrap();
+        assert_eq!(minutes.len(), 2);
+        assert_eq!(minutes[0].cpu, 10.0);
+        assert_eq!(minutes[1].time, start + 60);
+        assert_eq!(minutes[1].cpu, 15.0);
+        let (_, seconds) = store.query(start, start + 120).unwrap();
+        assert_eq!(seconds.len(), 121);
+    }
+}
@@ -0,0 +1,575 @@
+use crate::models::process::SortBy;
+use crate::system_monitor::{self, ProcessInfo};
+use humantime::format_rfc3339_seconds;
+use std::cmp::Ordering;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use strum::IntoEnumIterator;
+
+#[derive(Clone, Copy, PartialEq, strum::EnumString, strum::Display)]
+pub enum Format {
+    #[strum(serialize = "csv")]
+    Csv,
+    #[strum(serialize = "jsonl")]
+    JsonLines,
+}
+
+impl Format {
+    // JSON Lines for .jsonl and .json files, CSV otherwise
+    pub fn from_path(path: &Path) -> Format {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("jsonl") | Some("json") => Format::JsonLines,
+            _ => Format::Csv,
+        }
+    }
+}
+
+// Fields that can be logged. The system fields are logged once per tick, the
+// process fields once for each of the top processes, along with their PID.
+#[derive(Clone, Copy, PartialEq, strum::EnumString, strum::Display, strum::EnumIter)]
+pub enum Field {
+    // Percent over all cores
+    #[strum(serialize = "cpu")]
+    Cpu,
+    // Used bytes
+    #[strum(serialize = "memory")]
+    Memory,
+    #[strum(serialize = "swap")]
+    Swap,
+    // 1 minute load average
+    #[strum(serialize = "load")]
+    Load,
+    // Bytes per second
+    #[strum(serialize = "disk_read")]
+    DiskRead,
+    #[strum(serialize = "disk_write")]
+    DiskWrite,
+    #[strum(serialize = "net_rx")]
+    NetRx,
+    #[strum(serialize = "net_tx")]
+    NetTx,
+    #[strum(serialize = "process.name")]
+    ProcessName,
+    #[strum(serialize = "process.user")]
+    ProcessUser,
+    #[strum(serialize = "process.cpu")]
+    ProcessCpu,
+    #[strum(serialize = "process.memory")]
+    ProcessMemory,
+    #[strum(serialize = "process.disk_read")]
+    ProcessDiskRead,
+    #[strum(serialize = "process.disk_write")]
+    ProcessDiskWrite,
+}
+
+impl Field {
+    fn is_process(self) -> bool {
+        matches!(
+            self,
+            Field::ProcessName
+                | Field::ProcessUser
+                | Field::ProcessCpu
+                | Field::ProcessMemory
+                | Field::ProcessDiskRead
+                | Field::ProcessDiskWrite
+        )
+    }
+
+    // Name of the field within a process, e.g. "cpu" for "process.cpu"
+    fn key(self) -> String {
+        let name = self.to_string();
+        name.trim_start_matches("process.").to_string()
+    }
+}
+
+pub struct Config {
+    pub path: PathBuf,
+    pub format: Format,
+    pub fields: Vec<Field>,
+    // Number of processes logged on each tick
+    pub top: usize,
+    // The file is rotated when it would grow past max_size bytes, or once it
+    // has been written to for max_age
+    pub max_size: Option<u64>,
+    pub max_age: Option<Duration>,
+    // Number of rotated files kept next to the current one, as <path>.1 to <path>.<keep>
+    pub keep: usize,
+}
+
+impl Config {
+    pub fn new(path: PathBuf) -> Self {
+        Config {
+            format: Format::from_path(&path),
+            path,
+            fields: Field::iter().collect(),
+            top: 5,
+            max_size: None,
+            max_age: None,
+            keep: 5,
+        }
+    }
+}
+
+enum Value {
+    Text(String),
+    Number(String),
+}
+
+// Appends a record of the system and its top processes to a file on every tick
+pub struct Logger {
+    config: Config,
+    file: File,
+    size: u64,
+    opened: Instant,
+}
+
+impl Logger {
+    pub fn open(config: Config) -> io::Result<Logger> {
+        // A file written with other fields cannot be appended to, start afresh
+        if config.format == Format::Csv
+            && first_line(&config.path)?.is_some_and(|line| line != header(&config))
+        {
+            rotate(&config.path, config.keep)?;
+        }
+        let (file, size) = open(&config)?;
+        Ok(Logger {
+            config,
+            file,
+            size,
+            opened: Instant::now(),
+        })
+    }
+
+    pub fn log(
+        &mut self,
+        monitor: &system_monitor::Monitor,
+        sort_by: SortBy,
+        sort_ascending: bool,
+    ) -> io::Result<()> {
+        let mut processes: Vec<ProcessInfo> = monitor
+            .get_processes()
+            .into_iter()
+            .filter(|p| !p.thread)
+            .collect();
+        processes.sort_by(|a, b| {
+            let order = compare(a, b, sort_by);
+            if sort_ascending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+        processes.truncate(self.config.top);
+
+        let system = self
+            .config
+            .fields
+            .iter()
+            .filter(|f| !f.is_process())
+            .map(|f| (f.to_string(), system_value(monitor, *f)))
+            .collect::<Vec<_>>();
+        let processes = processes
+            .iter()
+            .map(|p| {
+                let mut values = vec![("pid".to_string(), Value::Number(p.pid.to_string()))];
+                values.extend(
+                    self.config
+                        .fields
+                        .iter()
+                        .filter(|f| f.is_process())
+                        .map(|f| (f.key(), process_value(p, *f))),
+                );
+                values
+            })
+            .collect::<Vec<_>>();
+
+        let timestamp = format_rfc3339_seconds(SystemTime::now()).to_string();
+        let line = match self.config.format {
+            Format::Csv => csv_line(&self.config, &timestamp, &system, &processes),
+            Format::JsonLines => json_line(&timestamp, &system, &processes),
+        };
+
+        let too_big = self
+            .config
+            .max_size
+            .is_some_and(|max| self.size > 0 && self.size + line.len() as u64 > max);
+        let too_old = self
+            .config
+            .max_age
+            .is_some_and(|max| self.opened.elapsed() >= max);
+        if too_big || too_old {
+            rotate(&self.config.path, self.config.keep)?;
+            (self.file, self.size) = open(&self.config)?;
+            self.opened = Instant::now();
+        }
+
+        // Written unbuffered, so that the trail survives ptop being killed
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+// Opens the log for appending, writing the CSV header into a new file
+fn open(config: &Config) -> io::Result<(File, u64)> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&config.path)?;
+    let mut size = file.metadata()?.len();
+    if size == 0 && config.format == Format::Csv {
+        let header = header(config) + "\n";
+        file.write_all(header.as_bytes())?;
+        size = header.len() as u64;
+    }
+    Ok((file, size))
+}
+
+fn first_line(path: &Path) -> io::Result<Option<String>> {
+    match File::open(path) {
+        Ok(file) => Ok(BufReader::new(file)
+            .lines()
+            .next()
+            .transpose()?
+            .filter(|line| !line.is_empty())),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// Shifts <path>.1 .. <path>.<keep - 1> up by one and moves the current file to
+// <path>.1, dropping the oldest
+fn rotate(path: &Path, keep: usize) -> io::Result<()> {
+    let rotated = |n: usize| PathBuf::from(format!("{}.{}", path.display(), n));
+    if keep == 0 {
+        return match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    for n in (1..keep).rev() {
+        if rotated(n).exists() {
+            fs::rename(rotated(n), rotated(n + 1))?;
+        }
+    }
+    if path.exists() {
+        fs::rename(path, rotated(1))?;
+    }
+    Ok(())
+}
+
+// Same order as the process table
+fn compare(a: &ProcessInfo, b: &ProcessInfo, sort_by: SortBy) -> Ordering {
+    match sort_by {
+        SortBy::Pid => b.pid.cmp(&a.pid),
+        SortBy::Name => b.name.cmp(&a.name),
+        SortBy::CpuUsage => b.cpu_usage.total_cmp(&a.cpu_usage),
+        SortBy::Memory => b.memory.cmp(&a.memory),
+        SortBy::RunTime => b.run_time.cmp(&a.run_time),
+        SortBy::Status => b.status.to_string().cmp(&a.status.to_string()),
+        SortBy::DiskRead => b.disk_read_rate.total_cmp(&a.disk_read_rate),
+        SortBy::DiskWrite => b.disk_write_rate.total_cmp(&a.disk_write_rate),
+        SortBy::Connections => b.connections.cmp(&a.connections),
+    }
+}
+
+fn system_value(monitor: &system_monitor::Monitor, field: Field) -> Value {
+    let networks = || monitor.get_networks().into_iter();
+    match field {
+        Field::Cpu => number(monitor.get_global_cpu_usage() as f64, 1),
+        Field::Memory => Value::Number(monitor.get_used_memory().0.to_string()),
+        Field::Swap => Value::Number(monitor.get_used_memory().1.to_string()),
+        Field::Load => match monitor.get_load_average() {
+            Some(load) => number(load.one, 2),
+            None => Value::Text(String::new()),
+        },
+        Field::DiskRead => number(monitor.get_disk_io_rates().0, 0),
+        Field::DiskWrite => number(monitor.get_disk_io_rates().1, 0),
+        Field::NetRx => number(networks().map(|n| n.receive_rate).sum(), 0),
+        Field::NetTx => number(networks().map(|n| n.transmit_rate).sum(), 0),
+        _ => Value::Text(String::new()),
+    }
+}
+
+fn process_value(process: &ProcessInfo, field: Field) -> Value {
+    match field {
+        Field::ProcessName => Value::Text(process.name.clone()),
+        Field::ProcessUser => Value::Text(process.user.clone()),
+        Field::ProcessCpu => number(process.cpu_usage as f64, 1),
+        Field::ProcessMemory => Value::Number(process.memory.to_string()),
+        Field::ProcessDiskRead => number(process.disk_read_rate, 0),
+        Field::ProcessDiskWrite => number(process.disk_write_rate, 0),
+        _ => Value::Text(String::new()),
+    }
+}
+
+// Formats a reading with the given number of decimals. NaN and infinities have
+// no JSON spelling, they are left blank like missing readings.
+fn number(value: f64, decimals: usize) -> Value {
+    if value.is_finite() {
+        Value::Number(format!("{:.*}", decimals, value))
+    } else {
+        Value::Text(String::new())
+    }
+}
+
+// The columns of a CSV log: the timestamp, the system fields, then the PID and
+// process fields of each of the top processes, e.g. "process1.cpu"
+fn header(config: &Config) -> String {
+    let mut columns = vec!["timestamp".to_string()];
+    columns.extend(
+        config
+            .fields
+            .iter()
+            .filter(|f| !f.is_process())
+            .map(|f| f.to_string()),
+    );
+    for n in 1..=config.top {
+        columns.push(format!("process{}.pid", n));
+        columns.extend(
+            config
+                .fields
+                .iter()
+                .filter(|f| f.is_process())
+                .map(|f| format!("process{}.{}", n, f.key())),
+        );
+    }
+    columns.join(",")
+}
+
+fn csv_line(
+    config: &Config,
+    timestamp: &str,
+    system: &[(String, Value)],
+    processes: &[Vec<(String, Value)>],
+) -> String {
+    let mut cells = vec![timestamp.to_string()];
+    cells.extend(system.iter().map(|(_, value)| csv_cell(value)));
+    let process_columns = 1 + config.fields.iter().filter(|f| f.is_process()).count();
+    for n in 0..config.top {
+        match processes.get(n) {
+            Some(values) => cells.extend(values.iter().map(|(_, value)| csv_cell(value))),
+            // Fewer processes than asked for, keep the columns aligned
+            None => cells.extend(std::iter::repeat_n(String::new(), process_columns)),
+        }
+    }
+    cells.join(",") + "\n"
+}
+
+// Quotes cells containing separators, quotes or line breaks
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Number(number) => number.clone(),
+        Value::Text(text) if text.contains([',', '"', '\n', '\r']) => {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        }
+        Value::Text(text) => text.clone(),
+    }
+}
+
+fn json_line(
+    timestamp: &str,
+    system: &[(String, Value)],
+    processes: &[Vec<(String, Value)>],
+) -> String {
+    let mut members = vec![format!("\"timestamp\":{}", json_string(timestamp))];
+    members.extend(system.iter().map(json_member));
+    let processes: Vec<String> = processes
+        .iter()
+        .map(|values| {
+            let members: Vec<String> = values.iter().map(json_member).collect();
+            format!("{{{}}}", members.join(","))
+        })
+        .collect();
+    members.push(format!("\"processes\":[{}]", processes.join(",")));
+    format!("{{{}}}\n", members.join(","))
+}
+
+fn json_member((key, value): &(String, Value)) -> String {
+    let value = match value {
+        Value::Number(number) => number.clone(),
+        Value::Text(text) if text.is_empty() => "null".to_string(),
+        Value::Text(text) => json_string(text),
+    };
+    format!("{}:{}", json_string(key), value)
+}
+
+fn json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::json;
+    use crate::fixture::Fixture;
+
+    fn config(path: PathBuf, fields: Vec<Field>) -> Config {
+        Config {
+            fields,
+            top: 2,
+            ..Config::new(path)
+        }
+    }
+
+    fn rotated(path: &Path, n: usize) -> PathBuf {
+        PathBuf::from(format!("{}.{}", path.display(), n))
+    }
+
+    #[test]
+    fn writes_csv() {
+        let fixture = Fixture::new();
+        let path = fixture.path().join("ptop.csv");
+        let monitor = system_monitor::Monitor::new();
+        let fields = vec![Field::Cpu, Field::Memory, Field::ProcessCpu];
+        let mut logger = Logger::open(config(path.clone(), fields)).unwrap();
+        logger.log(&monitor, SortBy::Pid, true).unwrap();
+        logger.log(&monitor, SortBy::Pid, true).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(
+            lines[0],
+            "timestamp,cpu,memory,process1.pid,process1.cpu,process2.pid,process2.cpu"
+        );
+        assert_eq!(lines.len(), 3);
+        for line in &lines[1..] {
+            let cells: Vec<&str> = line.split(',').collect();
+            assert_eq!(cells.len(), 7);
+            assert!(humantime::parse_rfc3339(cells[0]).is_ok());
+            assert!(cells[2].parse::<u64>().is_ok());
+        }
+        // Sorted by ascending PID, init comes first
+        assert_eq!(lines[1].split(',').nth(3), Some("1"));
+
+        // A log written with other fields is set aside rather than appended to
+        drop(logger);
+        let mut logger = Logger::open(config(path.clone(), vec![Field::Cpu])).unwrap();
+        logger.log(&monitor, SortBy::Pid, true).unwrap();
+        assert_eq!(fs::read_to_string(rotated(&path, 1)).unwrap(), content);
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("timestamp,cpu,process1.pid,process2.pid\n"));
+    }
+
+    #[test]
+    fn writes_json_lines() {
+        let fixture = Fixture::new();
+        let path = fixture.path().join("ptop.jsonl");
+        let monitor = system_monitor::Monitor::new();
+        let fields = vec![Field::Load, Field::ProcessName, Field::ProcessMemory];
+        let mut logger = Logger::open(config(path.clone(), fields)).unwrap();
+        logger.log(&monitor, SortBy::Pid, true).unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 1);
+        let record = json::parse(content.trim_end()).unwrap();
+        assert!(record.get("timestamp").and_then(|t| t.as_str()).is_some());
+        assert!(matches!(record.get("load"), Some(json::Value::Number(_))));
+        assert!(record.get("cpu").is_none());
+        let processes = record.get("processes").unwrap().as_array().unwrap();
+        assert_eq!(processes.len(), 2);
+        assert_eq!(processes[0].get("pid"), Some(&json::Value::Number(1.0)));
+        assert!(processes[0].get("name").is_some());
+        assert!(matches!(
+            processes[0].get("memory"),
+            Some(json::Value::Number(_))
+        ));
+    }
+
+    #[test]
+    fn escapes_values() {
+        assert_eq!(
+            csv_cell(&Value::Text("a,\"b\"".to_string())),
+            "\"a,\"\"b\"\"\""
+        );
+        assert_eq!(csv_cell(&Value::Text("plain".to_string())), "plain");
+        assert_eq!(json_string("a\"\\\n\u{1}"), "\"a\\\"\\\\\\n\\u0001\"");
+        assert_eq!(
+            json_member(&("user".to_string(), Value::Text(String::new()))),
+            "\"user\":null"
+        );
+    }
+
+    #[test]
+    fn blanks_non_finite_numbers() {
+        let values = [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 12.345]
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| (format!("v{}", i), number(value, 1)))
+            .collect::<Vec<_>>();
+        let line = json_line("2024-01-01T00:00:00Z", &values, &[]);
+        assert_eq!(
+            line,
+            "{\"timestamp\":\"2024-01-01T00:00:00Z\",\"v0\":null,\"v1\":null,\"v2\":null,\
+             \"v3\":12.3,\"processes\":[]}\n"
+        );
+        assert!(json::parse(line.trim_end()).is_some());
+        let config = config(PathBuf::from("ptop.csv"), vec![]);
+        assert_eq!(csv_line(&config, "t", &values, &[]), "t,,,,12.3,,\n");
+    }
+
+    #[test]
+    fn rotates_by_size() {
+        let fixture = Fixture::new();
+        let path = fixture.path().join("ptop.csv");
+        let monitor = system_monitor::Monitor::new();
+        let mut config = config(path.clone(), vec![Field::Cpu]);
+        // Room for the header and one record per file
+        config.top = 0;
+        config.max_size = Some(50);
+        config.keep = 2;
+        let mut logger = Logger::open(config).unwrap();
+        for _ in 0..4 {
+            logger.log(&monitor, SortBy::Pid, true).unwrap();
+        }
+
+        for path in [path.clone(), rotated(&path, 1), rotated(&path, 2)] {
+            let content = fs::read_to_string(path).unwrap();
+            assert_eq!(content.lines().count(), 2);
+            assert!(content.len() <= 50);
+        }
+        assert!(!rotated(&path, 3).exists());
+    }
+
+    #[test]
+    fn rotates_by_age() {
+        let fixture = Fixture::new();
+        let path = fixture.path().join("ptop.jsonl");
+        let monitor = system_monitor::Monitor::new();
+        let mut config = config(path.clone(), vec![Field::Cpu]);
+        config.max_age = Some(Duration::from_secs(3600));
+        let mut logger = Logger::open(config).unwrap();
+        logger.log(&monitor, SortBy::Pid, true).unwrap();
+        logger.log(&monitor, SortBy::Pid, true).unwrap();
+        assert!(!rotated(&path, 1).exists());
+
+        logger.opened = Instant::now()
+            .checked_sub(Duration::from_secs(3600))
+            .unwrap();
+        logger.log(&monitor, SortBy::Pid, true).unwrap();
+        assert_eq!(
+            fs::read_to_string(rotated(&path, 1))
+                .unwrap()
+                .lines()
+                .count(),
+            2
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1);
+    }
+}
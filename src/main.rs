@@ -2,30 +2,330 @@ use ratatui::crossterm::event;
 use update::update_widgets;
 
 mod app;
+mod cli;
+mod container;
+mod exporter;
+#[cfg(test)]
+mod fixture;
+mod history;
+mod logger;
 mod models;
+mod procfs;
+mod remote;
+mod sampler;
+mod supervisor;
+mod sysfs;
 mod system_monitor;
+mod systemd;
 mod update;
+mod utmp;
+
+// Interval between updates when no key is pressed
+const TICK: std::time::Duration = std::time::Duration::from_millis(1000);
 
 fn main() {
+    let mut args = match cli::Args::parse() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    // Launch the supervised command before taking over the terminal, so that
+    // errors are still readable
+    let mut supervisor = if args.command.is_empty() {
+        None
+    } else {
+        match supervisor::Supervisor::spawn(&args.command) {
+            Ok(supervisor) => Some(supervisor),
+            Err(e) => {
+                eprintln!("Failed to launch {}: {}", args.command[0], e);
+                std::process::exit(1);
+            }
+        }
+    };
+
+    let metrics = args
+        .metrics_address
+        .as_ref()
+        .map(|address| match exporter::serve(address) {
+            Ok(metrics) => metrics,
+            Err(e) => {
+                eprintln!("Failed to serve metrics on {}: {}", address, e);
+                std::process::exit(1);
+            }
+        });
+
+    let mut logger = args.log.take().map(|config| {
+        let path = config.path.clone();
+        match logger::Logger::open(config) {
+            Ok(logger) => logger,
+            Err(e) => {
+                eprintln!("Failed to open log {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+    });
+
+    let mut history = args
+        .history
+        .as_ref()
+        .map(|dir| match history::Store::open(dir) {
+            Ok(store) => store,
+            Err(e) => {
+                eprintln!("Failed to open history {}: {}", dir.display(), e);
+                std::process::exit(1);
+            }
+        });
+
+    let token = args
+        .token_file
+        .as_ref()
+        .map(|path| match remote::read_token(path) {
+            Ok(token) => token,
+            Err(e) => {
+                eprintln!("Failed to read token {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        });
+
+    let mut monitor = match (&args.connect, &token) {
+        (Some(address), Some(token)) => {
+            let tls = args
+                .tls_ca
+                .as_ref()
+                .map(|ca| match remote::client::tls_config(ca) {
+                    Ok(config) => config,
+                    Err(e) => {
+                        eprintln!("Failed to load TLS certificate: {}", e);
+                        std::process::exit(1);
+                    }
+                });
+            let config = remote::client::Config {
+                address: address.clone(),
+                token: token.clone(),
+                tls,
+            };
+            match remote::client::Client::connect(config) {
+                Ok(client) => system_monitor::Monitor::connect(client),
+                Err(e) => {
+                    eprintln!("Failed to connect to {}: {}", address, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        _ => system_monitor::Monitor::new(),
+    };
+
+    let agent = match (&args.agent, &token) {
+        (Some(address), Some(token)) => {
+            let tls = match (&args.tls_cert, &args.tls_key) {
+                (Some(cert), Some(key)) => match remote::agent::tls_config(cert, key) {
+                    Ok(config) => Some(config),
+                    Err(e) => {
+                        eprintln!("Failed to load TLS certificate: {}", e);
+                        std::process::exit(1);
+                    }
+                },
+                _ => None,
+            };
+            let plain = tls.is_none();
+            match remote::agent::serve(address, token.clone(), tls) {
+                Ok(agent) if plain && !agent.local_addr().ip().is_loopback() => {
+                    eprintln!(
+                        "Warning: serving {} without TLS, the token and the metrics are sent in the clear",
+                        agent.local_addr()
+                    );
+                    Some(agent)
+                }
+                Ok(agent) => Some(agent),
+                Err(e) => {
+                    eprintln!("Failed to serve agent on {}: {}", address, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        _ => None,
+    };
+
+    if args.headless || agent.is_some() {
+        run_headless(
+            &args,
+            &mut monitor,
+            supervisor.as_mut(),
+            metrics.as_ref(),
+            logger,
+            history,
+            agent,
+        );
+        if let Some(mut supervisor) = supervisor {
+            supervisor.stop();
+            println!("{}", supervisor.summary());
+        }
+        return;
+    }
+
     let mut app = app::PTop::new();
-    let mut monitor = system_monitor::Monitor::new();
+    // Errors cannot be shown while the UI owns the terminal, keep the first one for later
+    let mut log_error = None;
+    if let Some(supervisor) = &supervisor {
+        app.supervise(supervisor);
+    }
+    if let Some(store) = history.take() {
+        app.widgets.history.record_to(store);
+    }
 
+    // Key presses update the UI in between ticks, only ticks are logged
+    let mut next_tick = std::time::Instant::now();
     loop {
+        let tick = std::time::Instant::now() >= next_tick;
+        if tick {
+            next_tick = std::time::Instant::now() + TICK;
+        }
         monitor.update();
+        for definition in app.widgets.alert_table.take_added() {
+            monitor.forward_alert(definition);
+        }
+        if let Some(supervisor) = supervisor.as_mut() {
+            if let Some(status) = supervisor.poll(&monitor) {
+                app.widgets
+                    .alert_table
+                    .set_exit_status(supervisor.pid, status.to_string());
+            }
+        }
         update_widgets(&mut app, &monitor);
+        if let Some(metrics) = &metrics {
+            exporter::publish(
+                metrics,
+                exporter::render(
+                    &monitor,
+                    &app.widgets.alert_table,
+                    args.metrics_processes.as_deref(),
+                ),
+            );
+        }
+        if let Some(log) = logger.as_mut().filter(|_| tick) {
+            let table_state = &app.state.process_table_state;
+            if let Err(e) = log.log(
+                &monitor,
+                table_state.sort_condition,
+                table_state.sort_ascending,
+            ) {
+                log_error = Some(e);
+                logger = None;
+            }
+        }
         if let Err(e) = app.draw() {
             eprintln!("Failed to draw UI: {}", e);
             return;
         }
 
-        if event::poll(std::time::Duration::from_millis(1000)).unwrap() {
+        if event::poll(next_tick.saturating_duration_since(std::time::Instant::now())).unwrap() {
             if let event::Event::Key(key) = event::read().unwrap() {
                 match app.state.mode {
                     app::Mode::Normal => match key.code {
                         event::KeyCode::Char('q') => break,
-                        event::KeyCode::Char('j') => app.state.select_next_process(),
-                        event::KeyCode::Char('k') => app.state.select_prev_process(),
-                        event::KeyCode::Char('t') => {
+                        event::KeyCode::Tab => app.state.page = app.state.page.next(),
+                        event::KeyCode::Char('+') | event::KeyCode::Char('=') => {
+                            app.state.chart_window = app.state.chart_window.zoom_in();
+                        }
+                        event::KeyCode::Char('-') => {
+                            app.state.chart_window = app.state.chart_window.zoom_out();
+                        }
+                        event::KeyCode::Char('j') => match app.state.page {
+                            app::Page::Overview => app.state.select_next_process(),
+                            app::Page::Cpu => app.state.pressure_table_state.select_next(),
+                            app::Page::Storage => app.state.filesystem_table_state.select_next(),
+                            app::Page::Network => app.state.network_table_state.select_next(),
+                            app::Page::Cgroups => app.state.cgroup_table_state.select_next(),
+                            app::Page::Containers => app.state.container_table_state.select_next(),
+                            app::Page::Units => app.state.unit_table_state.select_next(),
+                            app::Page::Users => app.state.user_table_state.select_next(),
+                            app::Page::Hardware => app.state.sensor_table_state.select_next(),
+                            app::Page::History => app.state.history_state.select_next(),
+                            // Nothing to select on the Memory page
+                            app::Page::Memory => {}
+                        },
+                        event::KeyCode::Char('k') => match app.state.page {
+                            app::Page::Overview => app.state.select_prev_process(),
+                            app::Page::Cpu => app.state.pressure_table_state.select_prev(),
+                            app::Page::Storage => app.state.filesystem_table_state.select_prev(),
+                            app::Page::Network => app.state.network_table_state.select_prev(),
+                            app::Page::Cgroups => app.state.cgroup_table_state.select_prev(),
+                            app::Page::Containers => app.state.container_table_state.select_prev(),
+                            app::Page::Units => app.state.unit_table_state.select_prev(),
+                            app::Page::Users => app.state.user_table_state.select_prev(),
+                            app::Page::Hardware => app.state.sensor_table_state.select_prev(),
+                            app::Page::History => app.state.history_state.select_prev(),
+                            app::Page::Memory => {}
+                        },
+                        event::KeyCode::Char('f') if app.state.page == app::Page::Cpu => {
+                            app.state.alert_pressure_threshold.clear();
+                            app.state.mode = app::Mode::AlertPressureThreshold;
+                        }
+                        event::KeyCode::Char('f') if app.state.page == app::Page::Storage => {
+                            app.state.alert_disk_threshold.clear();
+                            app.state.mode = app::Mode::AlertDiskThreshold;
+                        }
+                        event::KeyCode::Char('f') if app.state.page == app::Page::Hardware => {
+                            app.state.alert_temperature_threshold.clear();
+                            app.state.mode = app::Mode::AlertTemperatureThreshold;
+                        }
+                        event::KeyCode::Char('b') if app.state.page == app::Page::Hardware => {
+                            app.state.alert_battery_threshold.clear();
+                            app.state.mode = app::Mode::AlertBatteryThreshold;
+                        }
+                        event::KeyCode::Char('r') if app.state.page == app::Page::History => {
+                            app.state.history_state.next_range();
+                        }
+                        event::KeyCode::Char('[') if app.state.page == app::Page::History => {
+                            app.state.history_state.pan_earlier();
+                        }
+                        event::KeyCode::Char(']') if app.state.page == app::Page::History => {
+                            app.state.history_state.pan_later();
+                        }
+                        event::KeyCode::Char(' ') if app.state.page == app::Page::Network => {
+                            if let Some(interface) = app
+                                .state
+                                .network_table_state
+                                .selected()
+                                .and_then(|selected| {
+                                    app.widgets.network_table.get_interface(selected)
+                                })
+                            {
+                                app.state
+                                    .network_table_state
+                                    .toggle_interface(&interface.name);
+                            }
+                        }
+                        event::KeyCode::Char('r') if app.state.page == app::Page::Units => {
+                            if let Some(unit) = app
+                                .state
+                                .unit_table_state
+                                .selected()
+                                .and_then(|selected| app.widgets.unit_table.get_unit(selected))
+                            {
+                                app.state.unit_table_state.pending =
+                                    Some((models::unit::UnitAction::Restart, unit.name.clone()));
+                                app.state.mode = app::Mode::UnitConfirm;
+                            }
+                        }
+                        event::KeyCode::Char('t') if app.state.page == app::Page::Units => {
+                            if let Some(unit) = app
+                                .state
+                                .unit_table_state
+                                .selected()
+                                .and_then(|selected| app.widgets.unit_table.get_unit(selected))
+                            {
+                                app.state.unit_table_state.pending =
+                                    Some((models::unit::UnitAction::Stop, unit.name.clone()));
+                                app.state.mode = app::Mode::UnitConfirm;
+                            }
+                        }
+                        // Keys acting on the selected process only work where the process
+                        // table is shown
+                        event::KeyCode::Char('t') if app.state.page == app::Page::Overview => {
                             if let Some(selected) = app.state.process_table_state.selected() {
                                 app.widgets
                                     .process_table
@@ -73,6 +373,162 @@ fn main() {
                             app.state.process_table_state.sort_ascending =
                                 !app.state.process_table_state.sort_ascending;
                         }
+                        event::KeyCode::Char('7') => {
+                            app.state.process_table_state.sort_condition =
+                                models::process::SortBy::DiskRead;
+                            app.state.process_table_state.sort_ascending =
+                                !app.state.process_table_state.sort_ascending;
+                        }
+                        event::KeyCode::Char('8') => {
+                            app.state.process_table_state.sort_condition =
+                                models::process::SortBy::DiskWrite;
+                            app.state.process_table_state.sort_ascending =
+                                !app.state.process_table_state.sort_ascending;
+                        }
+                        event::KeyCode::Char('9') => {
+                            app.state.process_table_state.sort_condition =
+                                models::process::SortBy::Connections;
+                            app.state.process_table_state.sort_ascending =
+                                !app.state.process_table_state.sort_ascending;
+                        }
+                        event::KeyCode::Char('n') if app.state.page == app::Page::Overview => {
+                            if let Some(process) = app
+                                .state
+                                .process_table_state
+                                .selected()
+                                .and_then(|selected| {
+                                    app.widgets.process_table.get_process(selected)
+                                })
+                            {
+                                app.widgets.socket_table.open(
+                                    &monitor,
+                                    process.pid,
+                                    process.name.clone(),
+                                );
+                                app.state.mode = app::Mode::SocketView;
+                            }
+                        }
+                        event::KeyCode::Char('o') if app.state.page == app::Page::Overview => {
+                            if let Some(process) = app
+                                .state
+                                .process_table_state
+                                .selected()
+                                .and_then(|selected| {
+                                    app.widgets.process_table.get_process(selected)
+                                })
+                            {
+                                app.widgets
+                                    .inspector
+                                    .open(process.pid, process.name.clone());
+                                app.state.inspector_state.search.clear();
+                                app.state.mode = app::Mode::Inspector;
+                            }
+                        }
+                        event::KeyCode::Char('T') if app.state.page == app::Page::Overview => {
+                            if let Some(process) = app
+                                .state
+                                .process_table_state
+                                .selected()
+                                .and_then(|selected| {
+                                    app.widgets.process_table.get_process(selected)
+                                })
+                            {
+                                app.widgets
+                                    .thread_table
+                                    .open(process.pid, process.name.clone());
+                                app.state.thread_table_state.filter.clear();
+                                app.state.mode = app::Mode::ThreadView;
+                            }
+                        }
+                        event::KeyCode::Char('H') => {
+                            app.state.process_table_state.show_threads =
+                                !app.state.process_table_state.show_threads;
+                        }
+                        event::KeyCode::Char('s') if app.state.page == app::Page::Overview => {
+                            if let Some(process) = app
+                                .state
+                                .process_table_state
+                                .selected()
+                                .and_then(|selected| {
+                                    app.widgets.process_table.get_process(selected)
+                                })
+                            {
+                                app.widgets.stack_view.sample(
+                                    &monitor,
+                                    process.pid,
+                                    process.name.clone(),
+                                );
+                                app.state.stack_view_state = models::stack::StackViewState::new();
+                                app.state.mode = app::Mode::StackView;
+                            }
+                        }
+                        event::KeyCode::Char('g') => {
+                            app.state.process_table_state.cycle_group_by();
+                        }
+                        event::KeyCode::Enter if app.state.page == app::Page::Cgroups => {
+                            if let Some(cgroup) = app
+                                .state
+                                .cgroup_table_state
+                                .selected()
+                                .and_then(|selected| app.widgets.cgroup_table.get_cgroup(selected))
+                            {
+                                app.state
+                                    .process_table_state
+                                    .toggle_cgroup_filter(&cgroup.path);
+                                app.state.page = app::Page::Overview;
+                            }
+                        }
+                        event::KeyCode::Enter if app.state.page == app::Page::Containers => {
+                            if let Some(cgroup) = app
+                                .state
+                                .container_table_state
+                                .selected()
+                                .and_then(|selected| {
+                                    app.widgets.container_table.get_container(selected)
+                                })
+                                .and_then(|container| container.cgroup.as_ref())
+                            {
+                                app.state.process_table_state.toggle_cgroup_filter(cgroup);
+                                app.state.page = app::Page::Overview;
+                            }
+                        }
+                        event::KeyCode::Enter if app.state.page == app::Page::Units => {
+                            if let Some(cgroup) = app
+                                .state
+                                .unit_table_state
+                                .selected()
+                                .and_then(|selected| app.widgets.unit_table.get_unit(selected))
+                                .and_then(|unit| unit.control_group.as_ref())
+                            {
+                                app.state.process_table_state.toggle_cgroup_filter(cgroup);
+                                app.state.page = app::Page::Overview;
+                            }
+                        }
+                        event::KeyCode::Enter if app.state.page == app::Page::Users => {
+                            if let Some(user) = app
+                                .state
+                                .user_table_state
+                                .selected()
+                                .and_then(|selected| app.widgets.user_table.get_user(selected))
+                            {
+                                app.state.process_table_state.toggle_user_filter(&user.name);
+                                app.state.page = app::Page::Overview;
+                            }
+                        }
+                        event::KeyCode::Enter if app.state.page == app::Page::Overview => {
+                            if let Some(selected) = app.state.process_table_state.selected() {
+                                if let Some(key) = app.widgets.process_table.get_group_key(selected)
+                                {
+                                    app.state.process_table_state.toggle_group(key);
+                                }
+                            }
+                        }
+                        event::KeyCode::Char('l') => {
+                            app.state.process_table_state.highlight_events =
+                                !app.state.process_table_state.highlight_events;
+                        }
+                        event::KeyCode::PageUp => app.state.event_log_state.scroll_up(),
+                        event::KeyCode::PageDown => app.state.event_log_state.scroll_down(),
                         event::KeyCode::Up => app.state.select_prev_alert(),
                         event::KeyCode::Down => app.state.select_next_alert(),
                         event::KeyCode::Char('a') => {
@@ -85,17 +541,27 @@ fn main() {
                                 app.widgets.alert_table.disarm_alert(selected);
                             }
                         }
-                        event::KeyCode::Char('c') => {
+                        event::KeyCode::Char('c') if app.state.page == app::Page::Overview => {
                             app.state.alert_cpu_threshold.clear();
                             app.state.mode = app::Mode::AlertCpuThreshold;
                         }
-                        event::KeyCode::Char('m') => {
+                        event::KeyCode::Char('m') if app.state.page == app::Page::Overview => {
                             app.state.alert_memory_threshold.clear();
                             app.state.mode = app::Mode::AlertMemoryThreshold;
                         }
-                        event::KeyCode::Char('e') => {
-                            if let Some(selected) = app.state.process_table_state.selected() {
-                                let process = app.widgets.process_table.get_process(selected);
+                        event::KeyCode::Char('w') if app.state.page == app::Page::Overview => {
+                            app.state.alert_write_rate_threshold.clear();
+                            app.state.mode = app::Mode::AlertWriteRateThreshold;
+                        }
+                        event::KeyCode::Char('e') if app.state.page == app::Page::Overview => {
+                            if let Some(process) = app
+                                .state
+                                .process_table_state
+                                .selected()
+                                .and_then(|selected| {
+                                    app.widgets.process_table.get_process(selected)
+                                })
+                            {
                                 app.widgets
                                     .alert_table
                                     .add_exit_code_alert(process.pid, process.name.clone());
@@ -119,16 +585,25 @@ fn main() {
                     },
                     app::Mode::AlertCpuThreshold => match key.code {
                         event::KeyCode::Enter => {
-                            if let Some(selected) = app.state.process_table_state.selected() {
-                                let process = app.widgets.process_table.get_process(selected);
-                                app.widgets.alert_table.add_cpu_alert(
-                                    process.pid,
-                                    process.name.clone(),
-                                    app.state.alert_cpu_threshold.parse().unwrap(),
-                                );
-                                app.state.alert_table_state.select_next();
+                            // Keep the prompt open until the threshold is a number
+                            if let Ok(threshold) = app.state.alert_cpu_threshold.parse() {
+                                if let Some(process) = app
+                                    .state
+                                    .process_table_state
+                                    .selected()
+                                    .and_then(|selected| {
+                                        app.widgets.process_table.get_process(selected)
+                                    })
+                                {
+                                    app.widgets.alert_table.add_cpu_alert(
+                                        process.pid,
+                                        process.name.clone(),
+                                        threshold,
+                                    );
+                                    app.state.alert_table_state.select_next();
+                                }
+                                app.state.mode = app::Mode::Normal;
                             }
-                            app.state.mode = app::Mode::Normal;
                         }
                         event::KeyCode::Backspace => {
                             app.state.alert_cpu_threshold.pop();
@@ -140,16 +615,25 @@ fn main() {
                     },
                     app::Mode::AlertMemoryThreshold => match key.code {
                         event::KeyCode::Enter => {
-                            if let Some(selected) = app.state.process_table_state.selected() {
-                                let process = app.widgets.process_table.get_process(selected);
-                                app.widgets.alert_table.add_memory_alert(
-                                    process.pid,
-                                    process.name.clone(),
-                                    app.state.alert_memory_threshold.parse().unwrap(),
-                                );
-                                app.state.alert_table_state.select_next();
+                            // Keep the prompt open until the threshold is a number
+                            if let Ok(threshold) = app.state.alert_memory_threshold.parse() {
+                                if let Some(process) = app
+                                    .state
+                                    .process_table_state
+                                    .selected()
+                                    .and_then(|selected| {
+                                        app.widgets.process_table.get_process(selected)
+                                    })
+                                {
+                                    app.widgets.alert_table.add_memory_alert(
+                                        process.pid,
+                                        process.name.clone(),
+                                        threshold,
+                                    );
+                                    app.state.alert_table_state.select_next();
+                                }
+                                app.state.mode = app::Mode::Normal;
                             }
-                            app.state.mode = app::Mode::Normal;
                         }
                         event::KeyCode::Backspace => {
                             app.state.alert_memory_threshold.pop();
@@ -159,9 +643,325 @@ fn main() {
                         }
                         _ => {}
                     },
+                    app::Mode::UnitConfirm => {
+                        if let (event::KeyCode::Char('y'), Some((action, name))) =
+                            (key.code, app.state.unit_table_state.pending.take())
+                        {
+                            app.state.unit_table_state.message = Some(match action {
+                                models::unit::UnitAction::Restart => {
+                                    match monitor.restart_unit(&name) {
+                                        Ok(()) => format!("restarting {}", name),
+                                        Err(e) => format!("restart of {} failed: {}", name, e),
+                                    }
+                                }
+                                models::unit::UnitAction::Stop => match monitor.stop_unit(&name) {
+                                    Ok(()) => format!("stopping {}", name),
+                                    Err(e) => format!("stop of {} failed: {}", name, e),
+                                },
+                            });
+                        }
+                        // Any other key cancels
+                        app.state.unit_table_state.pending = None;
+                        app.state.mode = app::Mode::Normal;
+                    }
+                    app::Mode::SocketView => match key.code {
+                        event::KeyCode::Esc | event::KeyCode::Char('q') => {
+                            app.widgets.socket_table.close();
+                            app.state.mode = app::Mode::Normal;
+                        }
+                        event::KeyCode::Char('j') => app.state.socket_table_state.select_next(),
+                        event::KeyCode::Char('k') => app.state.socket_table_state.select_prev(),
+                        _ => {}
+                    },
+                    app::Mode::Inspector => match key.code {
+                        event::KeyCode::Esc | event::KeyCode::Char('q') => {
+                            app.widgets.inspector.close();
+                            app.state.mode = app::Mode::Normal;
+                        }
+                        event::KeyCode::Tab => app.state.inspector_state.switch_tab(),
+                        event::KeyCode::Char('/') => {
+                            app.state.inspector_state.search.clear();
+                            app.state.mode = app::Mode::InspectorSearch;
+                        }
+                        event::KeyCode::Char('j') => app.state.inspector_state.select_next(),
+                        event::KeyCode::Char('k') => app.state.inspector_state.select_prev(),
+                        _ => {}
+                    },
+                    app::Mode::InspectorSearch => match key.code {
+                        event::KeyCode::Enter | event::KeyCode::Esc => {
+                            app.state.mode = app::Mode::Inspector;
+                        }
+                        event::KeyCode::Backspace => {
+                            app.state.inspector_state.search.pop();
+                        }
+                        event::KeyCode::Char(c) => {
+                            app.state.inspector_state.search.push(c);
+                        }
+                        _ => {}
+                    },
+                    app::Mode::ThreadView => match key.code {
+                        event::KeyCode::Esc | event::KeyCode::Char('q') => {
+                            app.widgets.thread_table.close();
+                            app.state.mode = app::Mode::Normal;
+                        }
+                        event::KeyCode::Char('j') => app.state.thread_table_state.select_next(),
+                        event::KeyCode::Char('k') => app.state.thread_table_state.select_prev(),
+                        event::KeyCode::Char('/') => {
+                            app.state.thread_table_state.filter.clear();
+                            app.state.mode = app::Mode::ThreadFilter;
+                        }
+                        event::KeyCode::Char('1') => app
+                            .state
+                            .thread_table_state
+                            .sort(models::thread::ThreadSortBy::Tid),
+                        event::KeyCode::Char('2') => app
+                            .state
+                            .thread_table_state
+                            .sort(models::thread::ThreadSortBy::Name),
+                        event::KeyCode::Char('3') => app
+                            .state
+                            .thread_table_state
+                            .sort(models::thread::ThreadSortBy::State),
+                        event::KeyCode::Char('4') => app
+                            .state
+                            .thread_table_state
+                            .sort(models::thread::ThreadSortBy::CpuUsage),
+                        event::KeyCode::Char('5') => app
+                            .state
+                            .thread_table_state
+                            .sort(models::thread::ThreadSortBy::LastCpu),
+                        _ => {}
+                    },
+                    app::Mode::ThreadFilter => match key.code {
+                        event::KeyCode::Enter | event::KeyCode::Esc => {
+                            app.state.mode = app::Mode::ThreadView;
+                        }
+                        event::KeyCode::Backspace => {
+                            app.state.thread_table_state.filter.pop();
+                        }
+                        event::KeyCode::Char(c) => {
+                            app.state.thread_table_state.filter.push(c);
+                        }
+                        _ => {}
+                    },
+                    app::Mode::StackView => match key.code {
+                        event::KeyCode::Esc | event::KeyCode::Char('q') => {
+                            app.widgets.stack_view.close();
+                            app.state.mode = app::Mode::Normal;
+                        }
+                        event::KeyCode::Char('j') => app.state.stack_view_state.scroll_down(),
+                        event::KeyCode::Char('k') => app.state.stack_view_state.scroll_up(),
+                        _ => {}
+                    },
+                    app::Mode::AlertDiskThreshold => match key.code {
+                        event::KeyCode::Enter => {
+                            // Keep the prompt open until the threshold is a number
+                            if let Ok(threshold) = app.state.alert_disk_threshold.parse() {
+                                if let Some(filesystem) =
+                                    app.state.filesystem_table_state.selected().and_then(
+                                        |selected| {
+                                            app.widgets.filesystem_table.get_filesystem(selected)
+                                        },
+                                    )
+                                {
+                                    app.widgets.alert_table.add_disk_usage_alert(
+                                        filesystem.mount_point.clone(),
+                                        threshold,
+                                    );
+                                    app.state.alert_table_state.select_next();
+                                }
+                                app.state.mode = app::Mode::Normal;
+                            }
+                        }
+                        event::KeyCode::Backspace => {
+                            app.state.alert_disk_threshold.pop();
+                        }
+                        event::KeyCode::Char(c) => {
+                            app.state.alert_disk_threshold.push(c);
+                        }
+                        _ => {}
+                    },
+                    app::Mode::AlertPressureThreshold => match key.code {
+                        event::KeyCode::Enter => {
+                            // Keep the prompt open until the threshold is a number
+                            if let Ok(threshold) = app.state.alert_pressure_threshold.parse() {
+                                if let Some(resource) = app
+                                    .state
+                                    .pressure_table_state
+                                    .selected()
+                                    .and_then(|selected| {
+                                        app.widgets.pressure_table.get_resource(selected)
+                                    })
+                                {
+                                    app.widgets
+                                        .alert_table
+                                        .add_pressure_alert(resource, threshold);
+                                    app.state.alert_table_state.select_next();
+                                }
+                                app.state.mode = app::Mode::Normal;
+                            }
+                        }
+                        event::KeyCode::Backspace => {
+                            app.state.alert_pressure_threshold.pop();
+                        }
+                        event::KeyCode::Char(c) => {
+                            app.state.alert_pressure_threshold.push(c);
+                        }
+                        _ => {}
+                    },
+                    app::Mode::AlertTemperatureThreshold => match key.code {
+                        event::KeyCode::Enter => {
+                            // Keep the prompt open until the threshold is a number
+                            if let Ok(threshold) = app.state.alert_temperature_threshold.parse() {
+                                if let Some(sensor) = app
+                                    .state
+                                    .sensor_table_state
+                                    .selected()
+                                    .and_then(|selected| {
+                                        app.widgets.sensor_table.get_sensor(selected)
+                                    })
+                                {
+                                    app.widgets
+                                        .alert_table
+                                        .add_temperature_alert(sensor.label.clone(), threshold);
+                                    app.state.alert_table_state.select_next();
+                                }
+                                app.state.mode = app::Mode::Normal;
+                            }
+                        }
+                        event::KeyCode::Backspace => {
+                            app.state.alert_temperature_threshold.pop();
+                        }
+                        event::KeyCode::Char(c) => {
+                            app.state.alert_temperature_threshold.push(c);
+                        }
+                        _ => {}
+                    },
+                    app::Mode::AlertBatteryThreshold => match key.code {
+                        event::KeyCode::Enter => {
+                            // Keep the prompt open until the threshold is a number
+                            if let Ok(threshold) = app.state.alert_battery_threshold.parse() {
+                                if let Some(battery) = app.widgets.power_table.get_battery() {
+                                    app.widgets
+                                        .alert_table
+                                        .add_battery_alert(battery.to_string(), threshold);
+                                    app.state.alert_table_state.select_next();
+                                }
+                                app.state.mode = app::Mode::Normal;
+                            }
+                        }
+                        event::KeyCode::Backspace => {
+                            app.state.alert_battery_threshold.pop();
+                        }
+                        event::KeyCode::Char(c) => {
+                            app.state.alert_battery_threshold.push(c);
+                        }
+                        _ => {}
+                    },
+                    app::Mode::AlertWriteRateThreshold => match key.code {
+                        event::KeyCode::Enter => {
+                            // Keep the prompt open until the threshold is a number
+                            if let Ok(threshold) = app.state.alert_write_rate_threshold.parse() {
+                                if let Some(process) = app
+                                    .state
+                                    .process_table_state
+                                    .selected()
+                                    .and_then(|selected| {
+                                        app.widgets.process_table.get_process(selected)
+                                    })
+                                {
+                                    app.widgets.alert_table.add_write_rate_alert(
+                                        process.pid,
+                                        process.name.clone(),
+                                        threshold,
+                                    );
+                                    app.state.alert_table_state.select_next();
+                                }
+                                app.state.mode = app::Mode::Normal;
+                            }
+                        }
+                        event::KeyCode::Backspace => {
+                            app.state.alert_write_rate_threshold.pop();
+                        }
+                        event::KeyCode::Char(c) => {
+                            app.state.alert_write_rate_threshold.push(c);
+                        }
+                        _ => {}
+                    },
                 }
             }
         }
     }
     app.finish();
+    if let Some(e) = log_error {
+        eprintln!("Stopped logging: {}", e);
+    }
+
+    if let Some(mut supervisor) = supervisor {
+        supervisor.stop();
+        println!("{}", supervisor.summary());
+    }
+}
+
+// Keeps the metrics, the log, the history and the agent clients up to date
+// without taking over the terminal, until the supervised command exits or
+// forever when there is none
+fn run_headless(
+    args: &cli::Args,
+    monitor: &mut system_monitor::Monitor,
+    mut supervisor: Option<&mut supervisor::Supervisor>,
+    metrics: Option<&exporter::MetricsBuffer>,
+    mut logger: Option<logger::Logger>,
+    mut history: Option<history::Store>,
+    agent: Option<remote::agent::Agent>,
+) {
+    // Without the process table, processes are logged in its default order
+    let table_state = models::process::ProcessTableState::new();
+    let mut alerts = models::alert::AlertTable::new();
+    if let Some(supervisor) = &supervisor {
+        alerts.add_exit_code_alert(supervisor.pid, supervisor.name.clone());
+    }
+
+    loop {
+        monitor.update();
+        if let Some(agent) = &agent {
+            agent.handle_requests(monitor, &mut alerts);
+        }
+        let exited = supervisor
+            .as_mut()
+            .and_then(|supervisor| Some((supervisor.pid, supervisor.poll(monitor)?)));
+        if let Some((pid, status)) = &exited {
+            alerts.set_exit_status(*pid, status.to_string());
+        }
+        alerts.update_alerts(monitor);
+        if let Some(metrics) = metrics {
+            exporter::publish(
+                metrics,
+                exporter::render(monitor, &alerts, args.metrics_processes.as_deref()),
+            );
+        }
+        if let Some(log) = logger.as_mut() {
+            if let Err(e) = log.log(
+                monitor,
+                table_state.sort_condition,
+                table_state.sort_ascending,
+            ) {
+                eprintln!("Stopped logging: {}", e);
+                logger = None;
+            }
+        }
+        if let Some(store) = history.as_mut() {
+            if let Err(e) = store.record(history::Sample::collect(monitor)) {
+                eprintln!("Stopped recording history: {}", e);
+                history = None;
+            }
+        }
+        if let Some(agent) = &agent {
+            agent.publish(monitor);
+        }
+        if exited.is_some() {
+            return;
+        }
+        std::thread::sleep(TICK);
+    }
 }
@@ -1,5 +1,22 @@
 pub mod alert;
+pub mod cgroup;
+pub mod chart;
+pub mod container;
 pub mod cpu;
+pub mod disk;
+pub mod event;
+pub mod history;
 pub mod info;
+pub mod inspector;
+pub mod load;
 pub mod memory;
+pub mod network;
+pub mod output;
+pub mod power;
 pub mod process;
+pub mod sensor;
+pub mod socket;
+pub mod stack;
+pub mod thread;
+pub mod unit;
+pub mod user;
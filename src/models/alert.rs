@@ -1,3 +1,4 @@
+use crate::procfs::pressure::Resource;
 use crate::update::UpdateableWidgetWithState;
 use crate::{app::AppState, system_monitor};
 use ratatui::{
@@ -7,6 +8,9 @@ use ratatui::{
     widgets::{Block, Borders, Row, StatefulWidget, Table, TableState},
 };
 
+// Number of consecutive ticks a write rate must stay above its threshold to trigger
+const SUSTAINED_TICKS: usize = 5;
+
 #[derive(PartialEq, strum::EnumString, strum::Display)]
 pub enum AlertStatus {
     Armed,
@@ -14,59 +18,170 @@ pub enum AlertStatus {
     Triggered,
 }
 
-#[derive(PartialEq, strum::EnumString, strum::Display)]
+#[derive(Clone, Copy, PartialEq, strum::EnumString, strum::Display)]
 pub enum AlertCondition {
     CpuUsage(f32),
     MemoryUsage(f32),
+    // Threshold in MiB/s
+    WriteRate(f32),
     Exit(),
+    // Threshold in percent of the filesystem mounted at the alert's name
+    DiskUsage(f32),
+    // Threshold in degrees Celsius of the sensor labelled with the alert's name
+    Temperature(f32),
+    // Threshold in percent of the 10s "some" stall average of the resource named by the alert
+    Pressure(f32),
+    // Threshold in percent of charge of the battery named by the alert, checked while discharging
+    BatteryLow(f32),
+}
+
+impl AlertCondition {
+    // The value the condition compares against, exit alerts have none
+    pub fn threshold(&self) -> Option<f32> {
+        match *self {
+            AlertCondition::CpuUsage(threshold)
+            | AlertCondition::MemoryUsage(threshold)
+            | AlertCondition::WriteRate(threshold)
+            | AlertCondition::DiskUsage(threshold)
+            | AlertCondition::Temperature(threshold)
+            | AlertCondition::Pressure(threshold)
+            | AlertCondition::BatteryLow(threshold) => Some(threshold),
+            AlertCondition::Exit() => None,
+        }
+    }
+}
+
+// What an alert watches, all that an agent needs to evaluate it as well
+#[derive(Clone)]
+pub struct AlertDefinition {
+    pub pid: Option<u32>,
+    pub name: String,
+    pub condition: AlertCondition,
 }
 
 pub struct AlertEntry {
-    pid: u32,
+    // None for system-wide alerts, which are not tied to a process
+    pid: Option<u32>,
     name: String,
     condition: AlertCondition,
     status: AlertStatus,
+    exceeded_ticks: usize,
+    // Extra information about why the alert triggered, e.g. an exit status
+    detail: Option<String>,
 }
 
 pub struct AlertTable {
     alerts: Vec<AlertEntry>,
+    // Alerts added from the UI since the last take_added, to forward to the
+    // agent when monitoring a remote host
+    added: Vec<AlertDefinition>,
 }
 
 impl AlertTable {
     pub fn new() -> Self {
-        AlertTable { alerts: Vec::new() }
+        AlertTable {
+            alerts: Vec::new(),
+            added: Vec::new(),
+        }
     }
 
-    pub fn add_cpu_alert(&mut self, pid: u32, name: String, threshold: f32) {
+    // Adds an alert unless the same one already exists, as both would always
+    // report the same and be indistinguishable in the exported metrics
+    pub fn add_alert(&mut self, definition: AlertDefinition) {
+        if self.contains(&definition) {
+            return;
+        }
         self.alerts.push(AlertEntry {
-            pid,
-            name,
-            condition: AlertCondition::CpuUsage(threshold),
+            pid: definition.pid,
+            name: definition.name,
+            condition: definition.condition,
             status: AlertStatus::Armed,
+            exceeded_ticks: 0,
+            detail: None,
         });
     }
 
-    pub fn add_memory_alert(&mut self, pid: u32, name: String, threshold: f32) {
-        self.alerts.push(AlertEntry {
+    fn contains(&self, definition: &AlertDefinition) -> bool {
+        self.alerts.iter().any(|a| {
+            a.pid == definition.pid
+                && a.name == definition.name
+                && a.condition == definition.condition
+        })
+    }
+
+    pub fn take_added(&mut self) -> Vec<AlertDefinition> {
+        std::mem::take(&mut self.added)
+    }
+
+    // Adds an alert defined in the UI
+    fn define(&mut self, pid: Option<u32>, name: String, condition: AlertCondition) {
+        let definition = AlertDefinition {
             pid,
             name,
-            condition: AlertCondition::MemoryUsage(threshold),
-            status: AlertStatus::Armed,
-        });
+            condition,
+        };
+        if !self.contains(&definition) {
+            self.added.push(definition.clone());
+            self.add_alert(definition);
+        }
+    }
+
+    pub fn add_cpu_alert(&mut self, pid: u32, name: String, threshold: f32) {
+        self.define(Some(pid), name, AlertCondition::CpuUsage(threshold));
+    }
+
+    pub fn add_memory_alert(&mut self, pid: u32, name: String, threshold: f32) {
+        self.define(Some(pid), name, AlertCondition::MemoryUsage(threshold));
+    }
+
+    pub fn add_write_rate_alert(&mut self, pid: u32, name: String, threshold: f32) {
+        self.define(Some(pid), name, AlertCondition::WriteRate(threshold));
     }
 
     pub fn add_exit_code_alert(&mut self, pid: u32, name: String) {
-        self.alerts.push(AlertEntry {
-            pid,
-            name,
-            condition: AlertCondition::Exit(),
-            status: AlertStatus::Armed,
-        });
+        self.define(Some(pid), name, AlertCondition::Exit());
+    }
+
+    pub fn add_disk_usage_alert(&mut self, mount_point: String, threshold: f32) {
+        self.define(None, mount_point, AlertCondition::DiskUsage(threshold));
+    }
+
+    pub fn add_temperature_alert(&mut self, label: String, threshold: f32) {
+        self.define(None, label, AlertCondition::Temperature(threshold));
+    }
+
+    pub fn add_pressure_alert(&mut self, resource: Resource, threshold: f32) {
+        self.define(
+            None,
+            resource.to_string(),
+            AlertCondition::Pressure(threshold),
+        );
+    }
+
+    pub fn add_battery_alert(&mut self, battery: String, threshold: f32) {
+        self.define(None, battery, AlertCondition::BatteryLow(threshold));
+    }
+
+    // Returns the (pid, name, condition, triggered) of every alert
+    pub fn get_states(&self) -> Vec<(Option<u32>, &str, AlertCondition, bool)> {
+        self.alerts
+            .iter()
+            .map(|a| {
+                (
+                    a.pid,
+                    a.name.as_str(),
+                    a.condition,
+                    a.status == AlertStatus::Triggered,
+                )
+            })
+            .collect()
     }
 
     pub fn arm_alert(&mut self, index: usize) {
         if index < self.alerts.len() {
             self.alerts[index].status = AlertStatus::Armed;
+            self.alerts[index].exceeded_ticks = 0;
+            self.alerts[index].detail = None;
         }
     }
 
@@ -76,11 +191,29 @@ impl AlertTable {
         }
     }
 
+    // Triggers the exit alerts of a process whose real exit status is known,
+    // which is only the case for processes launched by ptop
+    pub fn set_exit_status(&mut self, pid: u32, status: String) {
+        for alert in self.alerts.iter_mut() {
+            if alert.pid == Some(pid)
+                && alert.condition == AlertCondition::Exit()
+                && alert.status == AlertStatus::Armed
+            {
+                alert.status = AlertStatus::Triggered;
+                alert.detail = Some(status.clone());
+            }
+        }
+    }
+
     pub fn update_alerts(&mut self, monitor: &system_monitor::Monitor) {
         for alert in self.alerts.iter_mut() {
             if alert.status == AlertStatus::Armed {
-                if let Some((cpu_usage, memory_usage, status)) =
-                    monitor.get_process_status_by_pid(alert.pid)
+                let Some(pid) = alert.pid else {
+                    update_system_alert(alert, monitor);
+                    continue;
+                };
+                if let Some((cpu_usage, memory_usage, write_rate, status)) =
+                    monitor.get_process_status_by_pid(pid)
                 {
                     match alert.condition {
                         AlertCondition::CpuUsage(threshold) => {
@@ -93,11 +226,26 @@ impl AlertTable {
                                 alert.status = AlertStatus::Triggered;
                             }
                         }
+                        AlertCondition::WriteRate(threshold) => {
+                            if write_rate > threshold as f64 * 1024.0 * 1024.0 {
+                                alert.exceeded_ticks += 1;
+                            } else {
+                                alert.exceeded_ticks = 0;
+                            }
+                            if alert.exceeded_ticks >= SUSTAINED_TICKS {
+                                alert.status = AlertStatus::Triggered;
+                            }
+                        }
                         AlertCondition::Exit() => {
                             if status == sysinfo::ProcessStatus::Stop {
                                 alert.status = AlertStatus::Triggered;
                             }
                         }
+                        // System-wide conditions are handled by update_system_alert
+                        AlertCondition::DiskUsage(_)
+                        | AlertCondition::Temperature(_)
+                        | AlertCondition::Pressure(_)
+                        | AlertCondition::BatteryLow(_) => {}
                     }
                 } else {
                     // if the process is not found, we assume it has exited
@@ -110,6 +258,31 @@ impl AlertTable {
     }
 }
 
+// Checks an alert on a system-wide condition
+fn update_system_alert(alert: &mut AlertEntry, monitor: &system_monitor::Monitor) {
+    let triggered = match alert.condition {
+        AlertCondition::DiskUsage(threshold) => monitor
+            .get_disk_usage_by_mount_point(&alert.name)
+            .is_some_and(|usage| usage > threshold),
+        AlertCondition::Temperature(threshold) => monitor
+            .get_temperature_by_label(&alert.name)
+            .is_some_and(|temperature| temperature > threshold),
+        AlertCondition::Pressure(threshold) => alert
+            .name
+            .parse::<Resource>()
+            .ok()
+            .and_then(|resource| monitor.get_pressure(resource))
+            .is_some_and(|pressure| pressure.some.avg10 > threshold),
+        AlertCondition::BatteryLow(threshold) => monitor
+            .get_battery_by_name(&alert.name)
+            .is_some_and(|(capacity, discharging)| discharging && capacity < threshold),
+        _ => false,
+    };
+    if triggered {
+        alert.status = AlertStatus::Triggered;
+    }
+}
+
 pub struct AlertTableState {
     pub state: TableState,
 }
@@ -149,10 +322,13 @@ impl StatefulWidget for &AlertTable {
                 };
 
                 Row::new(vec![
-                    a.pid.to_string(),
+                    a.pid.map(|pid| pid.to_string()).unwrap_or_default(),
                     a.name.clone(),
                     a.condition.to_string(),
-                    a.status.to_string(),
+                    match &a.detail {
+                        Some(detail) => format!("{} ({})", a.status, detail),
+                        None => a.status.to_string(),
+                    },
                 ])
                 .style(status_style)
             })
@@ -160,7 +336,7 @@ impl StatefulWidget for &AlertTable {
 
         let header = Row::new(vec![
             "PID".to_string(),
-            "Process Name".to_string(),
+            "Name".to_string(),
             "Condition".to_string(),
             "Status".to_string(),
         ]);
@@ -169,9 +345,9 @@ impl StatefulWidget for &AlertTable {
             rows,
             [
                 Constraint::Percentage(10),
-                Constraint::Percentage(50),
-                Constraint::Percentage(20),
+                Constraint::Percentage(40),
                 Constraint::Percentage(20),
+                Constraint::Percentage(30),
             ],
         )
         .block(
@@ -0,0 +1,209 @@
+use crate::system_monitor;
+use crate::update::UpdateableWidget;
+use humansize::{format_size, BINARY};
+use humantime::format_duration;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Style, Stylize},
+    widgets::{Block, Borders, Row, StatefulWidget, Table, TableState},
+};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+pub struct Cgroup {
+    pub path: String,
+    cpu_usage: f64,
+    memory_current: Option<u64>,
+    memory_max: Option<u64>,
+    memory_high: Option<u64>,
+    pids: Option<u64>,
+    io_read_rate: f64,
+    io_write_rate: f64,
+    throttled_periods: u64,
+    throttled_time: u64,
+}
+
+// Cumulative counters of a cgroup at the previous sample
+struct Sample {
+    cpu_usage: u64,
+    io_read: u64,
+    io_written: u64,
+}
+
+pub struct CgroupTable {
+    cgroups: Vec<Cgroup>,
+    previous: HashMap<String, Sample>,
+    last_sample: Instant,
+}
+
+impl CgroupTable {
+    pub fn new() -> Self {
+        CgroupTable {
+            cgroups: Vec::new(),
+            previous: HashMap::new(),
+            last_sample: Instant::now(),
+        }
+    }
+
+    pub fn get_cgroup(&self, row: usize) -> Option<&Cgroup> {
+        self.cgroups.get(row)
+    }
+}
+
+pub struct CgroupTableState {
+    pub state: TableState,
+}
+
+impl CgroupTableState {
+    pub fn new() -> Self {
+        CgroupTableState {
+            state: TableState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.state.select_previous();
+    }
+
+    pub fn select_next(&mut self) {
+        self.state.select_next();
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.state.selected()
+    }
+}
+
+// Returns whether a cgroup path is the given cgroup or one of its descendants
+pub fn in_cgroup(path: &str, cgroup: &str) -> bool {
+    cgroup == "/"
+        || path == cgroup
+        || path
+            .strip_prefix(cgroup)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+fn format_memory(bytes: Option<u64>) -> String {
+    bytes
+        .map(|bytes| format_size(bytes, BINARY))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+impl StatefulWidget for &CgroupTable {
+    type State = TableState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let rows = self
+            .cgroups
+            .iter()
+            .map(|c| {
+                // Indent each cgroup under its parent
+                let depth = c.path.matches('/').count().saturating_sub(1);
+                let name = c.path.rsplit('/').next().filter(|n| !n.is_empty());
+                let style = if c.throttled_periods > 0 {
+                    Style::default().yellow()
+                } else {
+                    Style::default()
+                };
+                Row::new(vec![
+                    format!("{}{}", "  ".repeat(depth), name.unwrap_or("/")),
+                    format!("{:.1}%", c.cpu_usage),
+                    format_memory(c.memory_current),
+                    format_memory(c.memory_max),
+                    format_memory(c.memory_high),
+                    c.pids
+                        .map(|p| p.to_string())
+                        .unwrap_or_else(|| "-".to_string()),
+                    format!("{}/s", format_size(c.io_read_rate as u64, BINARY)),
+                    format!("{}/s", format_size(c.io_write_rate as u64, BINARY)),
+                    format!(
+                        "{} ({})",
+                        c.throttled_periods,
+                        format_duration(Duration::from_millis(c.throttled_time / 1000))
+                    ),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<Row>>();
+
+        let header = Row::new(vec![
+            "Cgroup".to_string(),
+            "CPU".to_string(),
+            "Memory".to_string(),
+            "Max".to_string(),
+            "High".to_string(),
+            "Pids".to_string(),
+            "I/O Read".to_string(),
+            "I/O Write".to_string(),
+            "Throttled".to_string(),
+        ]);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(30), // Cgroup
+                Constraint::Percentage(7),  // CPU
+                Constraint::Percentage(9),  // Memory
+                Constraint::Percentage(9),  // Max
+                Constraint::Percentage(9),  // High
+                Constraint::Percentage(6),  // Pids
+                Constraint::Percentage(9),  // I/O Read
+                Constraint::Percentage(9),  // I/O Write
+                Constraint::Percentage(12), // Throttled
+            ],
+        )
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title("Cgroups")
+                .style(Style::default()),
+        )
+        .header(header)
+        .row_highlight_style(Style::new().bold());
+        StatefulWidget::render(table, area, buf, state);
+    }
+}
+
+impl UpdateableWidget for CgroupTable {
+    fn update(&mut self, monitor: &system_monitor::Monitor) {
+        let elapsed = self.last_sample.elapsed().as_secs_f64().max(0.001);
+        self.last_sample = Instant::now();
+
+        let previous = std::mem::take(&mut self.previous);
+        self.cgroups = monitor
+            .get_cgroups()
+            .into_iter()
+            .map(|c| {
+                // Usage is only known from the second sample of a cgroup on
+                let delta = |now: u64, then: fn(&Sample) -> u64| {
+                    previous
+                        .get(&c.path)
+                        .map_or(0, |sample| now.saturating_sub(then(sample)))
+                        as f64
+                };
+                let cgroup = Cgroup {
+                    path: c.path.clone(),
+                    cpu_usage: delta(c.cpu_usage, |s| s.cpu_usage) / 1e6 / elapsed * 100.0,
+                    memory_current: c.memory_current,
+                    memory_max: c.memory_max,
+                    memory_high: c.memory_high,
+                    pids: c.pids_current,
+                    io_read_rate: delta(c.io_read, |s| s.io_read) / elapsed,
+                    io_write_rate: delta(c.io_written, |s| s.io_written) / elapsed,
+                    throttled_periods: c.throttled_periods,
+                    throttled_time: c.throttled_time,
+                };
+                self.previous.insert(
+                    c.path,
+                    Sample {
+                        cpu_usage: c.cpu_usage,
+                        io_read: c.io_read,
+                        io_written: c.io_written,
+                    },
+                );
+                cgroup
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,177 @@
+use humantime::format_duration;
+use ratatui::{
+    style::{Style, Stylize},
+    widgets::Axis,
+};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// Time span shown by the charts, from the latest update back
+#[derive(Clone, Copy, PartialEq)]
+pub enum Window {
+    OneMinute,
+    FiveMinutes,
+    OneHour,
+}
+
+impl Window {
+    pub fn duration(self) -> Duration {
+        match self {
+            Window::OneMinute => Duration::from_secs(60),
+            Window::FiveMinutes => Duration::from_secs(5 * 60),
+            Window::OneHour => Duration::from_secs(60 * 60),
+        }
+    }
+
+    pub fn zoom_in(self) -> Self {
+        match self {
+            Window::OneHour => Window::FiveMinutes,
+            _ => Window::OneMinute,
+        }
+    }
+
+    pub fn zoom_out(self) -> Self {
+        match self {
+            Window::OneMinute => Window::FiveMinutes,
+            _ => Window::OneHour,
+        }
+    }
+}
+
+// Values over time in a ring buffer, dropping the oldest once they are out of
+// the widest window
+pub struct Series<T> {
+    points: VecDeque<(Instant, T)>,
+}
+
+impl<T> Series<T> {
+    pub fn new() -> Self {
+        Series {
+            points: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.push_at(Instant::now(), value);
+    }
+
+    // Points are kept by age rather than count, since updates triggered by
+    // key presses come faster than the tick
+    fn push_at(&mut self, now: Instant, value: T) {
+        let max_age = Window::OneHour.duration();
+        while self
+            .points
+            .front()
+            .is_some_and(|(time, _)| now.duration_since(*time) > max_age)
+        {
+            self.points.pop_front();
+        }
+        self.points.push_back((now, value));
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.points.back().map(|(_, value)| value)
+    }
+
+    // The latest values, oldest first, each with its age
+    pub fn latest(&self, count: usize) -> impl Iterator<Item = (Duration, &T)> {
+        let now = Instant::now();
+        self.points
+            .iter()
+            .skip(self.points.len().saturating_sub(count))
+            .map(move |(time, value)| (now.duration_since(*time), value))
+    }
+}
+
+impl Series<f64> {
+    // Chart points within the window, at seconds before now
+    pub fn points(&self, window: Window) -> Vec<(f64, f64)> {
+        self.points_at(Instant::now(), window)
+    }
+
+    fn points_at(&self, now: Instant, window: Window) -> Vec<(f64, f64)> {
+        self.points
+            .iter()
+            .map(|(time, value)| (-now.duration_since(*time).as_secs_f64(), *value))
+            .filter(|(x, _)| *x >= -window.duration().as_secs_f64())
+            .collect()
+    }
+}
+
+// X axis spanning the window, labelled with the time before now, e.g. "-5m",
+// "-2m 30s" and "now"
+pub fn time_axis(window: Window) -> Axis<'static> {
+    let span = window.duration();
+    Axis::default()
+        .style(Style::default().white())
+        .bounds([-span.as_secs_f64(), 0.0])
+        .labels([
+            format!("-{}", format_duration(span)),
+            format!("-{}", format_duration(span / 2)),
+            "now".to_string(),
+        ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(start: Instant, seconds: &[u64]) -> Series<f64> {
+        let mut series = Series::new();
+        for &s in seconds {
+            series.push_at(start + Duration::from_secs(s), s as f64);
+        }
+        series
+    }
+
+    #[test]
+    fn evicts_points_by_age() {
+        let start = Instant::now();
+        // Several points a second, as when keys are held down, stay for the hour
+        let fast: Vec<u64> = (0..3600).flat_map(|s| [s, s, s, s]).collect();
+        let mut series = series(start, &fast);
+        assert_eq!(series.points.len(), 4 * 3600);
+
+        series.push_at(start + Duration::from_secs(3601), 3601.0);
+        assert_eq!(series.points.front().map(|(_, v)| *v), Some(1.0));
+        series.push_at(start + Duration::from_secs(2 * 3600), 7200.0);
+        assert_eq!(series.points.front().map(|(_, v)| *v), Some(3601.0));
+        assert_eq!(series.last(), Some(&7200.0));
+    }
+
+    #[test]
+    fn places_points_before_now() {
+        let start = Instant::now();
+        let series = series(start, &[0, 3000, 3500, 3590, 3600]);
+        let now = start + Duration::from_secs(3600);
+        assert_eq!(
+            series.points_at(now, Window::OneMinute),
+            vec![(-10.0, 3590.0), (0.0, 3600.0)]
+        );
+        assert_eq!(
+            series.points_at(now, Window::FiveMinutes),
+            vec![(-100.0, 3500.0), (-10.0, 3590.0), (0.0, 3600.0)]
+        );
+        assert_eq!(series.points_at(now, Window::OneHour).len(), 5);
+        assert_eq!(series.points_at(now, Window::OneHour)[0], (-3600.0, 0.0));
+    }
+
+    #[test]
+    fn zooms_between_windows() {
+        assert!(Window::OneHour.zoom_in() == Window::FiveMinutes);
+        assert!(Window::FiveMinutes.zoom_in() == Window::OneMinute);
+        assert!(Window::OneMinute.zoom_in() == Window::OneMinute);
+        assert!(Window::OneMinute.zoom_out() == Window::FiveMinutes);
+        assert!(Window::OneHour.zoom_out() == Window::OneHour);
+        assert_eq!(Window::FiveMinutes.duration(), Duration::from_secs(300));
+    }
+
+    #[test]
+    fn labels_the_time_axis() {
+        let axis = format!("{:?}", time_axis(Window::FiveMinutes));
+        assert!(axis.contains("-300.0"));
+        for label in ["-5m", "-2m 30s", "now"] {
+            assert!(axis.contains(label), "{} missing from {}", label, axis);
+        }
+    }
+}
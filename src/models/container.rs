@@ -0,0 +1,198 @@
+use crate::sysfs::cgroup::container_id;
+use crate::system_monitor;
+use crate::update::UpdateableWidget;
+use humansize::{format_size, BINARY};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Style, Stylize},
+    widgets::{Block, Borders, Row, StatefulWidget, Table, TableState},
+};
+use std::collections::HashMap;
+
+pub struct Container {
+    id: String,
+    name: String,
+    image: String,
+    state: String,
+    status: String,
+    cpu_usage: f32,
+    memory: u64,
+    processes: usize,
+    // Cgroup holding the container's processes, to filter the process table by
+    pub cgroup: Option<String>,
+}
+
+// Resource usage summed over the processes of one container
+#[derive(Default)]
+struct Usage {
+    cpu_usage: f32,
+    memory: u64,
+    processes: usize,
+    cgroup: Option<String>,
+}
+
+pub struct ContainerTable {
+    containers: Vec<Container>,
+    available: bool,
+}
+
+impl ContainerTable {
+    pub fn new() -> Self {
+        ContainerTable {
+            containers: Vec::new(),
+            available: false,
+        }
+    }
+
+    // Whether a container runtime socket was found
+    pub fn is_available(&self) -> bool {
+        self.available
+    }
+
+    pub fn get_container(&self, row: usize) -> Option<&Container> {
+        self.containers.get(row)
+    }
+}
+
+pub struct ContainerTableState {
+    pub state: TableState,
+}
+
+impl ContainerTableState {
+    pub fn new() -> Self {
+        ContainerTableState {
+            state: TableState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.state.select_previous();
+    }
+
+    pub fn select_next(&mut self) {
+        self.state.select_next();
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.state.selected()
+    }
+}
+
+// Cuts a process's cgroup path after the component naming its container, since
+// runtimes may nest further cgroups inside the container's
+fn container_cgroup(path: &str) -> Option<String> {
+    let components: Vec<&str> = path.split('/').collect();
+    let end = components
+        .iter()
+        .position(|component| container_id(component).is_some())?;
+    Some(components[..=end].join("/"))
+}
+
+impl StatefulWidget for &ContainerTable {
+    type State = TableState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let rows = self
+            .containers
+            .iter()
+            .map(|c| {
+                let style = if c.state == "running" {
+                    Style::default()
+                } else {
+                    Style::default().dark_gray()
+                };
+                Row::new(vec![
+                    c.id.chars().take(12).collect(),
+                    c.name.clone(),
+                    c.image.clone(),
+                    c.status.clone(),
+                    c.cpu_usage.to_string() + "%",
+                    format_size(c.memory, BINARY),
+                    c.processes.to_string(),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<Row>>();
+
+        let header = Row::new(vec![
+            "ID".to_string(),
+            "Name".to_string(),
+            "Image".to_string(),
+            "Status".to_string(),
+            "CPU".to_string(),
+            "Memory".to_string(),
+            "Processes".to_string(),
+        ]);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(12), // ID
+                Constraint::Percentage(18), // Name
+                Constraint::Percentage(24), // Image
+                Constraint::Percentage(18), // Status
+                Constraint::Percentage(8),  // CPU
+                Constraint::Percentage(10), // Memory
+                Constraint::Percentage(10), // Processes
+            ],
+        )
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title("Containers")
+                .style(Style::default()),
+        )
+        .header(header)
+        .row_highlight_style(Style::new().bold());
+        StatefulWidget::render(table, area, buf, state);
+    }
+}
+
+impl UpdateableWidget for ContainerTable {
+    fn update(&mut self, monitor: &system_monitor::Monitor) {
+        self.available = monitor.has_container_runtime();
+        if !self.available {
+            self.containers.clear();
+            return;
+        }
+
+        // Link processes to containers through the container ID in their cgroup path
+        let mut usage: HashMap<String, Usage> = HashMap::new();
+        for p in monitor.get_processes().into_iter().filter(|p| !p.thread) {
+            let Some(cgroup) = monitor.get_process_cgroup(p.pid) else {
+                continue;
+            };
+            let Some(id) = container_id(&cgroup) else {
+                continue;
+            };
+            let entry = usage.entry(id).or_default();
+            entry.cpu_usage += p.cpu_usage;
+            entry.memory += p.memory;
+            entry.processes += 1;
+            entry.cgroup = entry.cgroup.take().or_else(|| container_cgroup(&cgroup));
+        }
+
+        self.containers = monitor
+            .get_containers()
+            .into_iter()
+            .map(|c| {
+                let usage =
+                    c.id.get(..12)
+                        .and_then(|id| usage.remove(id))
+                        .unwrap_or_default();
+                Container {
+                    id: c.id,
+                    name: c.name,
+                    image: c.image,
+                    state: c.state,
+                    status: c.status,
+                    cpu_usage: usage.cpu_usage,
+                    memory: usage.memory,
+                    processes: usage.processes,
+                    cgroup: usage.cgroup,
+                }
+            })
+            .collect();
+    }
+}
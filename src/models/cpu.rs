@@ -1,39 +1,118 @@
+use crate::app::AppState;
+use crate::models::chart::{time_axis, Series, Window};
+use crate::procfs::stat::CpuBreakdown;
+use crate::sysfs::cpufreq::CpuFrequency;
 use crate::system_monitor;
-use crate::update::UpdateableWidget;
+use crate::update::{UpdateableWidget, UpdateableWidgetWithState};
+use humantime::format_duration;
 use ratatui::{
     buffer::Buffer,
-    layout::Rect,
-    style::{Style, Stylize},
+    layout::{Constraint, Rect},
+    style::{Color, Style, Stylize},
     symbols::Marker,
-    widgets::{Axis, BarChart, Block, Borders, Chart, Dataset, GraphType, Widget},
+    text::{Line, Span},
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Row, Table, Widget},
 };
+use std::time::Duration;
+
+// Width of each core's bar and the gap between bars, in characters
+const BAR_WIDTH: u16 = 8;
+const BAR_GAP: u16 = 2;
+
+// Width of the core names in front of each heatmap row
+const HEATMAP_LABEL_WIDTH: u16 = 8;
+
+// Heatmap colors by utilization, from idle to fully busy in steps of 20%
+const HEAT_COLORS: [Color; 5] = [
+    Color::Blue,
+    Color::Cyan,
+    Color::Green,
+    Color::Yellow,
+    Color::Red,
+];
+
+// The busy CPU states from the bottom of a stack to its top, idle time is left blank
+fn segments(breakdown: &CpuBreakdown) -> [(&'static str, Color, f64); 8] {
+    [
+        ("usr", Color::Green, breakdown.user),
+        ("nice", Color::Blue, breakdown.nice),
+        ("sys", Color::Red, breakdown.system),
+        ("iowait", Color::Yellow, breakdown.iowait),
+        ("irq", Color::Magenta, breakdown.irq),
+        ("sirq", Color::LightMagenta, breakdown.softirq),
+        ("steal", Color::Cyan, breakdown.steal),
+        ("guest", Color::LightBlue, breakdown.guest),
+    ]
+}
+
+// Title listing each state in its color, with its current share when known
+fn legend(title: &str, breakdown: Option<&CpuBreakdown>) -> Line<'static> {
+    let mut spans = vec![Span::from(format!("{} ", title))];
+    for (label, color, percent) in segments(&breakdown.copied().unwrap_or_default()) {
+        let text = match breakdown {
+            Some(_) => format!("{} {:.1} ", label, percent),
+            None => format!("{} ", label),
+        };
+        spans.push(Span::styled(text, Style::default().fg(color)));
+    }
+    Line::from(spans)
+}
+
+fn stack(breakdown: &CpuBreakdown) -> Vec<(Color, f64)> {
+    segments(breakdown)
+        .iter()
+        .map(|(_, color, percent)| (*color, *percent))
+        .collect()
+}
+
+// Draws one column of a stacked chart from (color, percent) segments, bottom
+// up, scaled so that 100% fills the height
+pub fn render_stack(buf: &mut Buffer, area: Rect, segments: &[(Color, f64)]) {
+    let mut top = 0.0;
+    let stack: Vec<(Color, f64)> = segments
+        .iter()
+        .map(|(color, percent)| {
+            top += percent;
+            (*color, top)
+        })
+        .collect();
+    for row in 0..area.height {
+        // A cell belongs to the state covering its middle
+        let level = (row as f64 + 0.5) / area.height as f64 * 100.0;
+        let Some((color, _)) = stack.iter().find(|(_, top)| *top >= level) else {
+            break;
+        };
+        for x in area.left()..area.right() {
+            buf[(x, area.bottom() - 1 - row)]
+                .set_symbol("█")
+                .set_fg(*color);
+        }
+    }
+}
 
 pub struct CpuHistogram {
-    usage: Vec<(f64, f64)>,
-    update_count: usize,
+    usage: Series<f64>,
+    window: Window,
 }
 
 impl CpuHistogram {
     pub fn new() -> Self {
         CpuHistogram {
-            usage: Vec::new(),
-            update_count: 0,
+            usage: Series::new(),
+            window: Window::OneMinute,
         }
     }
 }
 
 impl Widget for &CpuHistogram {
     fn render(self, area: Rect, buf: &mut Buffer) {
+        let usage = self.usage.points(self.window);
         let datasets = vec![Dataset::default()
             .name("CPU Usage(%)")
             .marker(Marker::Braille)
             .graph_type(GraphType::Line)
             .style(Style::default().cyan())
-            .data(&self.usage)];
-
-        let x_axis = Axis::default()
-            .style(Style::default().white())
-            .bounds([self.update_count as f64 - 100.0, self.update_count as f64]);
+            .data(&usage)];
 
         let y_axis = Axis::default()
             .style(Style::default().white())
@@ -45,73 +124,272 @@ impl Widget for &CpuHistogram {
                     .title("CPU Histogram")
                     .style(Style::default()),
             )
-            .x_axis(x_axis)
+            .x_axis(time_axis(self.window))
             .y_axis(y_axis);
 
         chart.render(area, buf);
     }
 }
 
-impl UpdateableWidget for CpuHistogram {
+impl UpdateableWidgetWithState for CpuHistogram {
+    fn update_with_state(&mut self, monitor: &system_monitor::Monitor, state: &mut AppState) {
+        self.window = state.chart_window;
+        self.usage.push(monitor.get_global_cpu_usage() as f64);
+    }
+}
+
+pub struct CpuBreakdownHistogram {
+    breakdowns: Series<CpuBreakdown>,
+}
+
+impl CpuBreakdownHistogram {
+    pub fn new() -> Self {
+        CpuBreakdownHistogram {
+            breakdowns: Series::new(),
+        }
+    }
+}
+
+impl Widget for &CpuBreakdownHistogram {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .title(legend("CPU Time Breakdown(%)", self.breakdowns.last()))
+            .style(Style::default());
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        // One column per update, the latest on the right
+        let breakdowns: Vec<_> = self.breakdowns.latest(inner.width as usize).collect();
+        let start = inner.right() - breakdowns.len() as u16;
+        for (i, (_, breakdown)) in breakdowns.into_iter().enumerate() {
+            render_stack(
+                buf,
+                Rect::new(start + i as u16, inner.y, 1, inner.height),
+                &stack(breakdown),
+            );
+        }
+    }
+}
+
+impl UpdateableWidget for CpuBreakdownHistogram {
     fn update(&mut self, monitor: &system_monitor::Monitor) {
-        self.update_count += 1;
-        self.usage.push((
-            self.update_count as f64,
-            monitor.get_global_cpu_usage() as f64,
-        ));
+        if let Some(breakdown) = monitor.get_cpu_breakdown() {
+            self.breakdowns.push(breakdown);
+        }
     }
 }
 
 pub struct CpuPerCore {
-    usage: Vec<f64>,
-    update_count: usize,
+    cores: Vec<CpuBreakdown>,
 }
 
 impl CpuPerCore {
     pub fn new() -> Self {
-        CpuPerCore {
-            usage: Vec::new(),
-            update_count: 0,
-        }
+        CpuPerCore { cores: Vec::new() }
     }
 }
 
 impl Widget for &CpuPerCore {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let data: Vec<(String, u64)> = self
-            .usage
-            .iter()
-            .enumerate()
-            .map(|(i, &usage)| (format!("Core {}", i), usage as u64))
-            .collect();
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .title(legend("CPU Per Core Usage", None))
+            .style(Style::default());
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.height < 2 {
+            return;
+        }
 
-        let bars: Vec<(&str, u64)> = data.iter().map(|(s, v)| (s.as_str(), *v)).collect();
+        // Each bar stacks the busy states of one core, above its usage and name
+        for (i, core) in self.cores.iter().enumerate() {
+            let x = inner.x + i as u16 * (BAR_WIDTH + BAR_GAP);
+            if x + BAR_WIDTH > inner.right() {
+                break;
+            }
+            let bar = Rect::new(x, inner.y, BAR_WIDTH, inner.height - 1);
+            render_stack(buf, bar, &stack(core));
+            buf.set_string(
+                x,
+                bar.bottom() - 1,
+                format!("{:.0}", 100.0 - core.idle),
+                Style::default().white().bold(),
+            );
+            buf.set_stringn(
+                x,
+                inner.bottom() - 1,
+                format!("Core {}", i),
+                BAR_WIDTH as usize,
+                Style::default(),
+            );
+        }
+    }
+}
 
-        let bar_chart = BarChart::default()
-            .block(
-                Block::new()
-                    .borders(Borders::ALL)
-                    .title("CPU Per Core Usage")
-                    .style(Style::default()),
-            )
-            .bar_width(8)
-            .bar_gap(2)
-            .bar_style(Style::default().green())
-            .value_style(Style::default().white())
-            .data(&bars)
-            .max(100);
+impl UpdateableWidget for CpuPerCore {
+    fn update(&mut self, monitor: &system_monitor::Monitor) {
+        self.cores = monitor.get_per_cpu_breakdown();
+    }
+}
 
-        bar_chart.render(area, buf);
+pub struct CpuHeatmap {
+    // Utilization of each core per update
+    history: Series<Vec<f64>>,
+}
+
+impl CpuHeatmap {
+    pub fn new() -> Self {
+        CpuHeatmap {
+            history: Series::new(),
+        }
     }
 }
 
-impl UpdateableWidget for CpuPerCore {
+fn heat_color(usage: f64) -> Color {
+    let bucket = (usage / 100.0 * HEAT_COLORS.len() as f64) as usize;
+    HEAT_COLORS[bucket.min(HEAT_COLORS.len() - 1)]
+}
+
+impl Widget for &CpuHeatmap {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut title = vec![Span::from("CPU Per Core History ")];
+        for (i, color) in HEAT_COLORS.iter().enumerate() {
+            title.push(Span::styled(
+                format!("{}%+ ", i * 100 / HEAT_COLORS.len()),
+                Style::default().fg(*color),
+            ));
+        }
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .title(Line::from(title))
+            .style(Style::default());
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.width <= HEATMAP_LABEL_WIDTH {
+            return;
+        }
+
+        // One row per core and one column per update, the latest on the right
+        let width = inner.width - HEATMAP_LABEL_WIDTH;
+        let columns: Vec<_> = self.history.latest(width as usize).collect();
+        let start = inner.right() - columns.len() as u16;
+        let cores = self.history.last().map_or(0, |usage| usage.len());
+        for core in 0..cores.min(inner.height as usize) {
+            let y = inner.y + core as u16;
+            buf.set_stringn(
+                inner.x,
+                y,
+                format!("Core {}", core),
+                HEATMAP_LABEL_WIDTH as usize,
+                Style::default(),
+            );
+            for (i, (_, usage)) in columns.iter().enumerate() {
+                if let Some(usage) = usage.get(core) {
+                    buf[(start + i as u16, y)]
+                        .set_symbol("█")
+                        .set_fg(heat_color(*usage));
+                }
+            }
+        }
+
+        // Mark the age of the oldest visible column in the bottom-left corner
+        if let Some((age, _)) = columns.first() {
+            if inner.height as usize > cores {
+                buf.set_string(
+                    inner.x,
+                    inner.bottom() - 1,
+                    format!("-{}", format_duration(Duration::from_secs(age.as_secs()))),
+                    Style::default().dark_gray(),
+                );
+            }
+        }
+    }
+}
+
+impl UpdateableWidget for CpuHeatmap {
     fn update(&mut self, monitor: &system_monitor::Monitor) {
-        self.update_count += 1;
-        self.usage = monitor
-            .get_per_cpu_usage()
+        let usage = monitor
+            .get_per_cpu_breakdown()
             .iter()
-            .map(|&usage| usage as f64)
-            .collect();
+            .map(|core| 100.0 - core.idle)
+            .collect::<Vec<f64>>();
+        if !usage.is_empty() {
+            self.history.push(usage);
+        }
+    }
+}
+
+pub struct CpuFrequencyTable {
+    frequencies: Vec<CpuFrequency>,
+}
+
+impl CpuFrequencyTable {
+    pub fn new() -> Self {
+        CpuFrequencyTable {
+            frequencies: Vec::new(),
+        }
+    }
+}
+
+fn format_frequency(mhz: Option<u64>) -> String {
+    mhz.map(|mhz| format!("{} MHz", mhz))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+impl Widget for &CpuFrequencyTable {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let rows = self
+            .frequencies
+            .iter()
+            .map(|f| {
+                // A core running well below its max frequency may be throttled
+                let style = match (f.current, f.max) {
+                    (Some(current), Some(max)) if current * 2 < max => Style::default().yellow(),
+                    _ => Style::default(),
+                };
+                Row::new(vec![
+                    format!("Core {}", f.core),
+                    format_frequency(f.current),
+                    format_frequency(f.min),
+                    format_frequency(f.max),
+                    f.governor.clone().unwrap_or_else(|| "-".to_string()),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<Row>>();
+
+        let header = Row::new(vec![
+            "Core".to_string(),
+            "Current".to_string(),
+            "Min".to_string(),
+            "Max".to_string(),
+            "Governor".to_string(),
+        ]);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(16), // Core
+                Constraint::Percentage(20), // Current
+                Constraint::Percentage(20), // Min
+                Constraint::Percentage(20), // Max
+                Constraint::Percentage(24), // Governor
+            ],
+        )
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title("CPU Frequency")
+                .style(Style::default()),
+        )
+        .header(header);
+        Widget::render(table, area, buf);
+    }
+}
+
+impl UpdateableWidget for CpuFrequencyTable {
+    fn update(&mut self, monitor: &system_monitor::Monitor) {
+        self.frequencies = monitor.get_cpu_frequencies();
     }
 }
@@ -0,0 +1,250 @@
+use crate::app::AppState;
+use crate::models::chart::{time_axis, Series, Window};
+use crate::system_monitor;
+use crate::update::{UpdateableWidget, UpdateableWidgetWithState};
+use humansize::{format_size, BINARY};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Color, Style, Stylize},
+    symbols::Marker,
+    text::{Line, Span},
+    widgets::{
+        Axis, Block, Borders, Cell, Chart, Dataset, GraphType, Row, StatefulWidget, Table,
+        TableState, Widget,
+    },
+};
+
+// Width of the usage bar drawn for each filesystem, in characters
+const USAGE_BAR_WIDTH: usize = 20;
+
+pub struct DiskIo {
+    read_rate: Series<f64>,
+    write_rate: Series<f64>,
+    window: Window,
+}
+
+impl DiskIo {
+    pub fn new() -> Self {
+        DiskIo {
+            read_rate: Series::new(),
+            write_rate: Series::new(),
+            window: Window::OneMinute,
+        }
+    }
+}
+
+impl Widget for &DiskIo {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let read_rate = self.read_rate.points(self.window);
+        let write_rate = self.write_rate.points(self.window);
+        let datasets = vec![
+            Dataset::default()
+                .name("Read(/s)")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().green())
+                .data(&read_rate),
+            Dataset::default()
+                .name("Write(/s)")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().red())
+                .data(&write_rate),
+        ];
+
+        // Scale to the busiest visible point, rates have no natural upper bound
+        let y_max = read_rate
+            .iter()
+            .chain(write_rate.iter())
+            .map(|(_, y)| *y)
+            .fold(1024.0, f64::max);
+        let y_axis = Axis::default()
+            .style(Style::default().white())
+            .bounds([0.0, y_max])
+            .labels(vec![
+                "0".to_string(),
+                format!("{}/s", format_size(y_max as u64, BINARY)),
+            ]);
+
+        let chart = Chart::new(datasets)
+            .block(
+                Block::new()
+                    .borders(Borders::ALL)
+                    .title("Disk I/O Histogram")
+                    .style(Style::default()),
+            )
+            .x_axis(time_axis(self.window))
+            .y_axis(y_axis);
+
+        chart.render(area, buf);
+    }
+}
+
+impl UpdateableWidgetWithState for DiskIo {
+    fn update_with_state(&mut self, monitor: &system_monitor::Monitor, state: &mut AppState) {
+        self.window = state.chart_window;
+        let (read_rate, write_rate) = monitor.get_disk_io_rates();
+        self.read_rate.push(read_rate);
+        self.write_rate.push(write_rate);
+    }
+}
+
+pub struct Filesystem {
+    name: String,
+    file_system: String,
+    pub mount_point: String,
+    total_space: u64,
+    available_space: u64,
+    total_inodes: u64,
+    free_inodes: u64,
+}
+
+pub struct FilesystemTable {
+    filesystems: Vec<Filesystem>,
+}
+
+impl FilesystemTable {
+    pub fn new() -> Self {
+        FilesystemTable {
+            filesystems: Vec::new(),
+        }
+    }
+
+    pub fn get_filesystem(&self, row: usize) -> Option<&Filesystem> {
+        self.filesystems.get(row)
+    }
+}
+
+pub struct FilesystemTableState {
+    pub state: TableState,
+}
+
+impl FilesystemTableState {
+    pub fn new() -> Self {
+        FilesystemTableState {
+            state: TableState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.state.select_previous();
+    }
+
+    pub fn select_next(&mut self) {
+        self.state.select_next();
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.state.selected()
+    }
+}
+
+fn usage_bar(percent: f64) -> Line<'static> {
+    let filled = ((percent / 100.0) * USAGE_BAR_WIDTH as f64).round() as usize;
+    let color = match percent {
+        p if p >= 90.0 => Color::Red,
+        p if p >= 70.0 => Color::Yellow,
+        _ => Color::Green,
+    };
+    Line::from(vec![
+        Span::styled(
+            "█".repeat(filled.min(USAGE_BAR_WIDTH)),
+            Style::default().fg(color),
+        ),
+        Span::styled(
+            "░".repeat(USAGE_BAR_WIDTH.saturating_sub(filled)),
+            Style::default().dark_gray(),
+        ),
+        Span::from(format!(" {:.1}%", percent)),
+    ])
+}
+
+impl StatefulWidget for &FilesystemTable {
+    type State = TableState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let rows = self
+            .filesystems
+            .iter()
+            .map(|f| {
+                let used = f.total_space - f.available_space;
+                let percent = if f.total_space > 0 {
+                    used as f64 / f.total_space as f64 * 100.0
+                } else {
+                    0.0
+                };
+                let inodes = if f.total_inodes > 0 {
+                    format!(
+                        "{:.1}%",
+                        (f.total_inodes - f.free_inodes) as f64 / f.total_inodes as f64 * 100.0
+                    )
+                } else {
+                    "-".to_string()
+                };
+                Row::new(vec![
+                    Cell::from(f.mount_point.clone()),
+                    Cell::from(f.name.clone()),
+                    Cell::from(f.file_system.clone()),
+                    Cell::from(format_size(f.total_space, BINARY)),
+                    Cell::from(format_size(used, BINARY)),
+                    Cell::from(format_size(f.available_space, BINARY)),
+                    Cell::from(inodes),
+                    Cell::from(usage_bar(percent)),
+                ])
+            })
+            .collect::<Vec<Row>>();
+
+        let header = Row::new(vec![
+            "Mount Point".to_string(),
+            "Device".to_string(),
+            "Type".to_string(),
+            "Total".to_string(),
+            "Used".to_string(),
+            "Available".to_string(),
+            "Inodes".to_string(),
+            "Usage".to_string(),
+        ]);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(18), // Mount Point
+                Constraint::Percentage(16), // Device
+                Constraint::Percentage(8),  // Type
+                Constraint::Percentage(9),  // Total
+                Constraint::Percentage(9),  // Used
+                Constraint::Percentage(9),  // Available
+                Constraint::Percentage(7),  // Inodes
+                Constraint::Percentage(24), // Usage
+            ],
+        )
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title("Filesystems")
+                .style(Style::default()),
+        )
+        .header(header)
+        .row_highlight_style(Style::new().bold());
+        StatefulWidget::render(table, area, buf, state);
+    }
+}
+
+impl UpdateableWidget for FilesystemTable {
+    fn update(&mut self, monitor: &system_monitor::Monitor) {
+        self.filesystems = monitor
+            .get_disks()
+            .into_iter()
+            .map(|d| Filesystem {
+                name: d.name,
+                file_system: d.file_system,
+                mount_point: d.mount_point,
+                total_space: d.total_space,
+                available_space: d.available_space,
+                total_inodes: d.total_inodes,
+                free_inodes: d.free_inodes,
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,324 @@
+use crate::models::process::Highlight;
+use crate::update::UpdateableWidgetWithState;
+use crate::{app::AppState, system_monitor};
+use humansize::{format_size, BINARY};
+use humantime::{format_duration, format_rfc3339_seconds};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders, Row, StatefulWidget, Table, TableState},
+};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, SystemTime};
+
+// Number of events kept in the feed
+const MAX_EVENTS: usize = 200;
+// Number of ticks a started/exited process stays highlighted in the process table
+const HIGHLIGHT_TICKS: usize = 3;
+
+#[derive(PartialEq, strum::Display)]
+pub enum EventKind {
+    Started,
+    Exited,
+}
+
+pub struct ProcessEvent {
+    kind: EventKind,
+    time: SystemTime,
+    pid: u32,
+    name: String,
+    parent: Option<u32>,
+    lifetime: u64,
+    peak_cpu_usage: f32,
+    peak_memory: u64,
+}
+
+// What we remember about a live process between two snapshots
+struct TrackedProcess {
+    name: String,
+    parent: Option<u32>,
+    run_time: u64,
+    peak_cpu_usage: f32,
+    peak_memory: u64,
+}
+
+pub struct EventLog {
+    events: VecDeque<ProcessEvent>,
+    tracked: HashMap<u32, TrackedProcess>,
+    update_count: usize,
+}
+
+impl EventLog {
+    pub fn new() -> Self {
+        EventLog {
+            events: VecDeque::new(),
+            tracked: HashMap::new(),
+            update_count: 0,
+        }
+    }
+
+    fn push(&mut self, event: ProcessEvent) {
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_back();
+        }
+        self.events.push_front(event);
+    }
+
+    // Diffs the current process snapshot against the previous one, producing
+    // started/exited events and updating the peak usage of live processes.
+    pub fn update_events(
+        &mut self,
+        monitor: &system_monitor::Monitor,
+        highlights: &mut HashMap<u32, (Highlight, usize)>,
+    ) {
+        self.diff(monitor.get_processes(), highlights);
+    }
+
+    // Same, for a given snapshot
+    pub fn diff(
+        &mut self,
+        processes: Vec<system_monitor::ProcessInfo>,
+        highlights: &mut HashMap<u32, (Highlight, usize)>,
+    ) {
+        self.update_count += 1;
+        let now = SystemTime::now();
+        let mut previous = std::mem::take(&mut self.tracked);
+
+        for p in processes.into_iter().filter(|p| !p.thread) {
+            match previous.remove(&p.pid) {
+                Some(mut tracked) => {
+                    tracked.run_time = p.run_time;
+                    tracked.peak_cpu_usage = tracked.peak_cpu_usage.max(p.cpu_usage);
+                    tracked.peak_memory = tracked.peak_memory.max(p.memory);
+                    self.tracked.insert(p.pid, tracked);
+                }
+                None => {
+                    // Everything is new on the first snapshot, which is not worth reporting
+                    if self.update_count > 1 {
+                        self.push(ProcessEvent {
+                            kind: EventKind::Started,
+                            time: now,
+                            pid: p.pid,
+                            name: p.name.clone(),
+                            parent: p.parent,
+                            lifetime: p.run_time,
+                            peak_cpu_usage: p.cpu_usage,
+                            peak_memory: p.memory,
+                        });
+                        highlights.insert(p.pid, (Highlight::Started, HIGHLIGHT_TICKS));
+                    }
+                    self.tracked.insert(
+                        p.pid,
+                        TrackedProcess {
+                            name: p.name,
+                            parent: p.parent,
+                            run_time: p.run_time,
+                            peak_cpu_usage: p.cpu_usage,
+                            peak_memory: p.memory,
+                        },
+                    );
+                }
+            }
+        }
+
+        // Whatever is left over from the previous snapshot has exited
+        for (pid, tracked) in previous {
+            self.push(ProcessEvent {
+                kind: EventKind::Exited,
+                time: now,
+                pid,
+                name: tracked.name,
+                parent: tracked.parent,
+                lifetime: tracked.run_time,
+                peak_cpu_usage: tracked.peak_cpu_usage,
+                peak_memory: tracked.peak_memory,
+            });
+            highlights.insert(pid, (Highlight::Exited, HIGHLIGHT_TICKS));
+        }
+    }
+}
+
+pub struct EventLogState {
+    pub state: TableState,
+}
+
+impl EventLogState {
+    pub fn new() -> Self {
+        EventLogState {
+            state: TableState::default(),
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.state.scroll_up_by(1);
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.state.scroll_down_by(1);
+    }
+}
+
+impl StatefulWidget for &EventLog {
+    type State = TableState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let rows = self
+            .events
+            .iter()
+            .map(|e| {
+                let style = match e.kind {
+                    EventKind::Started => Style::default().fg(Color::Green),
+                    EventKind::Exited => Style::default().fg(Color::Red),
+                };
+                // RFC 3339 timestamps look like 2024-01-01T12:34:56Z, keep the time only
+                let time = format_rfc3339_seconds(e.time).to_string();
+
+                Row::new(vec![
+                    time[11..19].to_string(),
+                    e.kind.to_string(),
+                    e.pid.to_string(),
+                    e.name.clone(),
+                    e.parent.map(|p| p.to_string()).unwrap_or_default(),
+                    format_duration(Duration::from_secs(e.lifetime)).to_string(),
+                    e.peak_cpu_usage.to_string() + "%",
+                    format_size(e.peak_memory, BINARY),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<Row>>();
+
+        let header = Row::new(vec![
+            "Time".to_string(),
+            "Event".to_string(),
+            "PID".to_string(),
+            "Name".to_string(),
+            "Parent".to_string(),
+            "Lifetime".to_string(),
+            "Peak CPU".to_string(),
+            "Peak Memory".to_string(),
+        ]);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(10), // Time
+                Constraint::Percentage(10), // Event
+                Constraint::Percentage(10), // PID
+                Constraint::Percentage(20), // Name
+                Constraint::Percentage(10), // Parent
+                Constraint::Percentage(15), // Lifetime
+                Constraint::Percentage(10), // Peak CPU
+                Constraint::Percentage(15), // Peak Memory
+            ],
+        )
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title("Process Events")
+                .style(Style::default()),
+        )
+        .header(header);
+        table.render(area, buf, state);
+    }
+}
+
+impl UpdateableWidgetWithState for EventLog {
+    fn update_with_state(&mut self, monitor: &system_monitor::Monitor, state: &mut AppState) {
+        self.update_events(monitor, &mut state.process_table_state.highlights);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sysinfo::ProcessStatus;
+
+    fn info(pid: u32, name: &str, cpu_usage: f32, memory: u64) -> system_monitor::ProcessInfo {
+        system_monitor::ProcessInfo {
+            pid,
+            parent: Some(1),
+            thread: false,
+            name: name.to_string(),
+            uid: Some(1000),
+            user: "alice".to_string(),
+            cpu_usage,
+            memory,
+            run_time: 10,
+            status: ProcessStatus::Run,
+            disk_read: 0,
+            disk_written: 0,
+            disk_read_rate: 0.0,
+            disk_write_rate: 0.0,
+            connections: 0,
+        }
+    }
+
+    #[test]
+    fn reports_started_and_exited_processes() {
+        let mut log = EventLog::new();
+        let mut highlights = HashMap::new();
+        log.diff(
+            vec![info(1, "init", 0.0, 1), info(2, "job", 5.0, 100)],
+            &mut highlights,
+        );
+        // The first snapshot only sets the baseline
+        assert!(log.events.is_empty());
+        assert!(highlights.is_empty());
+
+        let mut thread = info(4, "worker", 0.0, 0);
+        thread.thread = true;
+        log.diff(
+            vec![info(1, "init", 0.0, 1), info(2, "job", 50.0, 80)],
+            &mut highlights,
+        );
+        log.diff(
+            vec![info(1, "init", 0.0, 1), info(3, "new", 1.0, 10), thread],
+            &mut highlights,
+        );
+        assert_eq!(log.events.len(), 2);
+        let started = log
+            .events
+            .iter()
+            .find(|e| e.kind == EventKind::Started)
+            .unwrap();
+        assert_eq!((started.pid, started.name.as_str()), (3, "new"));
+        // The exited process keeps the peaks seen while it ran
+        let exited = log
+            .events
+            .iter()
+            .find(|e| e.kind == EventKind::Exited)
+            .unwrap();
+        assert_eq!(exited.pid, 2);
+        assert_eq!(exited.peak_cpu_usage, 50.0);
+        assert_eq!(exited.peak_memory, 100);
+        assert!(matches!(
+            highlights[&3],
+            (Highlight::Started, HIGHLIGHT_TICKS)
+        ));
+        assert!(matches!(
+            highlights[&2],
+            (Highlight::Exited, HIGHLIGHT_TICKS)
+        ));
+        // Threads are not processes of their own
+        assert!(!highlights.contains_key(&4));
+    }
+
+    #[test]
+    fn keeps_the_latest_events() {
+        let mut log = EventLog::new();
+        let mut highlights = HashMap::new();
+        log.diff(Vec::new(), &mut highlights);
+        for pid in 0..MAX_EVENTS as u32 + 10 {
+            log.diff(vec![info(pid, "short", 0.0, 0)], &mut highlights);
+        }
+        assert_eq!(log.events.len(), MAX_EVENTS);
+        // Newest first, each tick starting one process and ending the previous one
+        assert!(
+            log.events[0].kind == EventKind::Exited && log.events[0].pid == MAX_EVENTS as u32 + 8
+        );
+        assert!(
+            log.events[1].kind == EventKind::Started && log.events[1].pid == MAX_EVENTS as u32 + 9
+        );
+    }
+}
@@ -0,0 +1,353 @@
+use crate::app::{AppState, Page};
+use crate::history::{self, Sample, Store};
+use crate::system_monitor;
+use crate::update::UpdateableWidgetWithState;
+use humansize::{format_size, BINARY};
+use humantime::format_rfc3339_seconds;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Style, Stylize},
+    symbols::Marker,
+    widgets::{
+        Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph, Row, StatefulWidget, Table,
+        TableState, Widget,
+    },
+};
+use std::time::{Duration, UNIX_EPOCH};
+
+// Time ranges that can be browsed, with their length in seconds
+const RANGES: [(&str, u64); 5] = [
+    ("10m", 10 * 60),
+    ("1h", 60 * 60),
+    ("6h", 6 * 60 * 60),
+    ("1d", 24 * 60 * 60),
+    ("7d", 7 * 24 * 60 * 60),
+];
+
+// Formats seconds since the Unix epoch as UTC, e.g. "2024-05-01 13:45:10"
+fn format_time(time: u64) -> String {
+    let time = format_rfc3339_seconds(UNIX_EPOCH + Duration::from_secs(time)).to_string();
+    time.trim_end_matches('Z').replace('T', " ")
+}
+
+fn format_resolution(seconds: u64) -> &'static str {
+    match seconds {
+        1 => "1s",
+        60 => "1m",
+        _ => "1h",
+    }
+}
+
+pub struct HistoryBrowser {
+    store: Option<Store>,
+    // Why recording or reading the history stopped
+    error: Option<String>,
+    from: u64,
+    to: u64,
+    resolution: u64,
+    // Newest first
+    samples: Vec<Sample>,
+}
+
+impl HistoryBrowser {
+    pub fn new() -> Self {
+        HistoryBrowser {
+            store: None,
+            error: None,
+            from: 0,
+            to: 0,
+            resolution: 1,
+            samples: Vec::new(),
+        }
+    }
+
+    // Starts recording a sample on every update into the store
+    pub fn record_to(&mut self, store: Store) {
+        self.store = Some(store);
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.store.is_some() || self.error.is_some()
+    }
+}
+
+pub struct HistoryState {
+    pub state: TableState,
+    // Index into RANGES
+    range: usize,
+    // Seconds between the end of the browsed range and now
+    offset: u64,
+}
+
+impl HistoryState {
+    pub fn new() -> Self {
+        HistoryState {
+            state: TableState::default().with_selected(Some(0)),
+            range: 1,
+            offset: 0,
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.state.select_previous();
+    }
+
+    pub fn select_next(&mut self) {
+        self.state.select_next();
+    }
+
+    pub fn next_range(&mut self) {
+        self.range = (self.range + 1) % RANGES.len();
+    }
+
+    // Moves the browsed range by half its length
+    pub fn pan_earlier(&mut self) {
+        self.offset += RANGES[self.range].1 / 2;
+    }
+
+    pub fn pan_later(&mut self) {
+        self.offset = self.offset.saturating_sub(RANGES[self.range].1 / 2);
+    }
+}
+
+impl StatefulWidget for &HistoryBrowser {
+    type State = TableState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        if let Some(error) = &self.error {
+            Paragraph::new(format!("History stopped: {}", error))
+                .block(Block::new().borders(Borders::ALL).title("History"))
+                .render(area, buf);
+            return;
+        }
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(area);
+        let bottom_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(chunks[1]);
+
+        let selected = state.selected().and_then(|i| self.samples.get(i));
+        render_chart(self, selected, chunks[0], buf);
+        render_samples(self, bottom_chunks[0], buf, state);
+        render_processes(selected, bottom_chunks[1], buf);
+    }
+}
+
+fn render_chart(browser: &HistoryBrowser, selected: Option<&Sample>, area: Rect, buf: &mut Buffer) {
+    let series = |value: fn(&Sample) -> f32| -> Vec<(f64, f64)> {
+        browser
+            .samples
+            .iter()
+            .rev()
+            .map(|s| (s.time as f64, value(s) as f64))
+            .collect()
+    };
+    let cpu = series(|s| s.cpu);
+    let memory = series(|s| s.memory);
+    let swap = series(|s| s.swap);
+    let cursor: Vec<(f64, f64)> = selected
+        .map(|s| vec![(s.time as f64, 0.0), (s.time as f64, 100.0)])
+        .unwrap_or_default();
+
+    let datasets = vec![
+        Dataset::default()
+            .name("CPU Usage(%)")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().cyan())
+            .data(&cpu),
+        Dataset::default()
+            .name("Memory Usage(%)")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().yellow())
+            .data(&memory),
+        Dataset::default()
+            .name("Swap Usage(%)")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().magenta())
+            .data(&swap),
+        // The selected sample
+        Dataset::default()
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().white())
+            .data(&cursor),
+    ];
+
+    let middle = browser.from + (browser.to - browser.from) / 2;
+    let x_axis = Axis::default()
+        .style(Style::default().white())
+        .bounds([browser.from as f64, browser.to as f64])
+        .labels([browser.from, middle, browser.to].map(format_time));
+
+    let y_axis = Axis::default()
+        .style(Style::default().white())
+        .bounds([0.0, 100.0]);
+
+    Chart::new(datasets)
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "History ({} to {} UTC, one point per {})",
+                    format_time(browser.from),
+                    format_time(browser.to),
+                    format_resolution(browser.resolution)
+                ))
+                .style(Style::default()),
+        )
+        .x_axis(x_axis)
+        .y_axis(y_axis)
+        .render(area, buf);
+}
+
+fn render_samples(browser: &HistoryBrowser, area: Rect, buf: &mut Buffer, state: &mut TableState) {
+    let rows = browser
+        .samples
+        .iter()
+        .map(|s| {
+            Row::new(vec![
+                format_time(s.time),
+                format!("{:.1}%", s.cpu),
+                format!("{:.1}%", s.memory),
+                format!("{:.1}%", s.swap),
+                s.processes
+                    .first()
+                    .map(|p| p.name.clone())
+                    .unwrap_or_default(),
+            ])
+        })
+        .collect::<Vec<Row>>();
+
+    let header = Row::new(vec![
+        "Time (UTC)".to_string(),
+        "CPU".to_string(),
+        "Memory".to_string(),
+        "Swap".to_string(),
+        "Top Process".to_string(),
+    ]);
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(30), // Time
+            Constraint::Percentage(12), // CPU
+            Constraint::Percentage(12), // Memory
+            Constraint::Percentage(12), // Swap
+            Constraint::Percentage(34), // Top Process
+        ],
+    )
+    .block(
+        Block::new()
+            .borders(Borders::ALL)
+            .title(format!("Samples ({})", browser.samples.len()))
+            .style(Style::default()),
+    )
+    .header(header)
+    .row_highlight_style(Style::new().bold());
+    StatefulWidget::render(table, area, buf, state);
+}
+
+fn render_processes(selected: Option<&Sample>, area: Rect, buf: &mut Buffer) {
+    let rows = selected
+        .map(|s| s.processes.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .map(|p| {
+            Row::new(vec![
+                p.pid.to_string(),
+                p.name.clone(),
+                format!("{:.1}%", p.cpu),
+                format_size(p.memory, BINARY),
+            ])
+        })
+        .collect::<Vec<Row>>();
+
+    let header = Row::new(vec![
+        "PID".to_string(),
+        "Name".to_string(),
+        "CPU".to_string(),
+        "Memory".to_string(),
+    ]);
+
+    let title = match selected {
+        Some(s) => format!("Top Processes at {}", format_time(s.time)),
+        None => "Top Processes".to_string(),
+    };
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(15), // PID
+            Constraint::Percentage(45), // Name
+            Constraint::Percentage(20), // CPU
+            Constraint::Percentage(20), // Memory
+        ],
+    )
+    .block(
+        Block::new()
+            .borders(Borders::ALL)
+            .title(title)
+            .style(Style::default()),
+    )
+    .header(header);
+    Widget::render(table, area, buf);
+}
+
+impl UpdateableWidgetWithState for HistoryBrowser {
+    fn update_with_state(&mut self, monitor: &system_monitor::Monitor, state: &mut AppState) {
+        let Some(store) = self.store.as_mut() else {
+            return;
+        };
+        if let Err(e) = store.record(Sample::collect(monitor)) {
+            self.error = Some(e.to_string());
+            self.store = None;
+            return;
+        }
+
+        // The files are only read once browsed, and only queried while shown
+        if state.page != Page::History {
+            return;
+        }
+        let history_state = &mut state.history_state;
+        let span = RANGES[history_state.range].1;
+        let to = history::now().saturating_sub(history_state.offset);
+        let from = to.saturating_sub(span);
+        let (resolution, mut samples) = match store.query(from, to) {
+            Ok(result) => result,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                self.store = None;
+                return;
+            }
+        };
+        samples.reverse();
+
+        // Keep the selection on the same sample as new ones come in, or on the
+        // next older one once it is averaged away. The newest sample stays
+        // selected when it is.
+        let selected_time = history_state
+            .state
+            .selected()
+            .filter(|i| *i > 0)
+            .and_then(|i| self.samples.get(i))
+            .map(|s| s.time);
+        if let Some(time) = selected_time {
+            let position = samples.iter().position(|s| s.time <= time);
+            history_state
+                .state
+                .select(Some(position.unwrap_or(samples.len().saturating_sub(1))));
+        }
+
+        self.from = from;
+        self.to = to;
+        self.resolution = resolution;
+        self.samples = samples;
+    }
+}
@@ -16,6 +16,9 @@ pub struct Info {
     kernel_version: String,
     uptime: u64,
     cpu_name: String,
+    // Address of the agent and the state of the connection to it, when
+    // showing a remote host
+    agent: Option<String>,
 }
 
 impl Info {
@@ -26,13 +29,14 @@ impl Info {
             kernel_version: "".to_string(),
             uptime: 0,
             cpu_name: "".to_string(),
+            agent: None,
         }
     }
 }
 
 impl Widget for &Info {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let paragraph = Paragraph::new(vec![
+        let mut lines = vec![
             Line::from(vec![Span::from("Host Name: "), Span::from(&self.host_name)]),
             Line::from(vec![Span::from("OS Name: "), Span::from(&self.os_name)]),
             Line::from(vec![
@@ -44,9 +48,11 @@ impl Widget for &Info {
                 Span::from(format_duration(Duration::from_secs(self.uptime)).to_string()),
             ]),
             Line::from(vec![Span::from("CPU Name: "), Span::from(&self.cpu_name)]),
-        ])
-        .alignment(Alignment::Center)
-        .block(
+        ];
+        if let Some(agent) = &self.agent {
+            lines.push(Line::from(vec![Span::from("Agent: "), Span::from(agent)]));
+        }
+        let paragraph = Paragraph::new(lines).alignment(Alignment::Center).block(
             Block::new()
                 .borders(Borders::ALL)
                 .title("System Info")
@@ -63,5 +69,8 @@ impl UpdateableWidget for Info {
         self.kernel_version = monitor.get_kernel_version();
         self.uptime = monitor.get_uptime();
         self.cpu_name = monitor.get_cpu_name();
+        self.agent = monitor
+            .get_remote_status()
+            .map(|(address, status)| format!("{} ({})", address, status));
     }
 }
@@ -0,0 +1,213 @@
+use crate::procfs::{fd::OpenFile, maps::Mapping};
+use crate::update::UpdateableWidgetWithState;
+use crate::{app::AppState, system_monitor};
+use humansize::{format_size, BINARY};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Row, StatefulWidget, Table, TableState, Widget},
+};
+
+#[derive(Clone, Copy, PartialEq, strum::Display)]
+pub enum InspectorTab {
+    #[strum(serialize = "Open Files")]
+    Files,
+    #[strum(serialize = "Memory Maps")]
+    Maps,
+}
+
+pub struct Inspector {
+    pid: Option<u32>,
+    name: String,
+    tab: InspectorTab,
+    search: String,
+    files: Vec<OpenFile>,
+    mappings: Vec<Mapping>,
+    rollup: Option<Mapping>,
+}
+
+impl Inspector {
+    pub fn new() -> Self {
+        Inspector {
+            pid: None,
+            name: String::new(),
+            tab: InspectorTab::Files,
+            search: String::new(),
+            files: Vec::new(),
+            mappings: Vec::new(),
+            rollup: None,
+        }
+    }
+
+    pub fn open(&mut self, pid: u32, name: String) {
+        self.pid = Some(pid);
+        self.name = name;
+    }
+
+    pub fn close(&mut self) {
+        self.pid = None;
+        self.files.clear();
+        self.mappings.clear();
+        self.rollup = None;
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        text.to_lowercase().contains(&self.search.to_lowercase())
+    }
+}
+
+pub struct InspectorState {
+    pub state: TableState,
+    pub tab: InspectorTab,
+    pub search: String,
+}
+
+impl InspectorState {
+    pub fn new() -> Self {
+        InspectorState {
+            state: TableState::default().with_selected(Some(0)),
+            tab: InspectorTab::Files,
+            search: String::new(),
+        }
+    }
+
+    pub fn switch_tab(&mut self) {
+        self.tab = match self.tab {
+            InspectorTab::Files => InspectorTab::Maps,
+            InspectorTab::Maps => InspectorTab::Files,
+        };
+        self.state.select(Some(0));
+    }
+
+    pub fn select_prev(&mut self) {
+        self.state.select_previous();
+    }
+
+    pub fn select_next(&mut self) {
+        self.state.select_next();
+    }
+}
+
+impl StatefulWidget for &Inspector {
+    type State = TableState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .title(format!(
+                "{} of {} ({}) - Tab to switch, / to search",
+                self.tab,
+                self.name,
+                self.pid.unwrap_or_default()
+            ))
+            .style(Style::default());
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [summary_area, table_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+
+        let summary = match (self.tab, &self.rollup) {
+            (InspectorTab::Files, _) => format!("{} open file descriptors", self.files.len()),
+            (InspectorTab::Maps, Some(r)) => format!(
+                "{} mappings, RSS {}, PSS {}, Shared {}, Private {}, Swap {}",
+                self.mappings.len(),
+                format_size(r.rss, BINARY),
+                format_size(r.pss, BINARY),
+                format_size(r.shared, BINARY),
+                format_size(r.private, BINARY),
+                format_size(r.swap, BINARY),
+            ),
+            (InspectorTab::Maps, None) => format!("{} mappings", self.mappings.len()),
+        };
+        let mut line = vec![Span::from(summary)];
+        if !self.search.is_empty() {
+            line.push(Span::from(format!("  Search: {}", self.search)).yellow());
+        }
+        Paragraph::new(Line::from(line)).render(summary_area, buf);
+
+        let (header, rows, widths) = match self.tab {
+            InspectorTab::Files => (
+                Row::new(vec!["FD", "Type", "Offset", "Flags", "Path"]),
+                self.files
+                    .iter()
+                    .filter(|f| self.matches(&f.path) || self.matches(&f.kind))
+                    .map(|f| {
+                        Row::new(vec![
+                            f.fd.to_string(),
+                            f.kind.clone(),
+                            f.offset.to_string(),
+                            f.flags.clone(),
+                            f.path.clone(),
+                        ])
+                    })
+                    .collect::<Vec<Row>>(),
+                vec![
+                    Constraint::Percentage(6),  // FD
+                    Constraint::Percentage(12), // Type
+                    Constraint::Percentage(10), // Offset
+                    Constraint::Percentage(22), // Flags
+                    Constraint::Percentage(50), // Path
+                ],
+            ),
+            InspectorTab::Maps => (
+                Row::new(vec![
+                    "Address", "Perms", "Offset", "RSS", "PSS", "Shared", "Private", "Swap", "Path",
+                ]),
+                self.mappings
+                    .iter()
+                    .filter(|m| self.matches(&m.path) || self.matches(&m.perms))
+                    .map(|m| {
+                        Row::new(vec![
+                            format!("{:x}-{:x}", m.start, m.end),
+                            m.perms.clone(),
+                            format!("{:x}", m.offset),
+                            format_size(m.rss, BINARY),
+                            format_size(m.pss, BINARY),
+                            format_size(m.shared, BINARY),
+                            format_size(m.private, BINARY),
+                            format_size(m.swap, BINARY),
+                            m.path.clone(),
+                        ])
+                    })
+                    .collect::<Vec<Row>>(),
+                vec![
+                    Constraint::Percentage(20), // Address
+                    Constraint::Percentage(5),  // Perms
+                    Constraint::Percentage(7),  // Offset
+                    Constraint::Percentage(8),  // RSS
+                    Constraint::Percentage(8),  // PSS
+                    Constraint::Percentage(8),  // Shared
+                    Constraint::Percentage(8),  // Private
+                    Constraint::Percentage(8),  // Swap
+                    Constraint::Percentage(28), // Path
+                ],
+            ),
+        };
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .row_highlight_style(Style::new().bold());
+        StatefulWidget::render(table, table_area, buf, state);
+    }
+}
+
+impl UpdateableWidgetWithState for Inspector {
+    fn update_with_state(&mut self, monitor: &system_monitor::Monitor, state: &mut AppState) {
+        let Some(pid) = self.pid else {
+            return;
+        };
+        self.tab = state.inspector_state.tab;
+        self.search = state.inspector_state.search.clone();
+        // Only read the side that is shown, smaps in particular is expensive
+        match self.tab {
+            InspectorTab::Files => self.files = monitor.get_process_open_files(pid),
+            InspectorTab::Maps => {
+                self.mappings = monitor.get_process_mappings(pid);
+                self.rollup = monitor.get_process_memory_rollup(pid);
+            }
+        }
+    }
+}
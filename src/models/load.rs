@@ -0,0 +1,297 @@
+use crate::app::AppState;
+use crate::models::chart::{time_axis, Series, Window};
+use crate::procfs::pressure::{Resource, Stall};
+use crate::system_monitor;
+use crate::update::{UpdateableWidget, UpdateableWidgetWithState};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Color, Style, Stylize},
+    symbols::Marker,
+    widgets::{
+        Axis, Block, Borders, Chart, Dataset, GraphType, Row, StatefulWidget, Table, TableState,
+        Widget,
+    },
+};
+use strum::IntoEnumIterator;
+
+pub struct LoadHistogram {
+    one: Series<f64>,
+    five: Series<f64>,
+    fifteen: Series<f64>,
+    running: u32,
+    total: u32,
+    cpu_count: usize,
+    window: Window,
+}
+
+impl LoadHistogram {
+    pub fn new() -> Self {
+        LoadHistogram {
+            one: Series::new(),
+            five: Series::new(),
+            fifteen: Series::new(),
+            running: 0,
+            total: 0,
+            cpu_count: 1,
+            window: Window::OneMinute,
+        }
+    }
+}
+
+impl Widget for &LoadHistogram {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let one = self.one.points(self.window);
+        let five = self.five.points(self.window);
+        let fifteen = self.fifteen.points(self.window);
+        let datasets = vec![
+            Dataset::default()
+                .name("1 min")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().red())
+                .data(&one),
+            Dataset::default()
+                .name("5 min")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().yellow())
+                .data(&five),
+            Dataset::default()
+                .name("15 min")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().green())
+                .data(&fifteen),
+        ];
+
+        // A load equal to the number of cores means the CPUs are fully used, keep that line in view
+        let y_max = one
+            .iter()
+            .chain(five.iter())
+            .chain(fifteen.iter())
+            .map(|(_, y)| *y)
+            .fold(self.cpu_count as f64, f64::max);
+        let y_axis = Axis::default()
+            .style(Style::default().white())
+            .bounds([0.0, y_max])
+            .labels(vec!["0".to_string(), format!("{:.1}", y_max)]);
+
+        let chart = Chart::new(datasets)
+            .block(
+                Block::new()
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        "Load Average (Run Queue {}/{}, {} Cores)",
+                        self.running, self.total, self.cpu_count
+                    ))
+                    .style(Style::default()),
+            )
+            .x_axis(time_axis(self.window))
+            .y_axis(y_axis);
+
+        chart.render(area, buf);
+    }
+}
+
+impl UpdateableWidgetWithState for LoadHistogram {
+    fn update_with_state(&mut self, monitor: &system_monitor::Monitor, state: &mut AppState) {
+        self.window = state.chart_window;
+        self.cpu_count = monitor.get_cpu_count();
+        if let Some(load) = monitor.get_load_average() {
+            self.one.push(load.one);
+            self.five.push(load.five);
+            self.fifteen.push(load.fifteen);
+            self.running = load.running;
+            self.total = load.total;
+        }
+    }
+}
+
+pub struct PressureHistogram {
+    // "some" avg10 of each resource
+    stalls: Vec<(Resource, Series<f64>)>,
+    window: Window,
+}
+
+impl PressureHistogram {
+    pub fn new() -> Self {
+        PressureHistogram {
+            stalls: Resource::iter().map(|r| (r, Series::new())).collect(),
+            window: Window::OneMinute,
+        }
+    }
+}
+
+impl Widget for &PressureHistogram {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let colors = [Color::Cyan, Color::Yellow, Color::Magenta];
+        let points: Vec<Vec<(f64, f64)>> = self
+            .stalls
+            .iter()
+            .map(|(_, stalls)| stalls.points(self.window))
+            .collect();
+        let datasets = self
+            .stalls
+            .iter()
+            .zip(&points)
+            .zip(colors)
+            .map(|(((resource, _), stalls), color)| {
+                Dataset::default()
+                    .name(format!("{}(%)", resource))
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(color))
+                    .data(stalls)
+            })
+            .collect::<Vec<Dataset>>();
+
+        let y_axis = Axis::default()
+            .style(Style::default().white())
+            .bounds([0.0, 100.0]);
+
+        let chart = Chart::new(datasets)
+            .block(
+                Block::new()
+                    .borders(Borders::ALL)
+                    .title("Pressure Histogram (some, avg10)")
+                    .style(Style::default()),
+            )
+            .x_axis(time_axis(self.window))
+            .y_axis(y_axis);
+
+        chart.render(area, buf);
+    }
+}
+
+impl UpdateableWidgetWithState for PressureHistogram {
+    fn update_with_state(&mut self, monitor: &system_monitor::Monitor, state: &mut AppState) {
+        self.window = state.chart_window;
+        for (resource, stalls) in self.stalls.iter_mut() {
+            if let Some(pressure) = monitor.get_pressure(*resource) {
+                stalls.push(pressure.some.avg10 as f64);
+            }
+        }
+    }
+}
+
+struct ResourcePressure {
+    resource: Resource,
+    some: Option<Stall>,
+    full: Option<Stall>,
+}
+
+pub struct PressureTable {
+    resources: Vec<ResourcePressure>,
+}
+
+impl PressureTable {
+    pub fn new() -> Self {
+        PressureTable {
+            resources: Vec::new(),
+        }
+    }
+
+    pub fn get_resource(&self, row: usize) -> Option<Resource> {
+        self.resources.get(row).map(|r| r.resource)
+    }
+}
+
+pub struct PressureTableState {
+    pub state: TableState,
+}
+
+impl PressureTableState {
+    pub fn new() -> Self {
+        PressureTableState {
+            state: TableState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.state.select_previous();
+    }
+
+    pub fn select_next(&mut self) {
+        self.state.select_next();
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.state.selected()
+    }
+}
+
+fn format_stall(stall: Option<Stall>) -> Vec<String> {
+    match stall {
+        Some(stall) => vec![
+            format!("{:.2}%", stall.avg10),
+            format!("{:.2}%", stall.avg60),
+            format!("{:.2}%", stall.avg300),
+        ],
+        None => vec!["-".to_string(); 3],
+    }
+}
+
+impl StatefulWidget for &PressureTable {
+    type State = TableState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let rows = self
+            .resources
+            .iter()
+            .map(|r| {
+                let mut cells = vec![r.resource.to_string()];
+                cells.extend(format_stall(r.some));
+                cells.extend(format_stall(r.full));
+                Row::new(cells)
+            })
+            .collect::<Vec<Row>>();
+
+        let header = Row::new(vec![
+            "Resource".to_string(),
+            "Some 10s".to_string(),
+            "Some 60s".to_string(),
+            "Some 300s".to_string(),
+            "Full 10s".to_string(),
+            "Full 60s".to_string(),
+            "Full 300s".to_string(),
+        ]);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(16), // Resource
+                Constraint::Percentage(14), // Some 10s
+                Constraint::Percentage(14), // Some 60s
+                Constraint::Percentage(14), // Some 300s
+                Constraint::Percentage(14), // Full 10s
+                Constraint::Percentage(14), // Full 60s
+                Constraint::Percentage(14), // Full 300s
+            ],
+        )
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title("Pressure Stall Information")
+                .style(Style::default()),
+        )
+        .header(header)
+        .row_highlight_style(Style::new().bold());
+        StatefulWidget::render(table, area, buf, state);
+    }
+}
+
+impl UpdateableWidget for PressureTable {
+    fn update(&mut self, monitor: &system_monitor::Monitor) {
+        self.resources = Resource::iter()
+            .map(|resource| {
+                let pressure = monitor.get_pressure(resource);
+                ResourcePressure {
+                    resource,
+                    some: pressure.as_ref().map(|p| p.some),
+                    full: pressure.and_then(|p| p.full),
+                }
+            })
+            .collect();
+    }
+}
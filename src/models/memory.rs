@@ -1,50 +1,54 @@
+use crate::app::AppState;
+use crate::models::chart::{time_axis, Series, Window};
+use crate::models::cpu::render_stack;
+use crate::procfs::meminfo::MemInfo;
 use crate::system_monitor;
-use crate::update::UpdateableWidget;
+use crate::update::{UpdateableWidget, UpdateableWidgetWithState};
+use humansize::{format_size, BINARY};
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
-    style::{Style, Stylize},
+    style::{Color, Style, Stylize},
     symbols::Marker,
-    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Widget},
+    text::{Line, Span},
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph, Widget, Wrap},
 };
 
 pub struct Memory {
-    memory_usage: Vec<(f64, f64)>,
-    swap_usage: Vec<(f64, f64)>,
-    update_count: usize,
+    memory_usage: Series<f64>,
+    swap_usage: Series<f64>,
+    window: Window,
 }
 
 impl Memory {
     pub fn new() -> Self {
         Memory {
-            memory_usage: Vec::new(),
-            swap_usage: Vec::new(),
-            update_count: 0,
+            memory_usage: Series::new(),
+            swap_usage: Series::new(),
+            window: Window::OneMinute,
         }
     }
 }
 
 impl Widget for &Memory {
     fn render(self, area: Rect, buf: &mut Buffer) {
+        let memory_usage = self.memory_usage.points(self.window);
+        let swap_usage = self.swap_usage.points(self.window);
         let datasets = vec![
             Dataset::default()
                 .name("Memory Usage(%)")
                 .marker(Marker::Braille)
                 .graph_type(GraphType::Line)
                 .style(Style::default().yellow())
-                .data(&self.memory_usage),
+                .data(&memory_usage),
             Dataset::default()
                 .name("Swap Usage(%)")
                 .marker(Marker::Braille)
                 .graph_type(GraphType::Line)
                 .style(Style::default().magenta())
-                .data(&self.swap_usage),
+                .data(&swap_usage),
         ];
 
-        let x_axis = Axis::default()
-            .style(Style::default().white())
-            .bounds([self.update_count as f64 - 100.0, self.update_count as f64]);
-
         let y_axis = Axis::default()
             .style(Style::default().white())
             .bounds([0.0, 100.0]);
@@ -56,23 +60,195 @@ impl Widget for &Memory {
                     .title("Memory Histogram")
                     .style(Style::default()),
             )
-            .x_axis(x_axis)
+            .x_axis(time_axis(self.window))
             .y_axis(y_axis);
 
         chart.render(area, buf);
     }
 }
 
-impl UpdateableWidget for Memory {
-    fn update(&mut self, monitor: &system_monitor::Monitor) {
-        self.update_count += 1;
-        self.memory_usage.push((
-            self.update_count as f64,
-            monitor.get_used_memory().0 as f64 / monitor.get_total_memory().0 as f64 * 100.0,
-        ));
-        self.swap_usage.push((
-            self.update_count as f64,
-            monitor.get_used_memory().1 as f64 / monitor.get_total_memory().1 as f64 * 100.0,
+impl UpdateableWidgetWithState for Memory {
+    fn update_with_state(&mut self, monitor: &system_monitor::Monitor, state: &mut AppState) {
+        self.window = state.chart_window;
+        self.memory_usage
+            .push(monitor.get_used_memory().0 as f64 / monitor.get_total_memory().0 as f64 * 100.0);
+        self.swap_usage
+            .push(monitor.get_used_memory().1 as f64 / monitor.get_total_memory().1 as f64 * 100.0);
+    }
+}
+
+// The parts of physical memory from the bottom of a stack to its top, free memory is left blank
+fn segments(meminfo: &MemInfo) -> [(&'static str, Color, u64); 6] {
+    [
+        ("Used", Color::Green, meminfo.used()),
+        ("Slab", Color::Red, meminfo.slab_unreclaimable),
+        ("Slab Reclaimable", Color::Magenta, meminfo.slab_reclaimable),
+        ("Buffers", Color::Blue, meminfo.buffers),
+        ("Page Cache", Color::Yellow, meminfo.page_cache()),
+        ("Shared", Color::Cyan, meminfo.shared),
+    ]
+}
+
+fn stack(meminfo: &MemInfo) -> Vec<(Color, f64)> {
+    segments(meminfo)
+        .iter()
+        .map(|(_, color, bytes)| (*color, *bytes as f64 / meminfo.total.max(1) as f64 * 100.0))
+        .collect()
+}
+
+pub struct MemoryBreakdown {
+    meminfo: Option<MemInfo>,
+    swap_in_rate: f64,
+    swap_out_rate: f64,
+}
+
+impl MemoryBreakdown {
+    pub fn new() -> Self {
+        MemoryBreakdown {
+            meminfo: None,
+            swap_in_rate: 0.0,
+            swap_out_rate: 0.0,
+        }
+    }
+}
+
+impl Widget for &MemoryBreakdown {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .title("Memory Breakdown")
+            .style(Style::default());
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let Some(m) = &self.meminfo else {
+            return;
+        };
+        if inner.height == 0 {
+            return;
+        }
+
+        // First row: the stack laid on its side, one cell per 1/width of the total
+        let mut right = 0.0;
+        let bar: Vec<(Color, f64)> = stack(m)
+            .into_iter()
+            .map(|(color, percent)| {
+                right += percent;
+                (color, right)
+            })
+            .collect();
+        for column in 0..inner.width {
+            let level = (column as f64 + 0.5) / inner.width as f64 * 100.0;
+            let cell = &mut buf[(inner.x + column, inner.y)];
+            match bar.iter().find(|(_, right)| *right >= level) {
+                Some((color, _)) => cell.set_symbol("█").set_fg(*color),
+                None => cell.set_symbol("░").set_fg(Color::DarkGray),
+            };
+        }
+
+        let mut legend: Vec<Span> = segments(m)
+            .iter()
+            .map(|(label, color, bytes)| {
+                Span::styled(
+                    format!("■ {} {}  ", label, format_size(*bytes, BINARY)),
+                    Style::default().fg(*color),
+                )
+            })
+            .collect();
+        legend.push(Span::styled(
+            format!("░ Free {}", format_size(m.free, BINARY)),
+            Style::default().dark_gray(),
         ));
+
+        let committed_style = if m.committed > m.commit_limit {
+            Style::default().red()
+        } else {
+            Style::default()
+        };
+        let lines = vec![
+            Line::from(legend),
+            Line::from(format!(
+                "Total: {}  Available: {}",
+                format_size(m.total, BINARY),
+                format_size(m.available, BINARY)
+            )),
+            Line::from(format!(
+                "Dirty: {}  Writeback: {}",
+                format_size(m.dirty, BINARY),
+                format_size(m.writeback, BINARY)
+            )),
+            Line::from(format!(
+                "Huge Pages: {} free of {}",
+                format_size(m.huge_pages_free, BINARY),
+                format_size(m.huge_pages_total, BINARY)
+            )),
+            Line::from(Span::styled(
+                format!(
+                    "Committed: {} of {} limit",
+                    format_size(m.committed, BINARY),
+                    format_size(m.commit_limit, BINARY)
+                ),
+                committed_style,
+            )),
+            Line::from(format!(
+                "Swap: {} used of {}  In: {}/s  Out: {}/s",
+                format_size(m.swap_total.saturating_sub(m.swap_free), BINARY),
+                format_size(m.swap_total, BINARY),
+                format_size(self.swap_in_rate as u64, BINARY),
+                format_size(self.swap_out_rate as u64, BINARY)
+            )),
+        ];
+        let details = Rect::new(inner.x, inner.y + 1, inner.width, inner.height - 1);
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: true })
+            .render(details, buf);
+    }
+}
+
+impl UpdateableWidget for MemoryBreakdown {
+    fn update(&mut self, monitor: &system_monitor::Monitor) {
+        self.meminfo = monitor.get_meminfo();
+        (self.swap_in_rate, self.swap_out_rate) = monitor.get_swap_rates();
+    }
+}
+
+pub struct MemoryBreakdownHistogram {
+    history: Series<MemInfo>,
+}
+
+impl MemoryBreakdownHistogram {
+    pub fn new() -> Self {
+        MemoryBreakdownHistogram {
+            history: Series::new(),
+        }
+    }
+}
+
+impl Widget for &MemoryBreakdownHistogram {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .title("Memory Breakdown Histogram")
+            .style(Style::default());
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        // One column per update, the latest on the right
+        let history: Vec<_> = self.history.latest(inner.width as usize).collect();
+        let start = inner.right() - history.len() as u16;
+        for (i, (_, meminfo)) in history.into_iter().enumerate() {
+            render_stack(
+                buf,
+                Rect::new(start + i as u16, inner.y, 1, inner.height),
+                &stack(meminfo),
+            );
+        }
+    }
+}
+
+impl UpdateableWidget for MemoryBreakdownHistogram {
+    fn update(&mut self, monitor: &system_monitor::Monitor) {
+        if let Some(meminfo) = monitor.get_meminfo() {
+            self.history.push(meminfo);
+        }
     }
 }
@@ -0,0 +1,409 @@
+use crate::models::chart::{time_axis, Series, Window};
+use crate::update::UpdateableWidgetWithState;
+use crate::{app::AppState, system_monitor};
+use humansize::{format_size, BINARY};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Color, Style, Stylize},
+    symbols::Marker,
+    widgets::{
+        Axis, Block, Borders, Chart, Dataset, GraphType, Row, StatefulWidget, Table, TableState,
+        Widget,
+    },
+};
+use std::collections::{BTreeMap, HashSet};
+
+// Colors handed out to the (receive, transmit) lines of each interface in turn
+const COLORS: [(Color, Color); 4] = [
+    (Color::Green, Color::Red),
+    (Color::Cyan, Color::Magenta),
+    (Color::Yellow, Color::Blue),
+    (Color::LightGreen, Color::LightRed),
+];
+
+struct History {
+    receive_rate: Series<f64>,
+    transmit_rate: Series<f64>,
+}
+
+pub struct NetworkHistogram {
+    histories: BTreeMap<String, History>,
+    hidden: HashSet<String>,
+    window: Window,
+}
+
+impl NetworkHistogram {
+    pub fn new() -> Self {
+        NetworkHistogram {
+            histories: BTreeMap::new(),
+            hidden: HashSet::new(),
+            window: Window::OneMinute,
+        }
+    }
+}
+
+// Chart points of the receive and transmit rates of an interface
+struct Points {
+    receive_rate: Vec<(f64, f64)>,
+    transmit_rate: Vec<(f64, f64)>,
+}
+
+impl Widget for &NetworkHistogram {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let shown: Vec<(&String, Points)> = self
+            .histories
+            .iter()
+            .filter(|(name, _)| !self.hidden.contains(*name))
+            .map(|(name, history)| {
+                let points = Points {
+                    receive_rate: history.receive_rate.points(self.window),
+                    transmit_rate: history.transmit_rate.points(self.window),
+                };
+                (name, points)
+            })
+            .collect();
+
+        let datasets = shown
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (name, history))| {
+                let (rx_color, tx_color) = COLORS[i % COLORS.len()];
+                [
+                    Dataset::default()
+                        .name(format!("{} Rx(/s)", name))
+                        .marker(Marker::Braille)
+                        .graph_type(GraphType::Line)
+                        .style(Style::default().fg(rx_color))
+                        .data(&history.receive_rate),
+                    Dataset::default()
+                        .name(format!("{} Tx(/s)", name))
+                        .marker(Marker::Braille)
+                        .graph_type(GraphType::Line)
+                        .style(Style::default().fg(tx_color))
+                        .data(&history.transmit_rate),
+                ]
+            })
+            .collect::<Vec<Dataset>>();
+
+        // Scale to the busiest visible point, rates have no natural upper bound
+        let y_max = shown
+            .iter()
+            .flat_map(|(_, p)| p.receive_rate.iter().chain(p.transmit_rate.iter()))
+            .map(|(_, y)| *y)
+            .fold(1024.0, f64::max);
+        let y_axis = Axis::default()
+            .style(Style::default().white())
+            .bounds([0.0, y_max])
+            .labels(vec![
+                "0".to_string(),
+                format!("{}/s", format_size(y_max as u64, BINARY)),
+            ]);
+
+        let chart = Chart::new(datasets)
+            .block(
+                Block::new()
+                    .borders(Borders::ALL)
+                    .title("Network Histogram")
+                    .style(Style::default()),
+            )
+            .x_axis(time_axis(self.window))
+            .y_axis(y_axis);
+
+        chart.render(area, buf);
+    }
+}
+
+impl UpdateableWidgetWithState for NetworkHistogram {
+    fn update_with_state(&mut self, monitor: &system_monitor::Monitor, state: &mut AppState) {
+        self.window = state.chart_window;
+        self.hidden = state.network_table_state.hidden.clone();
+        self.record(monitor.get_networks());
+    }
+}
+
+impl NetworkHistogram {
+    // Adds the rates of a snapshot of the interfaces to their histories
+    fn record(&mut self, networks: Vec<system_monitor::NetworkInfo>) {
+        // Forget interfaces that went away
+        self.histories
+            .retain(|name, _| networks.iter().any(|n| &n.name == name));
+        for n in networks {
+            let history = self.histories.entry(n.name).or_insert(History {
+                receive_rate: Series::new(),
+                transmit_rate: Series::new(),
+            });
+            history.receive_rate.push(n.receive_rate);
+            history.transmit_rate.push(n.transmit_rate);
+        }
+    }
+}
+
+pub struct Interface {
+    pub name: String,
+    receive_rate: f64,
+    transmit_rate: f64,
+    total_received: u64,
+    total_transmitted: u64,
+    packets_received: u64,
+    packets_transmitted: u64,
+    errors_received: u64,
+    errors_transmitted: u64,
+    addresses: Vec<String>,
+    shown: bool,
+}
+
+pub struct NetworkTable {
+    interfaces: Vec<Interface>,
+}
+
+impl NetworkTable {
+    pub fn new() -> Self {
+        NetworkTable {
+            interfaces: Vec::new(),
+        }
+    }
+
+    pub fn get_interface(&self, row: usize) -> Option<&Interface> {
+        self.interfaces.get(row)
+    }
+}
+
+pub struct NetworkTableState {
+    pub state: TableState,
+    // Interfaces left out of the histogram
+    pub hidden: HashSet<String>,
+}
+
+impl NetworkTableState {
+    pub fn new() -> Self {
+        NetworkTableState {
+            state: TableState::default().with_selected(Some(0)),
+            hidden: HashSet::new(),
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.state.select_previous();
+    }
+
+    pub fn select_next(&mut self) {
+        self.state.select_next();
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.state.selected()
+    }
+
+    pub fn toggle_interface(&mut self, name: &str) {
+        if !self.hidden.remove(name) {
+            self.hidden.insert(name.to_string());
+        }
+    }
+}
+
+impl StatefulWidget for &NetworkTable {
+    type State = TableState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let rows = self
+            .interfaces
+            .iter()
+            .map(|i| {
+                let style = if i.shown {
+                    Style::default()
+                } else {
+                    Style::default().dark_gray()
+                };
+                Row::new(vec![
+                    if i.shown { "[x]" } else { "[ ]" }.to_string(),
+                    i.name.clone(),
+                    format!("{}/s", format_size(i.receive_rate as u64, BINARY)),
+                    format!("{}/s", format_size(i.transmit_rate as u64, BINARY)),
+                    format_size(i.total_received, BINARY),
+                    format_size(i.total_transmitted, BINARY),
+                    format!("{}/{}", i.packets_received, i.packets_transmitted),
+                    format!("{}/{}", i.errors_received, i.errors_transmitted),
+                    i.addresses.join(", "),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<Row>>();
+
+        let header = Row::new(vec![
+            "Show".to_string(),
+            "Interface".to_string(),
+            "Rx".to_string(),
+            "Tx".to_string(),
+            "Total Rx".to_string(),
+            "Total Tx".to_string(),
+            "Packets Rx/Tx".to_string(),
+            "Errors Rx/Tx".to_string(),
+            "Addresses".to_string(),
+        ]);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(5),  // Show
+                Constraint::Percentage(10), // Interface
+                Constraint::Percentage(9),  // Rx
+                Constraint::Percentage(9),  // Tx
+                Constraint::Percentage(9),  // Total Rx
+                Constraint::Percentage(9),  // Total Tx
+                Constraint::Percentage(14), // Packets Rx/Tx
+                Constraint::Percentage(9),  // Errors Rx/Tx
+                Constraint::Percentage(26), // Addresses
+            ],
+        )
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title("Network Interfaces")
+                .style(Style::default()),
+        )
+        .header(header)
+        .row_highlight_style(Style::new().bold());
+        StatefulWidget::render(table, area, buf, state);
+    }
+}
+
+impl UpdateableWidgetWithState for NetworkTable {
+    fn update_with_state(&mut self, monitor: &system_monitor::Monitor, state: &mut AppState) {
+        self.refresh(monitor.get_networks(), &state.network_table_state.hidden);
+    }
+}
+
+impl NetworkTable {
+    fn refresh(&mut self, networks: Vec<system_monitor::NetworkInfo>, hidden: &HashSet<String>) {
+        self.interfaces = networks
+            .into_iter()
+            .map(|n| Interface {
+                shown: !hidden.contains(&n.name),
+                name: n.name,
+                receive_rate: n.receive_rate,
+                transmit_rate: n.transmit_rate,
+                total_received: n.total_received,
+                total_transmitted: n.total_transmitted,
+                packets_received: n.packets_received,
+                packets_transmitted: n.packets_transmitted,
+                errors_received: n.errors_received,
+                errors_transmitted: n.errors_transmitted,
+                addresses: n.addresses,
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use system_monitor::NetworkInfo;
+
+    // An interface with the given byte counters, at the rates they moved at
+    // over one second since the previous sample. Like sysinfo, a counter that
+    // went backwards counts as no traffic.
+    fn interface(name: &str, previous: (u64, u64), total: (u64, u64)) -> NetworkInfo {
+        NetworkInfo {
+            name: name.to_string(),
+            receive_rate: total.0.saturating_sub(previous.0) as f64,
+            transmit_rate: total.1.saturating_sub(previous.1) as f64,
+            total_received: total.0,
+            total_transmitted: total.1,
+            packets_received: 0,
+            packets_transmitted: 0,
+            errors_received: 0,
+            errors_transmitted: 0,
+            addresses: Vec::new(),
+        }
+    }
+
+    fn rates(histogram: &NetworkHistogram, name: &str) -> Vec<(f64, f64)> {
+        let history = &histogram.histories[name];
+        history
+            .receive_rate
+            .latest(usize::MAX)
+            .zip(history.transmit_rate.latest(usize::MAX))
+            .map(|((_, rx), (_, tx))| (*rx, *tx))
+            .collect()
+    }
+
+    #[test]
+    fn records_rates_per_interface() {
+        let mut histogram = NetworkHistogram::new();
+        histogram.record(vec![
+            interface("eth0", (0, 0), (1000, 500)),
+            interface("lo", (0, 0), (10, 10)),
+        ]);
+        histogram.record(vec![
+            interface("eth0", (1000, 500), (5000, 700)),
+            interface("lo", (10, 10), (20, 20)),
+        ]);
+        // The counters reset, e.g. when the driver is reloaded
+        histogram.record(vec![
+            interface("eth0", (5000, 700), (300, 100)),
+            interface("lo", (20, 20), (30, 30)),
+        ]);
+        assert_eq!(
+            rates(&histogram, "eth0"),
+            vec![(1000.0, 500.0), (4000.0, 200.0), (0.0, 0.0)]
+        );
+        assert_eq!(
+            rates(&histogram, "lo"),
+            vec![(10.0, 10.0), (10.0, 10.0), (10.0, 10.0)]
+        );
+
+        // An interface that goes away starts from scratch when it comes back
+        histogram.record(vec![interface("lo", (30, 30), (40, 40))]);
+        assert!(!histogram.histories.contains_key("eth0"));
+        histogram.record(vec![
+            interface("eth0", (0, 0), (64, 64)),
+            interface("lo", (40, 40), (50, 50)),
+        ]);
+        assert_eq!(rates(&histogram, "eth0"), vec![(64.0, 64.0)]);
+        assert_eq!(rates(&histogram, "lo").len(), 5);
+    }
+
+    #[test]
+    fn hides_interfaces() {
+        let mut table = NetworkTable::new();
+        let mut state = NetworkTableState::new();
+        let shown = |table: &NetworkTable| -> Vec<(String, bool)> {
+            table
+                .interfaces
+                .iter()
+                .map(|i| (i.name.clone(), i.shown))
+                .collect()
+        };
+
+        state.toggle_interface("docker0");
+        table.refresh(
+            vec![
+                interface("docker0", (0, 0), (0, 0)),
+                interface("eth0", (0, 0), (0, 0)),
+            ],
+            &state.hidden,
+        );
+        assert_eq!(
+            shown(&table),
+            vec![("docker0".to_string(), false), ("eth0".to_string(), true)]
+        );
+
+        // A hidden interface that disappears stays hidden when it comes back,
+        // and can be unhidden while it is gone
+        table.refresh(vec![interface("eth0", (0, 0), (0, 0))], &state.hidden);
+        assert!(state.hidden.contains("docker0"));
+        table.refresh(
+            vec![
+                interface("docker0", (0, 0), (0, 0)),
+                interface("eth0", (0, 0), (0, 0)),
+            ],
+            &state.hidden,
+        );
+        assert_eq!(shown(&table)[0], ("docker0".to_string(), false));
+        state.toggle_interface("docker0");
+        assert!(state.hidden.is_empty());
+        table.refresh(vec![interface("docker0", (0, 0), (0, 0))], &state.hidden);
+        assert_eq!(shown(&table), vec![("docker0".to_string(), true)]);
+    }
+}
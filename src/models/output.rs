@@ -0,0 +1,44 @@
+use crate::supervisor::OutputBuffer;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    text::Line,
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+
+// Tails the stdout/stderr of a supervised command
+pub struct OutputPanel {
+    title: String,
+    lines: OutputBuffer,
+}
+
+impl OutputPanel {
+    pub fn new(name: &str, lines: OutputBuffer) -> Self {
+        OutputPanel {
+            title: format!("Output of {}", name),
+            lines,
+        }
+    }
+}
+
+impl Widget for &OutputPanel {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // Only the last lines that fit inside the borders are shown
+        let height = area.height.saturating_sub(2) as usize;
+        let lines = self.lines.lock().unwrap();
+        let text: Vec<Line> = lines
+            .iter()
+            .skip(lines.len().saturating_sub(height))
+            .map(|l| Line::from(l.as_str()))
+            .collect();
+
+        let paragraph = Paragraph::new(text).block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title(self.title.as_str())
+                .style(Style::default()),
+        );
+        paragraph.render(area, buf);
+    }
+}
@@ -0,0 +1,236 @@
+use crate::app::AppState;
+use crate::models::chart::{time_axis, Series, Window};
+use crate::system_monitor;
+use crate::update::{UpdateableWidget, UpdateableWidgetWithState};
+use humantime::format_duration;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Color, Style, Stylize},
+    symbols::Marker,
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Row, Table, Widget},
+};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+// Charge in percent below which a discharging battery is shown in red
+const LOW_BATTERY: f32 = 10.0;
+
+// Colors handed out to the power sources of the histogram in turn
+const COLORS: [Color; 6] = [
+    Color::Green,
+    Color::Yellow,
+    Color::Cyan,
+    Color::Magenta,
+    Color::Blue,
+    Color::LightRed,
+];
+
+pub struct PowerHistogram {
+    // Watts drawn from each battery and by each RAPL zone
+    power: BTreeMap<String, Series<f64>>,
+    window: Window,
+}
+
+impl PowerHistogram {
+    pub fn new() -> Self {
+        PowerHistogram {
+            power: BTreeMap::new(),
+            window: Window::OneMinute,
+        }
+    }
+}
+
+impl Widget for &PowerHistogram {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let power: Vec<(&String, Vec<(f64, f64)>)> = self
+            .power
+            .iter()
+            .map(|(name, power)| (name, power.points(self.window)))
+            .collect();
+        let datasets = power
+            .iter()
+            .enumerate()
+            .map(|(i, (name, power))| {
+                Dataset::default()
+                    .name(format!("{}(W)", name))
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(COLORS[i % COLORS.len()]))
+                    .data(power)
+            })
+            .collect::<Vec<Dataset>>();
+
+        // Scale to the highest visible draw, laptops rarely exceed a few tens of watts
+        let y_max = power
+            .iter()
+            .flat_map(|(_, power)| power)
+            .map(|(_, y)| *y)
+            .fold(10.0, f64::max);
+        let y_axis = Axis::default()
+            .style(Style::default().white())
+            .bounds([0.0, y_max])
+            .labels(vec!["0W".to_string(), format!("{:.1}W", y_max)]);
+
+        let chart = Chart::new(datasets)
+            .block(
+                Block::new()
+                    .borders(Borders::ALL)
+                    .title("Power Histogram")
+                    .style(Style::default()),
+            )
+            .x_axis(time_axis(self.window))
+            .y_axis(y_axis);
+
+        chart.render(area, buf);
+    }
+}
+
+impl UpdateableWidgetWithState for PowerHistogram {
+    fn update_with_state(&mut self, monitor: &system_monitor::Monitor, state: &mut AppState) {
+        self.window = state.chart_window;
+        let batteries = monitor
+            .get_power_supplies()
+            .into_iter()
+            .filter(|s| s.kind == "Battery")
+            .filter_map(|s| Some((s.name, s.power?)));
+        let power: Vec<(String, f64)> = batteries.chain(monitor.get_rapl_power()).collect();
+        // Forget sources that went away
+        self.power
+            .retain(|name, _| power.iter().any(|(n, _)| n == name));
+        for (name, watts) in power {
+            self.power
+                .entry(name)
+                .or_insert_with(Series::new)
+                .push(watts);
+        }
+    }
+}
+
+struct Source {
+    name: String,
+    kind: String,
+    status: String,
+    capacity: Option<f32>,
+    power: Option<f64>,
+    // Time to empty or to full, depending on the status
+    time_left: Option<u64>,
+    low: bool,
+}
+
+pub struct PowerTable {
+    sources: Vec<Source>,
+}
+
+impl PowerTable {
+    pub fn new() -> Self {
+        PowerTable {
+            sources: Vec::new(),
+        }
+    }
+
+    // Whether there is any power supply or RAPL zone to show
+    pub fn is_available(&self) -> bool {
+        !self.sources.is_empty()
+    }
+
+    // Returns the name of the first battery, the target of low battery alerts
+    pub fn get_battery(&self) -> Option<&str> {
+        self.sources
+            .iter()
+            .find(|s| s.kind == "Battery")
+            .map(|s| s.name.as_str())
+    }
+}
+
+impl Widget for &PowerTable {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let rows = self
+            .sources
+            .iter()
+            .map(|s| {
+                let style = if s.low {
+                    Style::default().red()
+                } else {
+                    Style::default()
+                };
+                Row::new(vec![
+                    s.name.clone(),
+                    s.kind.clone(),
+                    s.status.clone(),
+                    s.capacity.map(|c| format!("{:.0}%", c)).unwrap_or_default(),
+                    s.power.map(|p| format!("{:.2}W", p)).unwrap_or_default(),
+                    // Estimates are not worth showing to the second
+                    s.time_left
+                        .map(|t| format_duration(Duration::from_secs(t / 60 * 60)).to_string())
+                        .unwrap_or_default(),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<Row>>();
+
+        let header = Row::new(vec![
+            "Source".to_string(),
+            "Type".to_string(),
+            "Status".to_string(),
+            "Charge".to_string(),
+            "Power".to_string(),
+            "Time Left".to_string(),
+        ]);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(20), // Source
+                Constraint::Percentage(12), // Type
+                Constraint::Percentage(20), // Status
+                Constraint::Percentage(12), // Charge
+                Constraint::Percentage(12), // Power
+                Constraint::Percentage(24), // Time Left
+            ],
+        )
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title("Power")
+                .style(Style::default()),
+        )
+        .header(header);
+        Widget::render(table, area, buf);
+    }
+}
+
+impl UpdateableWidget for PowerTable {
+    fn update(&mut self, monitor: &system_monitor::Monitor) {
+        let supplies = monitor.get_power_supplies().into_iter().map(|s| {
+            let discharging = s.status.as_deref() == Some("Discharging");
+            Source {
+                low: discharging && s.capacity.is_some_and(|c| c <= LOW_BATTERY),
+                status: match (s.status, s.online) {
+                    (Some(status), _) => status,
+                    (None, Some(true)) => "Online".to_string(),
+                    (None, Some(false)) => "Offline".to_string(),
+                    (None, None) => String::new(),
+                },
+                name: s.name,
+                kind: s.kind,
+                capacity: s.capacity,
+                power: s.power,
+                time_left: s.time_to_empty.or(s.time_to_full),
+            }
+        });
+        let rapl = monitor
+            .get_rapl_power()
+            .into_iter()
+            .map(|(name, watts)| Source {
+                name,
+                kind: "RAPL".to_string(),
+                status: String::new(),
+                capacity: None,
+                power: Some(watts),
+                time_left: None,
+                low: false,
+            });
+        self.sources = supplies.chain(rapl).collect();
+    }
+}
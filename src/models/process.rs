@@ -1,3 +1,5 @@
+use crate::models::cgroup::in_cgroup;
+use crate::sysfs::cgroup::container_id;
 use crate::update::UpdateableWidgetWithState;
 use crate::{app::AppState, system_monitor};
 use humansize::{format_size, BINARY};
@@ -5,9 +7,10 @@ use humantime::format_duration;
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Rect},
-    style::{Style, Stylize},
+    style::{Color, Style, Stylize},
     widgets::{Block, Borders, Row, StatefulWidget, Table, TableState},
 };
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 use sysinfo::ProcessStatus;
 
@@ -19,18 +22,73 @@ pub enum SortBy {
     Memory,
     RunTime,
     Status,
+    DiskRead,
+    DiskWrite,
+    Connections,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, strum::Display)]
+pub enum GroupBy {
+    None,
+    Name,
+    User,
+    Cgroup,
+}
+
+impl GroupBy {
+    pub fn next(self) -> Self {
+        match self {
+            GroupBy::None => GroupBy::Name,
+            GroupBy::Name => GroupBy::User,
+            GroupBy::User => GroupBy::Cgroup,
+            GroupBy::Cgroup => GroupBy::None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Highlight {
+    Started,
+    Exited,
 }
 
 pub struct Process {
     pub pid: u32,
     pub name: String,
+    user: String,
+    cgroup: Option<String>,
     cpu_usage: f32,
     memory: u64,
     run_time: u64,
     status: ProcessStatus,
+    disk_read: u64,
+    disk_written: u64,
+    disk_read_rate: f64,
+    disk_write_rate: f64,
+    connections: usize,
+    highlight: Option<Highlight>,
 }
 
 impl Process {
+    fn new(p: system_monitor::ProcessInfo, cgroup: Option<String>) -> Self {
+        Process {
+            pid: p.pid,
+            name: p.name,
+            user: p.user,
+            cgroup,
+            cpu_usage: p.cpu_usage,
+            memory: p.memory,
+            run_time: p.run_time,
+            status: p.status,
+            disk_read: p.disk_read,
+            disk_written: p.disk_written,
+            disk_read_rate: p.disk_read_rate,
+            disk_write_rate: p.disk_write_rate,
+            connections: p.connections,
+            highlight: None,
+        }
+    }
+
     fn sort_by(&self, other: &Process, sort_by: SortBy) -> std::cmp::Ordering {
         match sort_by {
             SortBy::Pid => other.pid.cmp(&self.pid),
@@ -42,18 +100,110 @@ impl Process {
             SortBy::Memory => other.memory.cmp(&self.memory),
             SortBy::RunTime => other.run_time.cmp(&self.run_time),
             SortBy::Status => other.status.to_string().cmp(&self.status.to_string()),
+            SortBy::DiskRead => other
+                .disk_read_rate
+                .partial_cmp(&self.disk_read_rate)
+                .unwrap_or(std::cmp::Ordering::Equal),
+            SortBy::DiskWrite => other
+                .disk_write_rate
+                .partial_cmp(&self.disk_write_rate)
+                .unwrap_or(std::cmp::Ordering::Equal),
+            SortBy::Connections => other.connections.cmp(&self.connections),
+        }
+    }
+
+    fn group_key(&self, group_by: GroupBy) -> String {
+        match group_by {
+            GroupBy::None => self.pid.to_string(),
+            GroupBy::Name => self.name.clone(),
+            GroupBy::User => self.user.clone(),
+            GroupBy::Cgroup => self.cgroup.clone().unwrap_or_default(),
+        }
+    }
+}
+
+// Shows the container ID of a process, or the innermost component of its cgroup path
+fn format_cgroup(cgroup: &Option<String>) -> String {
+    match cgroup {
+        Some(path) => container_id(path)
+            .or_else(|| path.rsplit('/').next().map(|name| name.to_string()))
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "/".to_string()),
+        None => String::new(),
+    }
+}
+
+// Formats a disk I/O rate together with the total bytes transferred, e.g. "1 KiB/s (3 MiB)"
+fn format_disk_io(rate: f64, total: u64) -> String {
+    format!(
+        "{}/s ({})",
+        format_size(rate as u64, BINARY),
+        format_size(total, BINARY)
+    )
+}
+
+// Aggregated totals over all the processes sharing a group key
+struct ProcessGroup {
+    key: String,
+    members: Vec<usize>,
+    cpu_usage: f32,
+    memory: u64,
+    run_time: u64,
+    disk_read: u64,
+    disk_written: u64,
+    disk_read_rate: f64,
+    disk_write_rate: f64,
+    connections: usize,
+    expanded: bool,
+}
+
+impl ProcessGroup {
+    fn sort_by(&self, other: &ProcessGroup, sort_by: SortBy) -> std::cmp::Ordering {
+        match sort_by {
+            SortBy::Pid => other.members.len().cmp(&self.members.len()),
+            SortBy::Name | SortBy::Status => other.key.cmp(&self.key),
+            SortBy::CpuUsage => other
+                .cpu_usage
+                .partial_cmp(&self.cpu_usage)
+                .unwrap_or(std::cmp::Ordering::Equal),
+            SortBy::Memory => other.memory.cmp(&self.memory),
+            SortBy::RunTime => other.run_time.cmp(&self.run_time),
+            SortBy::DiskRead => other
+                .disk_read_rate
+                .partial_cmp(&self.disk_read_rate)
+                .unwrap_or(std::cmp::Ordering::Equal),
+            SortBy::DiskWrite => other
+                .disk_write_rate
+                .partial_cmp(&self.disk_write_rate)
+                .unwrap_or(std::cmp::Ordering::Equal),
+            SortBy::Connections => other.connections.cmp(&self.connections),
         }
     }
 }
 
+enum TableRow {
+    Process(usize),
+    Group(usize),
+}
+
 pub struct ProcessTable {
     processes: Vec<Process>,
+    groups: Vec<ProcessGroup>,
+    rows: Vec<TableRow>,
+    group_by: GroupBy,
+    cgroup_filter: Option<String>,
+    user_filter: Option<String>,
 }
 
 impl ProcessTable {
     pub fn new() -> Self {
         ProcessTable {
             processes: Vec::new(),
+            groups: Vec::new(),
+            rows: Vec::new(),
+            group_by: GroupBy::None,
+            cgroup_filter: None,
+            user_filter: None,
         }
     }
 
@@ -74,15 +224,140 @@ impl ProcessTable {
             .retain(|p| p.name.to_lowercase().contains(&filter));
     }
 
+    // Collapses the (already sorted and filtered) processes into groups, keeping
+    // the members of each group in their sorted order.
+    pub fn group(
+        &mut self,
+        group_by: GroupBy,
+        sort_by: SortBy,
+        sort_ascending: bool,
+        expanded: &HashSet<String>,
+    ) {
+        self.group_by = group_by;
+        self.groups.clear();
+        self.rows.clear();
+        if group_by == GroupBy::None {
+            self.rows = (0..self.processes.len()).map(TableRow::Process).collect();
+            return;
+        }
+
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for (i, p) in self.processes.iter().enumerate() {
+            let key = p.group_key(group_by);
+            let group = *index.entry(key.clone()).or_insert_with(|| {
+                self.groups.push(ProcessGroup {
+                    expanded: expanded.contains(&key),
+                    key,
+                    members: Vec::new(),
+                    cpu_usage: 0.0,
+                    memory: 0,
+                    run_time: 0,
+                    disk_read: 0,
+                    disk_written: 0,
+                    disk_read_rate: 0.0,
+                    disk_write_rate: 0.0,
+                    connections: 0,
+                });
+                self.groups.len() - 1
+            });
+            let group = &mut self.groups[group];
+            group.members.push(i);
+            group.cpu_usage += p.cpu_usage;
+            group.memory += p.memory;
+            group.run_time = group.run_time.max(p.run_time);
+            group.disk_read += p.disk_read;
+            group.disk_written += p.disk_written;
+            group.disk_read_rate += p.disk_read_rate;
+            group.disk_write_rate += p.disk_write_rate;
+            group.connections += p.connections;
+        }
+
+        self.groups.sort_by(|a, b| {
+            let order = a.sort_by(b, sort_by);
+            if sort_ascending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+
+        for (i, group) in self.groups.iter().enumerate() {
+            self.rows.push(TableRow::Group(i));
+            if group.expanded {
+                self.rows
+                    .extend(group.members.iter().map(|&m| TableRow::Process(m)));
+            }
+        }
+    }
+
+    // Replaces the processes with a new snapshot, keeping the exited ones in
+    // while they are highlighted, and counts down the highlights
+    fn refresh(&mut self, processes: Vec<Process>, table_state: &mut ProcessTableState) {
+        let highlight = |pid: u32| {
+            table_state
+                .highlights
+                .get(&pid)
+                .filter(|_| table_state.highlight_events)
+                .map(|(h, _)| *h)
+        };
+
+        // Exited processes linger in the table while they are highlighted. Their
+        // rows come from the previous snapshot, which highlighted them differently.
+        let exited: Vec<Process> = std::mem::take(&mut self.processes)
+            .into_iter()
+            .filter(|p| highlight(p.pid) == Some(Highlight::Exited))
+            .map(|p| Process {
+                highlight: Some(Highlight::Exited),
+                ..p
+            })
+            .collect();
+
+        self.processes = processes
+            .into_iter()
+            .map(|p| Process {
+                highlight: highlight(p.pid),
+                ..p
+            })
+            .filter(|p| {
+                table_state
+                    .user_filter
+                    .as_ref()
+                    .is_none_or(|user| &p.user == user)
+            })
+            .filter(|p| {
+                table_state.cgroup_filter.as_ref().is_none_or(|cgroup| {
+                    p.cgroup
+                        .as_ref()
+                        .is_some_and(|path| in_cgroup(path, cgroup))
+                })
+            })
+            .chain(exited)
+            .collect();
+        table_state.highlights.retain(|_, (_, ticks)| {
+            *ticks -= 1;
+            *ticks > 0
+        });
+    }
+
     pub fn terminate_process(&mut self, monitor: &mut system_monitor::Monitor, row: usize) {
-        if row < self.processes.len() {
-            let pid = self.processes[row].pid;
-            monitor.terminate_process(pid);
+        if let Some(process) = self.get_process(row) {
+            monitor.terminate_process(process.pid);
         }
     }
 
-    pub fn get_process(&self, row: usize) -> &Process {
-        &self.processes[row]
+    // Returns the process shown at the given row, or None if the row is a group
+    pub fn get_process(&self, row: usize) -> Option<&Process> {
+        match self.rows.get(row) {
+            Some(TableRow::Process(i)) => Some(&self.processes[*i]),
+            _ => None,
+        }
+    }
+
+    pub fn get_group_key(&self, row: usize) -> Option<&str> {
+        match self.rows.get(row) {
+            Some(TableRow::Group(i)) => Some(&self.groups[*i].key),
+            _ => None,
+        }
     }
 }
 
@@ -91,6 +366,18 @@ pub struct ProcessTableState {
     pub sort_condition: SortBy,
     pub sort_ascending: bool,
     pub filter: String,
+    pub group_by: GroupBy,
+    pub expanded_groups: HashSet<String>,
+    // When set, only this process and its descendants are shown
+    pub root_pid: Option<u32>,
+    // When set, only the processes in this cgroup and its descendants are shown
+    pub cgroup_filter: Option<String>,
+    // When set, only the processes of this user are shown
+    pub user_filter: Option<String>,
+    pub show_threads: bool,
+    pub highlight_events: bool,
+    // Recently started/exited PIDs and the number of ticks left to highlight them
+    pub highlights: HashMap<u32, (Highlight, usize)>,
 }
 
 impl ProcessTableState {
@@ -100,6 +387,43 @@ impl ProcessTableState {
             sort_condition: SortBy::CpuUsage,
             sort_ascending: false,
             filter: String::new(),
+            group_by: GroupBy::None,
+            expanded_groups: HashSet::new(),
+            root_pid: None,
+            cgroup_filter: None,
+            user_filter: None,
+            show_threads: true,
+            highlight_events: true,
+            highlights: HashMap::new(),
+        }
+    }
+
+    pub fn cycle_group_by(&mut self) {
+        self.group_by = self.group_by.next();
+        self.expanded_groups.clear();
+    }
+
+    pub fn toggle_group(&mut self, key: &str) {
+        if !self.expanded_groups.remove(key) {
+            self.expanded_groups.insert(key.to_string());
+        }
+    }
+
+    // Filters the table to a cgroup, or clears the filter when it is already set to it
+    pub fn toggle_cgroup_filter(&mut self, cgroup: &str) {
+        if self.cgroup_filter.as_deref() == Some(cgroup) {
+            self.cgroup_filter = None;
+        } else {
+            self.cgroup_filter = Some(cgroup.to_string());
+        }
+    }
+
+    // Filters the table to a user, or clears the filter when it is already set to them
+    pub fn toggle_user_filter(&mut self, user: &str) {
+        if self.user_filter.as_deref() == Some(user) {
+            self.user_filter = None;
+        } else {
+            self.user_filter = Some(user.to_string());
         }
     }
 
@@ -120,18 +444,68 @@ impl StatefulWidget for &ProcessTable {
     type State = TableState;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let grouped = self.group_by != GroupBy::None;
+        let mut qualifiers = Vec::new();
+        if let Some(cgroup) = &self.cgroup_filter {
+            qualifiers.push(format!("in {}", cgroup));
+        }
+        if let Some(user) = &self.user_filter {
+            qualifiers.push(format!("of {}", user));
+        }
+        if grouped {
+            qualifiers.push(format!("grouped by {}", self.group_by));
+        }
+        let title = if qualifiers.is_empty() {
+            "Processes".to_string()
+        } else {
+            format!("Processes ({})", qualifiers.join(", "))
+        };
+
         let rows = self
-            .processes
+            .rows
             .iter()
-            .map(|p| {
-                Row::new(vec![
-                    p.pid.to_string(),
-                    p.name.clone(),
-                    p.cpu_usage.to_string() + "%",
-                    format_size(p.memory, BINARY),
-                    format_duration(Duration::from_secs(p.run_time)).to_string(),
-                    p.status.to_string(),
-                ])
+            .map(|row| match *row {
+                TableRow::Process(i) => {
+                    let p = &self.processes[i];
+                    let style = match p.highlight {
+                        Some(Highlight::Started) => Style::default().fg(Color::Green),
+                        Some(Highlight::Exited) => Style::default().fg(Color::Red),
+                        None => Style::default(),
+                    };
+                    Row::new(vec![
+                        p.pid.to_string(),
+                        if grouped {
+                            format!("  {}", p.name)
+                        } else {
+                            p.name.clone()
+                        },
+                        p.cpu_usage.to_string() + "%",
+                        format_size(p.memory, BINARY),
+                        format_duration(Duration::from_secs(p.run_time)).to_string(),
+                        p.status.to_string(),
+                        format_disk_io(p.disk_read_rate, p.disk_read),
+                        format_disk_io(p.disk_write_rate, p.disk_written),
+                        p.connections.to_string(),
+                        format_cgroup(&p.cgroup),
+                    ])
+                    .style(style)
+                }
+                TableRow::Group(i) => {
+                    let g = &self.groups[i];
+                    Row::new(vec![
+                        format!("[{}]", g.members.len()),
+                        format!("{} {}", if g.expanded { "▾" } else { "▸" }, g.key),
+                        g.cpu_usage.to_string() + "%",
+                        format_size(g.memory, BINARY),
+                        format_duration(Duration::from_secs(g.run_time)).to_string(),
+                        String::new(),
+                        format_disk_io(g.disk_read_rate, g.disk_read),
+                        format_disk_io(g.disk_write_rate, g.disk_written),
+                        g.connections.to_string(),
+                        String::new(),
+                    ])
+                    .style(Style::default().cyan())
+                }
             })
             .collect::<Vec<Row>>();
 
@@ -142,23 +516,31 @@ impl StatefulWidget for &ProcessTable {
             "Memory".to_string(),
             "Run Time".to_string(),
             "Status".to_string(),
+            "Disk Read".to_string(),
+            "Disk Write".to_string(),
+            "Conns".to_string(),
+            "Cgroup".to_string(),
         ]);
 
         let table = Table::new(
             rows,
             [
-                Constraint::Percentage(10), // PID
-                Constraint::Percentage(30), // Name
-                Constraint::Percentage(10), // CPU
-                Constraint::Percentage(10), // Memory
-                Constraint::Percentage(30), // Run Time
-                Constraint::Percentage(10), // Status
+                Constraint::Percentage(7),  // PID
+                Constraint::Percentage(16), // Name
+                Constraint::Percentage(7),  // CPU
+                Constraint::Percentage(9),  // Memory
+                Constraint::Percentage(12), // Run Time
+                Constraint::Percentage(8),  // Status
+                Constraint::Percentage(13), // Disk Read
+                Constraint::Percentage(13), // Disk Write
+                Constraint::Percentage(5),  // Conns
+                Constraint::Percentage(10), // Cgroup
             ],
         )
         .block(
             Block::new()
                 .borders(Borders::ALL)
-                .title("Processes")
+                .title(title)
                 .style(Style::default()),
         )
         .header(header)
@@ -169,22 +551,278 @@ impl StatefulWidget for &ProcessTable {
 
 impl UpdateableWidgetWithState for ProcessTable {
     fn update_with_state(&mut self, monitor: &system_monitor::Monitor, state: &mut AppState) {
-        self.processes = monitor
+        let table_state = &mut state.process_table_state;
+        let tree = table_state
+            .root_pid
+            .map(|pid| monitor.get_process_tree(pid));
+
+        let processes = monitor
             .get_processes()
             .into_iter()
-            .map(|(pid, name, cpu_usage, memory, run_time, status)| Process {
-                pid,
-                name,
-                cpu_usage,
-                memory,
-                run_time,
-                status,
+            .filter(|p| table_state.show_threads || !p.thread)
+            .filter(|p| tree.as_ref().is_none_or(|tree| tree.contains(&p.pid)))
+            .map(|p| {
+                let cgroup = monitor.get_process_cgroup(p.pid);
+                Process::new(p, cgroup)
             })
             .collect();
+        self.refresh(processes, table_state);
+        self.cgroup_filter = table_state.cgroup_filter.clone();
+        self.user_filter = table_state.user_filter.clone();
+
         self.filter(&state.process_table_state.filter);
         self.sort_by(
             state.process_table_state.sort_condition,
             state.process_table_state.sort_ascending,
         );
+        self.group(
+            state.process_table_state.group_by,
+            state.process_table_state.sort_condition,
+            state.process_table_state.sort_ascending,
+            &state.process_table_state.expanded_groups,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::event::EventLog;
+
+    fn info(pid: u32, name: &str) -> system_monitor::ProcessInfo {
+        system_monitor::ProcessInfo {
+            pid,
+            parent: Some(1),
+            thread: false,
+            name: name.to_string(),
+            uid: Some(1000),
+            user: "alice".to_string(),
+            cpu_usage: 1.0,
+            memory: 1024,
+            run_time: 10,
+            status: ProcessStatus::Run,
+            disk_read: 0,
+            disk_written: 0,
+            disk_read_rate: 0.0,
+            disk_write_rate: 0.0,
+            connections: 0,
+        }
+    }
+
+    // Runs a snapshot through the event log and the table, as an update does
+    fn tick(
+        table: &mut ProcessTable,
+        log: &mut EventLog,
+        state: &mut ProcessTableState,
+        snapshot: &[(u32, &str)],
+    ) {
+        let snapshot: Vec<_> = snapshot
+            .iter()
+            .map(|(pid, name)| info(*pid, name))
+            .collect();
+        log.diff(snapshot.clone(), &mut state.highlights);
+        let processes = snapshot
+            .into_iter()
+            .map(|p| Process::new(p, None))
+            .collect();
+        table.refresh(processes, state);
+    }
+
+    fn highlights(table: &ProcessTable) -> Vec<(u32, Option<Highlight>)> {
+        let mut rows: Vec<_> = table
+            .processes
+            .iter()
+            .map(|p| (p.pid, p.highlight))
+            .collect();
+        rows.sort_by_key(|(pid, _)| *pid);
+        rows
+    }
+
+    #[test]
+    fn highlights_started_and_exited_processes() {
+        let mut table = ProcessTable::new();
+        let mut log = EventLog::new();
+        let mut state = ProcessTableState::new();
+        tick(&mut table, &mut log, &mut state, &[(1, "init"), (2, "job")]);
+        assert_eq!(highlights(&table), vec![(1, None), (2, None)]);
+
+        // A process starts, and is gone again by the next tick
+        tick(
+            &mut table,
+            &mut log,
+            &mut state,
+            &[(1, "init"), (2, "job"), (3, "short")],
+        );
+        assert_eq!(
+            highlights(&table),
+            vec![(1, None), (2, None), (3, Some(Highlight::Started))]
+        );
+        tick(&mut table, &mut log, &mut state, &[(1, "init")]);
+        assert_eq!(
+            highlights(&table),
+            vec![
+                (1, None),
+                (2, Some(Highlight::Exited)),
+                (3, Some(Highlight::Exited))
+            ]
+        );
+
+        // The exited rows linger in red for the remaining ticks, then go
+        tick(&mut table, &mut log, &mut state, &[(1, "init")]);
+        assert_eq!(
+            highlights(&table),
+            vec![
+                (1, None),
+                (2, Some(Highlight::Exited)),
+                (3, Some(Highlight::Exited))
+            ]
+        );
+        assert!(state.highlights.values().all(|(_, ticks)| *ticks == 1));
+        tick(&mut table, &mut log, &mut state, &[(1, "init")]);
+        assert_eq!(
+            highlights(&table),
+            vec![
+                (1, None),
+                (2, Some(Highlight::Exited)),
+                (3, Some(Highlight::Exited))
+            ]
+        );
+        assert!(state.highlights.is_empty());
+        tick(&mut table, &mut log, &mut state, &[(1, "init")]);
+        assert_eq!(highlights(&table), vec![(1, None)]);
+    }
+
+    #[test]
+    fn skips_highlights_when_disabled() {
+        let mut table = ProcessTable::new();
+        let mut log = EventLog::new();
+        let mut state = ProcessTableState::new();
+        state.highlight_events = false;
+        tick(&mut table, &mut log, &mut state, &[(1, "init"), (2, "job")]);
+        tick(&mut table, &mut log, &mut state, &[(1, "init"), (3, "new")]);
+        assert_eq!(highlights(&table), vec![(1, None), (3, None)]);
+    }
+
+    // A process with the given owner and cgroup, using a tenth of its PID as
+    // the CPU usage, memory and disk rates so that sums are easy to check
+    fn member(pid: u32, name: &str, user: &str, cgroup: &str) -> Process {
+        let info = system_monitor::ProcessInfo {
+            user: user.to_string(),
+            cpu_usage: pid as f32 / 10.0,
+            memory: pid as u64 * 1024,
+            run_time: pid as u64,
+            disk_read_rate: pid as f64,
+            disk_write_rate: pid as f64 * 2.0,
+            connections: 1,
+            ..info(pid, name)
+        };
+        Process::new(info, Some(cgroup.to_string()))
+    }
+
+    fn grouped_table() -> ProcessTable {
+        let mut table = ProcessTable::new();
+        table.processes = vec![
+            member(10, "bash", "alice", "/user.slice/session-1.scope"),
+            member(20, "bash", "bob", "/user.slice/session-2.scope"),
+            member(30, "nginx", "www", "/system.slice/nginx.service"),
+            member(40, "nginx", "www", "/system.slice/nginx.service"),
+            member(50, "vim", "alice", "/user.slice/session-1.scope"),
+        ];
+        table
+    }
+
+    // Group keys and member counts, in row order
+    fn groups(table: &ProcessTable) -> Vec<(&str, usize)> {
+        table
+            .groups
+            .iter()
+            .map(|g| (g.key.as_str(), g.members.len()))
+            .collect()
+    }
+
+    #[test]
+    fn groups_processes() {
+        let mut table = grouped_table();
+        table.sort_by(SortBy::CpuUsage, false);
+        table.group(GroupBy::User, SortBy::CpuUsage, false, &HashSet::new());
+        assert_eq!(groups(&table), vec![("www", 2), ("alice", 2), ("bob", 1)]);
+        let www = &table.groups[0];
+        assert!((www.cpu_usage - 7.0).abs() < 1e-4);
+        assert_eq!(www.memory, 70 * 1024);
+        assert_eq!(www.run_time, 40);
+        assert_eq!(www.disk_read_rate, 70.0);
+        assert_eq!(www.disk_write_rate, 140.0);
+        assert_eq!(www.connections, 2);
+
+        table.group(GroupBy::Name, SortBy::Memory, false, &HashSet::new());
+        assert_eq!(groups(&table), vec![("nginx", 2), ("vim", 1), ("bash", 2)]);
+        table.group(GroupBy::Name, SortBy::Pid, true, &HashSet::new());
+        assert_eq!(groups(&table)[0], ("vim", 1));
+        table.group(GroupBy::Name, SortBy::DiskWrite, true, &HashSet::new());
+        assert_eq!(groups(&table), vec![("bash", 2), ("vim", 1), ("nginx", 2)]);
+
+        table.group(GroupBy::Cgroup, SortBy::Name, true, &HashSet::new());
+        assert_eq!(
+            groups(&table),
+            vec![
+                ("/system.slice/nginx.service", 2),
+                ("/user.slice/session-1.scope", 2),
+                ("/user.slice/session-2.scope", 1)
+            ]
+        );
+        assert!((table.groups[1].cpu_usage - 6.0).abs() < 1e-4);
+        assert_eq!(table.rows.len(), 3);
+        assert!(table.get_process(0).is_none());
+
+        // Without grouping, every process gets a row of its own
+        table.group(GroupBy::None, SortBy::Name, true, &HashSet::new());
+        assert!(table.groups.is_empty());
+        assert_eq!(table.get_process(4).map(|p| p.pid), Some(10));
+        assert!(table.get_group_key(0).is_none());
+    }
+
+    #[test]
+    fn expands_groups() {
+        let mut table = grouped_table();
+        let mut state = ProcessTableState::new();
+        assert_eq!(state.group_by, GroupBy::None);
+        state.cycle_group_by();
+        state.cycle_group_by();
+        assert_eq!(state.group_by, GroupBy::User);
+
+        // Members follow their group in the processes' sort order
+        state.toggle_group("alice");
+        table.sort_by(SortBy::Pid, false);
+        table.group(state.group_by, SortBy::Name, true, &state.expanded_groups);
+        let rows: Vec<_> = (0..table.rows.len())
+            .map(|row| {
+                (
+                    table.get_group_key(row),
+                    table.get_process(row).map(|p| p.pid),
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (Some("alice"), None),
+                (None, Some(50)),
+                (None, Some(10)),
+                (Some("bob"), None),
+                (Some("www"), None)
+            ]
+        );
+
+        state.toggle_group("alice");
+        table.group(state.group_by, SortBy::Name, true, &state.expanded_groups);
+        assert_eq!(table.rows.len(), 3);
+
+        // Switching the grouping collapses everything
+        state.toggle_group("bob");
+        state.cycle_group_by();
+        assert_eq!(state.group_by, GroupBy::Cgroup);
+        assert!(state.expanded_groups.is_empty());
+        state.cycle_group_by();
+        assert_eq!(state.group_by, GroupBy::None);
     }
 }
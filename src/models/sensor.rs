@@ -0,0 +1,235 @@
+use crate::app::AppState;
+use crate::models::chart::{time_axis, Series, Window};
+use crate::system_monitor;
+use crate::update::{UpdateableWidget, UpdateableWidgetWithState};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Color, Style, Stylize},
+    symbols::Marker,
+    widgets::{
+        Axis, Block, Borders, Chart, Dataset, GraphType, Row, StatefulWidget, Table, TableState,
+        Widget,
+    },
+};
+use std::collections::BTreeMap;
+
+// Thresholds in degrees Celsius for sensors that do not report their own max/critical values
+const DEFAULT_MAX: f32 = 70.0;
+const DEFAULT_CRITICAL: f32 = 90.0;
+
+// Colors handed out to the sensors of the histogram in turn
+const COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Green,
+    Color::Blue,
+    Color::LightRed,
+];
+
+pub struct Sensor {
+    pub label: String,
+    current: f32,
+    max: Option<f32>,
+    critical: Option<f32>,
+}
+
+impl Sensor {
+    // Red at or above the critical value, yellow at or above the max value
+    fn color(&self) -> Color {
+        if self.current >= self.critical.unwrap_or(DEFAULT_CRITICAL) {
+            Color::Red
+        } else if self.current >= self.max.unwrap_or(DEFAULT_MAX) {
+            Color::Yellow
+        } else {
+            Color::Green
+        }
+    }
+}
+
+pub struct SensorHistogram {
+    temperatures: BTreeMap<String, Series<f64>>,
+    window: Window,
+}
+
+impl SensorHistogram {
+    pub fn new() -> Self {
+        SensorHistogram {
+            temperatures: BTreeMap::new(),
+            window: Window::OneMinute,
+        }
+    }
+}
+
+impl Widget for &SensorHistogram {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let temperatures: Vec<(&String, Vec<(f64, f64)>)> = self
+            .temperatures
+            .iter()
+            .map(|(label, temperature)| (label, temperature.points(self.window)))
+            .collect();
+        let datasets = temperatures
+            .iter()
+            .enumerate()
+            .map(|(i, (label, temperature))| {
+                Dataset::default()
+                    .name(format!("{}(°C)", label))
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(COLORS[i % COLORS.len()]))
+                    .data(temperature)
+            })
+            .collect::<Vec<Dataset>>();
+
+        // Most sensors stay below 100°C, leave room for the ones that do not
+        let y_max = temperatures
+            .iter()
+            .flat_map(|(_, temperature)| temperature)
+            .map(|(_, y)| *y)
+            .fold(100.0, f64::max);
+        let y_axis = Axis::default()
+            .style(Style::default().white())
+            .bounds([0.0, y_max])
+            .labels(vec!["0°C".to_string(), format!("{:.0}°C", y_max)]);
+
+        let chart = Chart::new(datasets)
+            .block(
+                Block::new()
+                    .borders(Borders::ALL)
+                    .title("Temperature Histogram")
+                    .style(Style::default()),
+            )
+            .x_axis(time_axis(self.window))
+            .y_axis(y_axis);
+
+        chart.render(area, buf);
+    }
+}
+
+impl UpdateableWidgetWithState for SensorHistogram {
+    fn update_with_state(&mut self, monitor: &system_monitor::Monitor, state: &mut AppState) {
+        self.window = state.chart_window;
+        let temperatures = monitor.get_temperatures();
+        // Forget sensors that went away
+        self.temperatures
+            .retain(|label, _| temperatures.iter().any(|t| &t.label == label));
+        for t in temperatures {
+            self.temperatures
+                .entry(t.label)
+                .or_insert_with(Series::new)
+                .push(t.current as f64);
+        }
+    }
+}
+
+pub struct SensorTable {
+    sensors: Vec<Sensor>,
+}
+
+impl SensorTable {
+    pub fn new() -> Self {
+        SensorTable {
+            sensors: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sensors.is_empty()
+    }
+
+    pub fn get_sensor(&self, row: usize) -> Option<&Sensor> {
+        self.sensors.get(row)
+    }
+}
+
+pub struct SensorTableState {
+    pub state: TableState,
+}
+
+impl SensorTableState {
+    pub fn new() -> Self {
+        SensorTableState {
+            state: TableState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.state.select_previous();
+    }
+
+    pub fn select_next(&mut self) {
+        self.state.select_next();
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.state.selected()
+    }
+}
+
+fn format_temperature(temperature: Option<f32>) -> String {
+    temperature
+        .map(|t| format!("{:.1}°C", t))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+impl StatefulWidget for &SensorTable {
+    type State = TableState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let rows = self
+            .sensors
+            .iter()
+            .map(|s| {
+                Row::new(vec![
+                    s.label.clone(),
+                    format_temperature(Some(s.current)),
+                    format_temperature(s.max),
+                    format_temperature(s.critical),
+                ])
+                .style(Style::default().fg(s.color()))
+            })
+            .collect::<Vec<Row>>();
+
+        let header = Row::new(vec![
+            "Sensor".to_string(),
+            "Current".to_string(),
+            "Max".to_string(),
+            "Critical".to_string(),
+        ]);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(40), // Sensor
+                Constraint::Percentage(20), // Current
+                Constraint::Percentage(20), // Max
+                Constraint::Percentage(20), // Critical
+            ],
+        )
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title("Sensors")
+                .style(Style::default()),
+        )
+        .header(header)
+        .row_highlight_style(Style::new().bold());
+        StatefulWidget::render(table, area, buf, state);
+    }
+}
+
+impl UpdateableWidget for SensorTable {
+    fn update(&mut self, monitor: &system_monitor::Monitor) {
+        self.sensors = monitor
+            .get_temperatures()
+            .into_iter()
+            .map(|t| Sensor {
+                label: t.label,
+                current: t.current,
+                max: t.max,
+                critical: t.critical,
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,123 @@
+use crate::procfs::net::Socket;
+use crate::system_monitor;
+use crate::update::UpdateableWidget;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Style, Stylize},
+    widgets::{Block, Borders, Row, StatefulWidget, Table, TableState},
+};
+
+pub struct SocketTable {
+    pid: Option<u32>,
+    name: String,
+    sockets: Vec<Socket>,
+}
+
+impl SocketTable {
+    pub fn new() -> Self {
+        SocketTable {
+            pid: None,
+            name: String::new(),
+            sockets: Vec::new(),
+        }
+    }
+
+    pub fn open(&mut self, monitor: &system_monitor::Monitor, pid: u32, name: String) {
+        self.pid = Some(pid);
+        self.name = name;
+        self.update(monitor);
+    }
+
+    pub fn close(&mut self) {
+        self.pid = None;
+        self.sockets.clear();
+    }
+}
+
+pub struct SocketTableState {
+    pub state: TableState,
+}
+
+impl SocketTableState {
+    pub fn new() -> Self {
+        SocketTableState {
+            state: TableState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.state.select_previous();
+    }
+
+    pub fn select_next(&mut self) {
+        self.state.select_next();
+    }
+}
+
+impl StatefulWidget for &SocketTable {
+    type State = TableState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let rows = self
+            .sockets
+            .iter()
+            .map(|s| {
+                Row::new(vec![
+                    s.protocol.to_string(),
+                    s.local.clone(),
+                    s.remote.clone(),
+                    s.state.clone(),
+                    s.tx_queue.to_string(),
+                    s.rx_queue.to_string(),
+                    s.inode.to_string(),
+                ])
+            })
+            .collect::<Vec<Row>>();
+
+        let header = Row::new(vec![
+            "Proto".to_string(),
+            "Local Address".to_string(),
+            "Remote Address".to_string(),
+            "State".to_string(),
+            "Send-Q".to_string(),
+            "Recv-Q".to_string(),
+            "Inode".to_string(),
+        ]);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(6),  // Proto
+                Constraint::Percentage(30), // Local Address
+                Constraint::Percentage(25), // Remote Address
+                Constraint::Percentage(15), // State
+                Constraint::Percentage(7),  // Send-Q
+                Constraint::Percentage(7),  // Recv-Q
+                Constraint::Percentage(10), // Inode
+            ],
+        )
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "Sockets of {} ({}) - {} open",
+                    self.name,
+                    self.pid.unwrap_or_default(),
+                    self.sockets.len()
+                ))
+                .style(Style::default()),
+        )
+        .header(header)
+        .row_highlight_style(Style::new().bold());
+        table.render(area, buf, state);
+    }
+}
+
+impl UpdateableWidget for SocketTable {
+    fn update(&mut self, monitor: &system_monitor::Monitor) {
+        if let Some(pid) = self.pid {
+            self.sockets = monitor.get_process_sockets(pid);
+        }
+    }
+}
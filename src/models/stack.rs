@@ -0,0 +1,126 @@
+use crate::sampler::Profile;
+use crate::system_monitor;
+use crate::update::UpdateableWidget;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, StatefulWidget},
+};
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+pub struct StackView {
+    pid: u32,
+    name: String,
+    profile: Option<Profile>,
+    // Set while the stacks are being sampled
+    pending: Option<Receiver<Profile>>,
+}
+
+impl StackView {
+    pub fn new() -> Self {
+        StackView {
+            pid: 0,
+            name: String::new(),
+            profile: None,
+            pending: None,
+        }
+    }
+
+    // Sampling takes the whole sampling window, it is only done on request
+    pub fn sample(&mut self, monitor: &system_monitor::Monitor, pid: u32, name: String) {
+        self.pid = pid;
+        self.name = name;
+        self.profile = None;
+        self.pending = Some(monitor.sample_stacks(pid));
+    }
+
+    pub fn close(&mut self) {
+        self.profile = None;
+        self.pending = None;
+    }
+}
+
+impl UpdateableWidget for StackView {
+    // Picks up the profile once sampling is done
+    fn update(&mut self, _monitor: &system_monitor::Monitor) {
+        let Some(pending) = &self.pending else {
+            return;
+        };
+        match pending.try_recv() {
+            Ok(profile) => {
+                self.profile = Some(profile);
+                self.pending = None;
+            }
+            Err(TryRecvError::Disconnected) => self.pending = None,
+            Err(TryRecvError::Empty) => {}
+        }
+    }
+}
+
+pub struct StackViewState {
+    pub state: ListState,
+}
+
+impl StackViewState {
+    pub fn new() -> Self {
+        StackViewState {
+            state: ListState::default(),
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.state.scroll_up_by(1);
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.state.scroll_down_by(1);
+    }
+}
+
+impl StatefulWidget for &StackView {
+    type State = ListState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let mut items = Vec::new();
+        if let Some(profile) = &self.profile {
+            for note in &profile.notes {
+                items.push(ListItem::new(Line::from(Span::from(note.clone()).yellow())));
+            }
+            let total: usize = profile.stacks.iter().map(|(count, _)| count).sum();
+            for (count, frames) in &profile.stacks {
+                items.push(ListItem::new(Line::from(
+                    Span::from(format!(
+                        "{:.1}% ({} of {} thread samples)",
+                        *count as f32 / total.max(1) as f32 * 100.0,
+                        count,
+                        total
+                    ))
+                    .cyan()
+                    .bold(),
+                )));
+                items.extend(
+                    frames
+                        .iter()
+                        .map(|frame| ListItem::new(format!("    {}", frame))),
+                );
+            }
+        }
+
+        let title = match &self.profile {
+            Some(profile) => format!(
+                "Stacks of {} ({}) - {} samples",
+                self.name, self.pid, profile.samples
+            ),
+            None => format!("Stacks of {} ({}) - sampling...", self.name, self.pid),
+        };
+        let list = List::new(items).block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title(title)
+                .style(Style::default()),
+        );
+        list.render(area, buf, state);
+    }
+}
@@ -0,0 +1,265 @@
+use crate::procfs::task::{clock_ticks_per_second, TaskStat};
+use crate::update::UpdateableWidgetWithState;
+use crate::{app::AppState, system_monitor};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Style, Stylize},
+    widgets::{Block, Borders, Row, StatefulWidget, Table, TableState},
+};
+use std::collections::HashMap;
+use std::time::Instant;
+
+#[derive(Clone, Copy, Debug)]
+pub enum ThreadSortBy {
+    Tid,
+    Name,
+    State,
+    CpuUsage,
+    LastCpu,
+}
+
+pub struct Thread {
+    tid: u32,
+    name: String,
+    state: char,
+    cpu_usage: f32,
+    last_cpu: u32,
+}
+
+impl Thread {
+    fn sort_by(&self, other: &Thread, sort_by: ThreadSortBy) -> std::cmp::Ordering {
+        match sort_by {
+            ThreadSortBy::Tid => other.tid.cmp(&self.tid),
+            ThreadSortBy::Name => other.name.cmp(&self.name),
+            ThreadSortBy::State => other.state.cmp(&self.state),
+            ThreadSortBy::CpuUsage => other
+                .cpu_usage
+                .partial_cmp(&self.cpu_usage)
+                .unwrap_or(std::cmp::Ordering::Equal),
+            ThreadSortBy::LastCpu => other.last_cpu.cmp(&self.last_cpu),
+        }
+    }
+}
+
+fn state_name(state: char) -> &'static str {
+    match state {
+        'R' => "Running",
+        'S' => "Sleeping",
+        'D' => "Disk Sleep",
+        'Z' => "Zombie",
+        'T' => "Stopped",
+        't' => "Tracing",
+        'I' => "Idle",
+        'X' => "Dead",
+        _ => "Unknown",
+    }
+}
+
+pub struct ThreadTable {
+    pid: Option<u32>,
+    name: String,
+    threads: Vec<Thread>,
+    // CPU time of each thread at the previous sample, to compute usage from deltas
+    previous: HashMap<u32, u64>,
+    last_sample: Instant,
+    ticks_per_second: u64,
+}
+
+impl ThreadTable {
+    pub fn new() -> Self {
+        ThreadTable {
+            pid: None,
+            name: String::new(),
+            threads: Vec::new(),
+            previous: HashMap::new(),
+            last_sample: Instant::now(),
+            ticks_per_second: clock_ticks_per_second(),
+        }
+    }
+
+    pub fn open(&mut self, pid: u32, name: String) {
+        self.pid = Some(pid);
+        self.name = name;
+        self.previous.clear();
+    }
+
+    pub fn close(&mut self) {
+        self.pid = None;
+        self.threads.clear();
+        self.previous.clear();
+    }
+
+    // Replaces the threads with a new reading of their stat files, taken the
+    // given number of seconds after the previous one. Threads that exited in
+    // between are forgotten.
+    fn sample(&mut self, tasks: Vec<TaskStat>, elapsed: f32) {
+        let mut previous = std::mem::take(&mut self.previous);
+        self.threads = tasks
+            .into_iter()
+            .map(|task| {
+                // A thread seen for the first time has no baseline yet
+                let delta = previous
+                    .remove(&task.tid)
+                    .map(|before| task.cpu_time.saturating_sub(before))
+                    .unwrap_or(0);
+                self.previous.insert(task.tid, task.cpu_time);
+                Thread {
+                    tid: task.tid,
+                    name: task.name,
+                    state: task.state,
+                    cpu_usage: delta as f32 / self.ticks_per_second as f32 / elapsed * 100.0,
+                    last_cpu: task.last_cpu,
+                }
+            })
+            .collect();
+    }
+}
+
+pub struct ThreadTableState {
+    pub state: TableState,
+    pub sort_condition: ThreadSortBy,
+    pub sort_ascending: bool,
+    pub filter: String,
+}
+
+impl ThreadTableState {
+    pub fn new() -> Self {
+        ThreadTableState {
+            state: TableState::default().with_selected(Some(0)),
+            sort_condition: ThreadSortBy::CpuUsage,
+            sort_ascending: false,
+            filter: String::new(),
+        }
+    }
+
+    pub fn sort(&mut self, sort_condition: ThreadSortBy) {
+        self.sort_condition = sort_condition;
+        self.sort_ascending = !self.sort_ascending;
+    }
+
+    pub fn select_prev(&mut self) {
+        self.state.select_previous();
+    }
+
+    pub fn select_next(&mut self) {
+        self.state.select_next();
+    }
+}
+
+impl StatefulWidget for &ThreadTable {
+    type State = TableState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let rows = self
+            .threads
+            .iter()
+            .map(|t| {
+                Row::new(vec![
+                    t.tid.to_string(),
+                    t.name.clone(),
+                    state_name(t.state).to_string(),
+                    format!("{:.1}%", t.cpu_usage),
+                    t.last_cpu.to_string(),
+                ])
+            })
+            .collect::<Vec<Row>>();
+
+        let header = Row::new(vec![
+            "TID".to_string(),
+            "Name".to_string(),
+            "State".to_string(),
+            "CPU".to_string(),
+            "Last CPU".to_string(),
+        ]);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(15), // TID
+                Constraint::Percentage(40), // Name
+                Constraint::Percentage(15), // State
+                Constraint::Percentage(15), // CPU
+                Constraint::Percentage(15), // Last CPU
+            ],
+        )
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "Threads of {} ({}) - {} threads",
+                    self.name,
+                    self.pid.unwrap_or_default(),
+                    self.threads.len()
+                ))
+                .style(Style::default()),
+        )
+        .header(header)
+        .row_highlight_style(Style::new().bold());
+        table.render(area, buf, state);
+    }
+}
+
+impl UpdateableWidgetWithState for ThreadTable {
+    fn update_with_state(&mut self, monitor: &system_monitor::Monitor, state: &mut AppState) {
+        let Some(pid) = self.pid else {
+            return;
+        };
+        let elapsed = self.last_sample.elapsed().as_secs_f32().max(0.001);
+        self.last_sample = Instant::now();
+
+        self.sample(monitor.get_process_tasks(pid), elapsed);
+
+        let table_state = &state.thread_table_state;
+        let filter = table_state.filter.to_lowercase();
+        self.threads
+            .retain(|t| t.name.to_lowercase().contains(&filter));
+        self.threads.sort_by(|a, b| {
+            let order = a.sort_by(b, table_state.sort_condition);
+            if table_state.sort_ascending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(tid: u32, cpu_time: u64) -> TaskStat {
+        TaskStat {
+            tid,
+            name: format!("worker-{}", tid),
+            state: 'R',
+            parent: 1,
+            cpu_time,
+            last_cpu: 0,
+        }
+    }
+
+    fn usage(table: &ThreadTable) -> Vec<(u32, f32)> {
+        table.threads.iter().map(|t| (t.tid, t.cpu_usage)).collect()
+    }
+
+    #[test]
+    fn computes_usage_from_deltas() {
+        let mut table = ThreadTable::new();
+        table.ticks_per_second = 100;
+        table.sample(vec![task(10, 1000), task(11, 500), task(12, 0)], 1.0);
+        // Nothing to compare against on the first sample
+        assert_eq!(usage(&table), vec![(10, 0.0), (11, 0.0), (12, 0.0)]);
+
+        // 50 ticks over half a second is a fully used core. Thread 12 exits and
+        // thread 13 starts.
+        table.sample(vec![task(10, 1050), task(11, 510), task(13, 40)], 0.5);
+        assert_eq!(usage(&table), vec![(10, 100.0), (11, 20.0), (13, 0.0)]);
+        assert!(!table.previous.contains_key(&12));
+
+        // A thread ID reused by a new thread starts from a new baseline
+        table.sample(vec![task(12, 5000), task(13, 60)], 1.0);
+        assert_eq!(usage(&table), vec![(12, 0.0), (13, 20.0)]);
+    }
+}
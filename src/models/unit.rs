@@ -0,0 +1,211 @@
+use crate::systemd::unit_name;
+use crate::update::UpdateableWidgetWithState;
+use crate::{app::AppState, system_monitor};
+use humansize::{format_size, BINARY};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Style, Stylize},
+    widgets::{Block, Borders, Row, StatefulWidget, Table, TableState},
+};
+use std::collections::HashMap;
+
+pub struct Unit {
+    pub name: String,
+    description: String,
+    active_state: String,
+    sub_state: String,
+    main_pid: Option<u32>,
+    tasks: Option<u64>,
+    cpu_usage: f32,
+    memory: u64,
+    processes: usize,
+    pub control_group: Option<String>,
+}
+
+// Resource usage summed over the processes of one unit
+#[derive(Default)]
+struct Usage {
+    cpu_usage: f32,
+    memory: u64,
+    processes: usize,
+}
+
+pub struct UnitTable {
+    units: Vec<Unit>,
+    available: bool,
+    message: Option<String>,
+}
+
+impl UnitTable {
+    pub fn new() -> Self {
+        UnitTable {
+            units: Vec::new(),
+            available: false,
+            message: None,
+        }
+    }
+
+    // Whether the system was booted with systemd
+    pub fn is_available(&self) -> bool {
+        self.available
+    }
+
+    pub fn get_unit(&self, row: usize) -> Option<&Unit> {
+        self.units.get(row)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum UnitAction {
+    Restart,
+    Stop,
+}
+
+pub struct UnitTableState {
+    pub state: TableState,
+    // Action and unit awaiting confirmation
+    pub pending: Option<(UnitAction, String)>,
+    // Outcome of the last restart or stop request
+    pub message: Option<String>,
+}
+
+impl UnitTableState {
+    pub fn new() -> Self {
+        UnitTableState {
+            state: TableState::default().with_selected(Some(0)),
+            pending: None,
+            message: None,
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.state.select_previous();
+    }
+
+    pub fn select_next(&mut self) {
+        self.state.select_next();
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.state.selected()
+    }
+}
+
+impl StatefulWidget for &UnitTable {
+    type State = TableState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let rows = self
+            .units
+            .iter()
+            .map(|u| {
+                let style = match u.active_state.as_str() {
+                    "failed" => Style::default().red(),
+                    "activating" | "deactivating" | "reloading" => Style::default().yellow(),
+                    "active" => Style::default(),
+                    _ => Style::default().dark_gray(),
+                };
+                Row::new(vec![
+                    u.name.clone(),
+                    format!("{} ({})", u.active_state, u.sub_state),
+                    u.main_pid.map(|pid| pid.to_string()).unwrap_or_default(),
+                    u.tasks.map(|tasks| tasks.to_string()).unwrap_or_default(),
+                    u.processes.to_string(),
+                    u.cpu_usage.to_string() + "%",
+                    format_size(u.memory, BINARY),
+                    u.description.clone(),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<Row>>();
+
+        let header = Row::new(vec![
+            "Unit".to_string(),
+            "State".to_string(),
+            "Main PID".to_string(),
+            "Tasks".to_string(),
+            "Processes".to_string(),
+            "CPU".to_string(),
+            "Memory".to_string(),
+            "Description".to_string(),
+        ]);
+
+        let title = match &self.message {
+            Some(message) => format!("Units ({})", message),
+            None => "Units".to_string(),
+        };
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(22), // Unit
+                Constraint::Percentage(14), // State
+                Constraint::Percentage(7),  // Main PID
+                Constraint::Percentage(6),  // Tasks
+                Constraint::Percentage(7),  // Processes
+                Constraint::Percentage(7),  // CPU
+                Constraint::Percentage(9),  // Memory
+                Constraint::Percentage(28), // Description
+            ],
+        )
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title(title)
+                .style(Style::default()),
+        )
+        .header(header)
+        .row_highlight_style(Style::new().bold());
+        StatefulWidget::render(table, area, buf, state);
+    }
+}
+
+impl UpdateableWidgetWithState for UnitTable {
+    fn update_with_state(&mut self, monitor: &system_monitor::Monitor, state: &mut AppState) {
+        self.message = state.unit_table_state.message.clone();
+        self.available = monitor.has_systemd();
+        if !self.available {
+            self.units.clear();
+            return;
+        }
+
+        // Link processes to units through the service named in their cgroup path
+        let mut usage: HashMap<String, Usage> = HashMap::new();
+        for p in monitor.get_processes().into_iter().filter(|p| !p.thread) {
+            let Some(cgroup) = monitor.get_process_cgroup(p.pid) else {
+                continue;
+            };
+            let Some(name) = unit_name(&cgroup) else {
+                continue;
+            };
+            let entry = usage.entry(name.to_string()).or_default();
+            entry.cpu_usage += p.cpu_usage;
+            entry.memory += p.memory;
+            entry.processes += 1;
+        }
+
+        self.units = monitor
+            .get_units()
+            .into_iter()
+            // Units that failed to load are only noise here
+            .filter(|u| u.load_state != "not-found")
+            .map(|u| {
+                let usage = usage.remove(&u.name).unwrap_or_default();
+                Unit {
+                    description: u.description,
+                    active_state: u.active_state,
+                    sub_state: u.sub_state,
+                    main_pid: u.main_pid,
+                    tasks: u.tasks,
+                    cpu_usage: usage.cpu_usage,
+                    // Prefer systemd's accounting, which includes the page cache of the unit
+                    memory: u.memory.unwrap_or(usage.memory),
+                    processes: usage.processes,
+                    control_group: u.control_group,
+                    name: u.name,
+                }
+            })
+            .collect();
+    }
+}
@@ -0,0 +1,233 @@
+use crate::system_monitor;
+use crate::update::UpdateableWidget;
+use humansize::{format_size, BINARY};
+use humantime::format_rfc3339_seconds;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Style, Stylize},
+    widgets::{Block, Borders, Row, StatefulWidget, Table, TableState, Widget},
+};
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+struct Session {
+    user: String,
+    line: String,
+    host: String,
+    pid: u32,
+    login_time: SystemTime,
+}
+
+pub struct SessionTable {
+    sessions: Vec<Session>,
+}
+
+impl SessionTable {
+    pub fn new() -> Self {
+        SessionTable {
+            sessions: Vec::new(),
+        }
+    }
+}
+
+impl Widget for &SessionTable {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let rows = self
+            .sessions
+            .iter()
+            .map(|s| {
+                Row::new(vec![
+                    s.user.clone(),
+                    s.line.clone(),
+                    if s.host.is_empty() {
+                        "local".to_string()
+                    } else {
+                        s.host.clone()
+                    },
+                    s.pid.to_string(),
+                    format_rfc3339_seconds(s.login_time).to_string(),
+                ])
+            })
+            .collect::<Vec<Row>>();
+
+        let header = Row::new(vec![
+            "User".to_string(),
+            "Terminal".to_string(),
+            "From".to_string(),
+            "PID".to_string(),
+            "Login".to_string(),
+        ]);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(18), // User
+                Constraint::Percentage(12), // Terminal
+                Constraint::Percentage(35), // From
+                Constraint::Percentage(10), // PID
+                Constraint::Percentage(25), // Login
+            ],
+        )
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title(format!("Sessions ({})", self.sessions.len()))
+                .style(Style::default()),
+        )
+        .header(header);
+        Widget::render(table, area, buf);
+    }
+}
+
+impl UpdateableWidget for SessionTable {
+    fn update(&mut self, monitor: &system_monitor::Monitor) {
+        self.sessions = monitor
+            .get_sessions()
+            .into_iter()
+            .map(|s| Session {
+                user: s.user,
+                line: s.line,
+                host: s.host,
+                pid: s.pid,
+                login_time: s.login_time,
+            })
+            .collect();
+    }
+}
+
+// Resource usage summed over the processes of one UID
+pub struct User {
+    uid: u32,
+    pub name: String,
+    sessions: usize,
+    processes: usize,
+    cpu_usage: f32,
+    memory: u64,
+}
+
+pub struct UserTable {
+    users: Vec<User>,
+}
+
+impl UserTable {
+    pub fn new() -> Self {
+        UserTable { users: Vec::new() }
+    }
+
+    pub fn get_user(&self, row: usize) -> Option<&User> {
+        self.users.get(row)
+    }
+}
+
+pub struct UserTableState {
+    pub state: TableState,
+}
+
+impl UserTableState {
+    pub fn new() -> Self {
+        UserTableState {
+            state: TableState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        self.state.select_previous();
+    }
+
+    pub fn select_next(&mut self) {
+        self.state.select_next();
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.state.selected()
+    }
+}
+
+impl StatefulWidget for &UserTable {
+    type State = TableState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        let rows = self
+            .users
+            .iter()
+            .map(|u| {
+                let style = if u.sessions > 0 {
+                    Style::default()
+                } else {
+                    Style::default().dark_gray()
+                };
+                Row::new(vec![
+                    u.uid.to_string(),
+                    u.name.clone(),
+                    u.sessions.to_string(),
+                    u.processes.to_string(),
+                    u.cpu_usage.to_string() + "%",
+                    format_size(u.memory, BINARY),
+                ])
+                .style(style)
+            })
+            .collect::<Vec<Row>>();
+
+        let header = Row::new(vec![
+            "UID".to_string(),
+            "User".to_string(),
+            "Sessions".to_string(),
+            "Processes".to_string(),
+            "CPU".to_string(),
+            "Memory".to_string(),
+        ]);
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(10), // UID
+                Constraint::Percentage(30), // User
+                Constraint::Percentage(15), // Sessions
+                Constraint::Percentage(15), // Processes
+                Constraint::Percentage(15), // CPU
+                Constraint::Percentage(15), // Memory
+            ],
+        )
+        .block(
+            Block::new()
+                .borders(Borders::ALL)
+                .title("Users")
+                .style(Style::default()),
+        )
+        .header(header)
+        .row_highlight_style(Style::new().bold());
+        StatefulWidget::render(table, area, buf, state);
+    }
+}
+
+impl UpdateableWidget for UserTable {
+    fn update(&mut self, monitor: &system_monitor::Monitor) {
+        let mut sessions: HashMap<String, usize> = HashMap::new();
+        for s in monitor.get_sessions() {
+            *sessions.entry(s.user).or_default() += 1;
+        }
+
+        let mut users: HashMap<u32, User> = HashMap::new();
+        for p in monitor.get_processes().into_iter().filter(|p| !p.thread) {
+            let Some(uid) = p.uid else {
+                continue;
+            };
+            let user = users.entry(uid).or_insert_with(|| User {
+                uid,
+                sessions: sessions.get(&p.user).copied().unwrap_or_default(),
+                name: p.user,
+                processes: 0,
+                cpu_usage: 0.0,
+                memory: 0,
+            });
+            user.processes += 1;
+            user.cpu_usage += p.cpu_usage;
+            user.memory += p.memory;
+        }
+
+        // Sorted by UID so that the selected row stays on the same user between updates
+        self.users = users.into_values().collect();
+        self.users.sort_by_key(|u| u.uid);
+    }
+}
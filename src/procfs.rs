@@ -0,0 +1,11 @@
+pub mod diskstats;
+pub mod fd;
+pub mod loadavg;
+pub mod maps;
+pub mod meminfo;
+pub mod net;
+pub mod pressure;
+pub mod stack;
+pub mod stat;
+pub mod task;
+pub mod vmstat;
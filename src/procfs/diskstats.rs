@@ -0,0 +1,70 @@
+use std::fs;
+use std::path::Path;
+
+// diskstats counts in 512-byte sectors, whatever the sector size of the device
+const SECTOR_SIZE: u64 = 512;
+
+// Cumulative bytes read from and written to a block device since boot
+pub struct DiskStat {
+    pub name: String,
+    pub read_bytes: u64,
+    pub written_bytes: u64,
+}
+
+pub fn read_diskstats(root: &Path) -> Vec<DiskStat> {
+    fs::read_to_string(root.join("diskstats"))
+        .map(|content| parse_diskstats(&content))
+        .unwrap_or_default()
+}
+
+// Parses "major minor name reads merged sectors_read ms writes merged
+// sectors_written ..." lines, one per device and partition
+pub fn parse_diskstats(content: &str) -> Vec<DiskStat> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            Some(DiskStat {
+                name: fields.get(2)?.to_string(),
+                read_bytes: fields.get(5)?.parse::<u64>().ok()? * SECTOR_SIZE,
+                written_bytes: fields.get(9)?.parse::<u64>().ok()? * SECTOR_SIZE,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    const DISKSTATS: &str = "   7       0 loop0 12 0 96 0 0 0 0 0 0 4 0 0 0 0 0 0 0
+ 254       0 vda 6805 5052 2518202 8212 21863 18488 18005928 30752 0 10040 42036 6671 0 15652424 3063 173 8
+ 254       1 vda1 6700 5000 2500000 8000 21800 18400 18000000 30000 0 10000 42000 6600 0 15600000 3000 0 0
+ 259       0 nvme0n1 10 0 8 0 2 0 16 0 0 0 0
+";
+
+    #[test]
+    fn parses_diskstats() {
+        let stats = parse_diskstats(DISKSTATS);
+        assert_eq!(stats.len(), 4);
+        assert_eq!(stats[1].name, "vda");
+        assert_eq!(stats[1].read_bytes, 2518202 * 512);
+        assert_eq!(stats[1].written_bytes, 18005928 * 512);
+        // Kernels before 4.18 report fewer fields
+        assert_eq!((stats[3].read_bytes, stats[3].written_bytes), (4096, 8192));
+    }
+
+    #[test]
+    fn skips_malformed_lines() {
+        assert!(parse_diskstats("   7       0 loop0 12 0\n8 0 sda a b c d e f g\n").is_empty());
+    }
+
+    #[test]
+    fn reads_diskstats() {
+        let proc = Fixture::new();
+        proc.write("diskstats", DISKSTATS);
+        assert_eq!(read_diskstats(proc.path()).len(), 4);
+        assert!(read_diskstats(Fixture::new().path()).is_empty());
+    }
+}
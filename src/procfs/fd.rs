@@ -0,0 +1,148 @@
+use std::fs;
+use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
+
+pub struct OpenFile {
+    pub fd: u32,
+    pub path: String,
+    pub kind: String,
+    pub offset: u64,
+    pub flags: String,
+}
+
+// Lists the open file descriptors of a process from /proc/<pid>/fd, with the
+// offset and open flags taken from the matching /proc/<pid>/fdinfo entry
+pub fn read_open_files(root: &Path, pid: u32) -> Vec<OpenFile> {
+    let proc_dir = root.join(pid.to_string());
+    let Ok(entries) = fs::read_dir(proc_dir.join("fd")) else {
+        return Vec::new();
+    };
+    let mut files: Vec<OpenFile> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let fd: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let path = fs::read_link(entry.path())
+                .ok()?
+                .to_string_lossy()
+                .into_owned();
+            let info = fs::read_to_string(proc_dir.join("fdinfo").join(fd.to_string()))
+                .unwrap_or_default();
+            let (offset, flags) = parse_fdinfo(&info);
+            Some(OpenFile {
+                fd,
+                kind: file_kind(&entry.path(), &path),
+                path,
+                offset,
+                flags: format_flags(flags),
+            })
+        })
+        .collect();
+    files.sort_by_key(|f| f.fd);
+    files
+}
+
+// Returns the "pos" and "flags" (octal) fields of an fdinfo file
+pub fn parse_fdinfo(content: &str) -> (u64, u32) {
+    let mut offset = 0;
+    let mut flags = 0;
+    for line in content.lines() {
+        if let Some((key, value)) = line.split_once(':') {
+            match key {
+                "pos" => offset = value.trim().parse().unwrap_or(0),
+                "flags" => flags = u32::from_str_radix(value.trim(), 8).unwrap_or(0),
+                _ => {}
+            }
+        }
+    }
+    (offset, flags)
+}
+
+// Pseudo files (sockets, pipes, anonymous inodes) are named by their link target,
+// everything else is classified by the type of the file the link resolves to
+fn file_kind(link: &Path, target: &str) -> String {
+    if let Some(kind) = target.strip_prefix("anon_inode:") {
+        return format!("anon ({})", kind.trim_matches(|c| c == '[' || c == ']'));
+    }
+    if let Some((kind, _)) = target.split_once(":[") {
+        return kind.to_string();
+    }
+    match fs::metadata(link).map(|m| m.file_type()) {
+        Ok(t) if t.is_dir() => "dir",
+        Ok(t) if t.is_file() => "file",
+        Ok(t) if t.is_char_device() => "char",
+        Ok(t) if t.is_block_device() => "block",
+        Ok(t) if t.is_fifo() => "fifo",
+        Ok(t) if t.is_socket() => "socket",
+        _ => "unknown",
+    }
+    .to_string()
+}
+
+// Decodes the open(2) flags worth showing, in the octal values used by Linux
+pub fn format_flags(flags: u32) -> String {
+    let mut names = vec![match flags & 0o3 {
+        0o0 => "RDONLY",
+        0o1 => "WRONLY",
+        _ => "RDWR",
+    }];
+    for (bit, name) in [
+        (0o100, "CREAT"),
+        (0o1000, "TRUNC"),
+        (0o2000, "APPEND"),
+        (0o4000, "NONBLOCK"),
+        (0o10000, "DSYNC"),
+        (0o40000, "DIRECT"),
+        (0o200000, "DIRECTORY"),
+        (0o2000000, "CLOEXEC"),
+    ] {
+        if flags & bit != 0 {
+            names.push(name);
+        }
+    }
+    names.join("|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn parses_fdinfo() {
+        let info = "pos:\t4096\nflags:\t02102001\nmnt_id:\t29\nino:\t1234\n";
+        assert_eq!(parse_fdinfo(info), (4096, 0o2102001));
+        assert_eq!(parse_fdinfo(""), (0, 0));
+    }
+
+    #[test]
+    fn formats_flags() {
+        assert_eq!(format_flags(0), "RDONLY");
+        assert_eq!(format_flags(0o2102001), "WRONLY|APPEND|CLOEXEC");
+        assert_eq!(format_flags(0o4002), "RDWR|NONBLOCK");
+    }
+
+    #[test]
+    fn reads_open_files() {
+        let proc = Fixture::new();
+        proc.write("data/log.txt", "");
+        let log = proc.path().join("data/log.txt");
+        proc.link("42/fd/10", log.to_str().unwrap())
+            .write("42/fdinfo/10", "pos:\t512\nflags:\t02102001\n")
+            .link("42/fd/2", "pipe:[777]")
+            .link("42/fd/3", "anon_inode:[eventfd]")
+            .link("42/fd/4", "/nonexistent/deleted (deleted)");
+
+        let files = read_open_files(proc.path(), 42);
+        let fds: Vec<u32> = files.iter().map(|f| f.fd).collect();
+        assert_eq!(fds, vec![2, 3, 4, 10]);
+        assert_eq!(files[0].kind, "pipe");
+        assert_eq!(files[1].kind, "anon (eventfd)");
+        assert_eq!(files[2].kind, "unknown");
+        assert_eq!(files[3].kind, "file");
+        assert_eq!(files[3].path, log.to_str().unwrap());
+        assert_eq!(files[3].offset, 512);
+        assert_eq!(files[3].flags, "WRONLY|APPEND|CLOEXEC");
+        // Descriptors without fdinfo read as read-only at offset 0
+        assert_eq!((files[0].offset, files[0].flags.as_str()), (0, "RDONLY"));
+    }
+}
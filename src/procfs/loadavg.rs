@@ -0,0 +1,61 @@
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone)]
+pub struct LoadAverage {
+    pub one: f64,
+    pub five: f64,
+    pub fifteen: f64,
+    // Runnable and total scheduling entities, i.e. the run queue
+    pub running: u32,
+    pub total: u32,
+}
+
+// Reads the load averages and the run queue from /proc/loadavg
+pub fn read_loadavg(root: &Path) -> Option<LoadAverage> {
+    parse_loadavg(&fs::read_to_string(root.join("loadavg")).ok()?)
+}
+
+// Parses "1.58 1.40 0.87 3/72 16902"
+pub fn parse_loadavg(content: &str) -> Option<LoadAverage> {
+    let mut fields = content.split_whitespace();
+    let one = fields.next()?.parse().ok()?;
+    let five = fields.next()?.parse().ok()?;
+    let fifteen = fields.next()?.parse().ok()?;
+    let (running, total) = fields.next()?.split_once('/')?;
+    Some(LoadAverage {
+        one,
+        five,
+        fifteen,
+        running: running.parse().ok()?,
+        total: total.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn parses_loadavg() {
+        let load = parse_loadavg("1.58 1.40 0.87 3/72 16902\n").unwrap();
+        assert_eq!((load.one, load.five, load.fifteen), (1.58, 1.40, 0.87));
+        assert_eq!((load.running, load.total), (3, 72));
+    }
+
+    #[test]
+    fn rejects_malformed_loadavg() {
+        assert!(parse_loadavg("").is_none());
+        assert!(parse_loadavg("1.58 1.40 0.87 3 16902").is_none());
+        assert!(parse_loadavg("1.58 high 0.87 3/72 16902").is_none());
+    }
+
+    #[test]
+    fn reads_loadavg() {
+        let proc = Fixture::new();
+        assert!(read_loadavg(proc.path()).is_none());
+        proc.write("loadavg", "0.00 0.01 0.05 1/100 42\n");
+        assert_eq!(read_loadavg(proc.path()).unwrap().total, 100);
+    }
+}
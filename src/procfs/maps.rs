@@ -0,0 +1,176 @@
+use std::fs;
+use std::path::Path;
+
+#[derive(Default)]
+pub struct Mapping {
+    pub start: u64,
+    pub end: u64,
+    pub perms: String,
+    pub offset: u64,
+    pub path: String,
+    pub rss: u64,
+    pub pss: u64,
+    pub shared: u64,
+    pub private: u64,
+    pub swap: u64,
+}
+
+pub fn read_mappings(root: &Path, pid: u32) -> Vec<Mapping> {
+    fs::read_to_string(root.join(pid.to_string()).join("smaps"))
+        .map(|content| parse_smaps(&content))
+        .unwrap_or_default()
+}
+
+// Returns the totals over all mappings, which smaps_rollup reports as a single
+// pseudo mapping in the same format as smaps
+pub fn read_rollup(root: &Path, pid: u32) -> Option<Mapping> {
+    let content = fs::read_to_string(root.join(pid.to_string()).join("smaps_rollup")).ok()?;
+    parse_smaps(&content).into_iter().next()
+}
+
+// Parses smaps style content: a maps header line per mapping followed by
+// "Key: value kB" lines. Sizes are returned in bytes.
+pub fn parse_smaps(content: &str) -> Vec<Mapping> {
+    let mut mappings: Vec<Mapping> = Vec::new();
+    for line in content.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.is_empty() {
+            continue;
+        }
+
+        if let Some((start, end)) = fields[0].split_once('-') {
+            if let (Ok(start), Ok(end)) =
+                (u64::from_str_radix(start, 16), u64::from_str_radix(end, 16))
+            {
+                mappings.push(Mapping {
+                    start,
+                    end,
+                    perms: fields.get(1).unwrap_or(&"").to_string(),
+                    offset: fields
+                        .get(2)
+                        .and_then(|o| u64::from_str_radix(o, 16).ok())
+                        .unwrap_or(0),
+                    path: fields.get(5..).unwrap_or_default().join(" "),
+                    ..Default::default()
+                });
+                continue;
+            }
+        }
+
+        let Some(mapping) = mappings.last_mut() else {
+            continue;
+        };
+        let value = fields
+            .get(1)
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0)
+            * 1024;
+        match fields[0] {
+            "Rss:" => mapping.rss = value,
+            "Pss:" => mapping.pss = value,
+            "Shared_Clean:" | "Shared_Dirty:" => mapping.shared += value,
+            "Private_Clean:" | "Private_Dirty:" => mapping.private += value,
+            "Swap:" => mapping.swap = value,
+            _ => {}
+        }
+    }
+    mappings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    const SMAPS: &str = "\
+55a4c1a00000-55a4c1a28000 r--p 00000000 08:01 1835053                    /usr/bin/bash
+Size:                160 kB
+Rss:                 160 kB
+Pss:                  40 kB
+Shared_Clean:        160 kB
+Shared_Dirty:          0 kB
+Private_Clean:         0 kB
+Private_Dirty:         0 kB
+Swap:                  0 kB
+VmFlags: rd mr mw me dw sd
+55a4c2c4e000-55a4c2df6000 rw-p 00000000 00:00 0                          [heap]
+Size:               1696 kB
+Rss:                1600 kB
+Pss:                1600 kB
+Shared_Clean:          0 kB
+Shared_Dirty:          0 kB
+Private_Clean:         4 kB
+Private_Dirty:      1596 kB
+Swap:                 12 kB
+VmFlags: rd wr mr mw me ac sd
+7f3b8c000000-7f3b8c021000 rw-p 00001000 00:00 0 
+Size:                132 kB
+Rss:                   8 kB
+Pss:                   8 kB
+Private_Dirty:         8 kB
+Swap:                  0 kB
+7f3b8d200000-7f3b8d201000 r--p 00000000 08:01 2 /tmp/with space (deleted)
+Rss:                   4 kB
+";
+
+    #[test]
+    fn parses_mappings() {
+        let proc = Fixture::new();
+        proc.write("42/smaps", SMAPS);
+        let mappings = read_mappings(proc.path(), 42);
+        assert_eq!(mappings.len(), 4);
+
+        let bash = &mappings[0];
+        assert_eq!((bash.start, bash.end), (0x55a4c1a00000, 0x55a4c1a28000));
+        assert_eq!(bash.perms, "r--p");
+        assert_eq!(bash.path, "/usr/bin/bash");
+        assert_eq!(bash.rss, 160 * 1024);
+        assert_eq!(bash.pss, 40 * 1024);
+        assert_eq!(bash.shared, 160 * 1024);
+        assert_eq!(bash.private, 0);
+
+        let heap = &mappings[1];
+        assert_eq!(heap.path, "[heap]");
+        assert_eq!(heap.private, 1600 * 1024);
+        assert_eq!(heap.swap, 12 * 1024);
+
+        // Anonymous mappings have no path
+        let anonymous = &mappings[2];
+        assert_eq!(anonymous.path, "");
+        assert_eq!(anonymous.offset, 0x1000);
+        assert_eq!(anonymous.rss, 8 * 1024);
+        assert_eq!(anonymous.shared, 0);
+
+        assert_eq!(mappings[3].path, "/tmp/with space (deleted)");
+    }
+
+    #[test]
+    fn reads_rollup() {
+        let proc = Fixture::new();
+        proc.write(
+            "42/smaps_rollup",
+            "55a4c1a00000-7ffd5e9fe000 ---p 00000000 00:00 0                          [rollup]\n\
+             Rss:                1768 kB\n\
+             Pss:                1648 kB\n\
+             Shared_Clean:        160 kB\n\
+             Private_Dirty:      1604 kB\n\
+             Swap:                 12 kB\n",
+        );
+        let rollup = read_rollup(proc.path(), 42).unwrap();
+        assert_eq!(rollup.rss, 1768 * 1024);
+        assert_eq!(rollup.pss, 1648 * 1024);
+        assert_eq!(rollup.shared, 160 * 1024);
+        assert_eq!(rollup.private, 1604 * 1024);
+        assert_eq!(rollup.swap, 12 * 1024);
+    }
+
+    #[test]
+    fn reads_nothing_for_missing_files() {
+        let proc = Fixture::new();
+        // Kernel threads and processes of other users have no readable smaps
+        proc.write("43/smaps_rollup", "");
+        assert!(read_rollup(proc.path(), 42).is_none());
+        assert!(read_rollup(proc.path(), 43).is_none());
+        assert!(read_mappings(proc.path(), 42).is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// Memory counters from /proc/meminfo, all in bytes
+#[derive(Clone, Copy, Default)]
+pub struct MemInfo {
+    pub total: u64,
+    pub free: u64,
+    pub available: u64,
+    pub buffers: u64,
+    pub cached: u64,
+    pub shared: u64,
+    pub slab_reclaimable: u64,
+    pub slab_unreclaimable: u64,
+    pub dirty: u64,
+    pub writeback: u64,
+    pub huge_pages_total: u64,
+    pub huge_pages_free: u64,
+    pub committed: u64,
+    pub commit_limit: u64,
+    pub swap_total: u64,
+    pub swap_free: u64,
+}
+
+impl MemInfo {
+    // Memory not accounted to any other part of the breakdown, i.e. what is left
+    // once free memory, buffers, the page cache (shared memory included) and
+    // both kinds of slab are taken out. This is mostly the anonymous memory of
+    // processes, plus kernel allocations such as page tables and stacks.
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(
+            self.free
+                + self.buffers
+                + self.cached
+                + self.slab_reclaimable
+                + self.slab_unreclaimable,
+        )
+    }
+
+    // Page cache without shared memory, which the kernel counts as cached too
+    pub fn page_cache(&self) -> u64 {
+        self.cached.saturating_sub(self.shared)
+    }
+}
+
+pub fn read_meminfo(root: &Path) -> Option<MemInfo> {
+    parse_meminfo(&fs::read_to_string(root.join("meminfo")).ok()?)
+}
+
+// Parses "MemTotal:        6147400 kB" lines. Huge page counts have no unit
+// and are converted to bytes with the huge page size.
+pub fn parse_meminfo(content: &str) -> Option<MemInfo> {
+    let values: HashMap<&str, u64> = content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let mut value = value.split_whitespace();
+            let number: u64 = value.next()?.parse().ok()?;
+            let bytes = match value.next() {
+                Some("kB") => number * 1024,
+                _ => number,
+            };
+            Some((key, bytes))
+        })
+        .collect();
+    let value = |key: &str| values.get(key).copied().unwrap_or(0);
+    let huge_page_size = value("Hugepagesize");
+
+    Some(MemInfo {
+        total: *values.get("MemTotal")?,
+        free: value("MemFree"),
+        available: value("MemAvailable"),
+        buffers: value("Buffers"),
+        cached: value("Cached"),
+        shared: value("Shmem"),
+        slab_reclaimable: value("SReclaimable"),
+        slab_unreclaimable: value("SUnreclaim"),
+        dirty: value("Dirty"),
+        writeback: value("Writeback"),
+        huge_pages_total: value("HugePages_Total") * huge_page_size,
+        huge_pages_free: value("HugePages_Free") * huge_page_size,
+        committed: value("Committed_AS"),
+        commit_limit: value("CommitLimit"),
+        swap_total: value("SwapTotal"),
+        swap_free: value("SwapFree"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    const MEMINFO: &str = "MemTotal:        8000000 kB
+MemFree:         1000000 kB
+MemAvailable:    5000000 kB
+Buffers:          200000 kB
+Cached:          3000000 kB
+SwapCached:            0 kB
+Shmem:            500000 kB
+SReclaimable:     400000 kB
+SUnreclaim:       100000 kB
+Dirty:              1200 kB
+Writeback:             0 kB
+CommitLimit:     6000000 kB
+Committed_AS:    7000000 kB
+SwapTotal:       2000000 kB
+SwapFree:        1500000 kB
+HugePages_Total:       4
+HugePages_Free:        1
+Hugepagesize:       2048 kB
+";
+
+    #[test]
+    fn parses_meminfo() {
+        let meminfo = parse_meminfo(MEMINFO).unwrap();
+        assert_eq!(meminfo.total, 8000000 * 1024);
+        assert_eq!(meminfo.available, 5000000 * 1024);
+        assert_eq!(meminfo.shared, 500000 * 1024);
+        assert_eq!(meminfo.dirty, 1200 * 1024);
+        assert_eq!(meminfo.committed, 7000000 * 1024);
+        assert_eq!(meminfo.swap_free, 1500000 * 1024);
+        // Huge page counts have no unit
+        assert_eq!(meminfo.huge_pages_total, 4 * 2048 * 1024);
+        assert_eq!(meminfo.huge_pages_free, 2048 * 1024);
+    }
+
+    #[test]
+    fn breaks_memory_down() {
+        let meminfo = parse_meminfo(MEMINFO).unwrap();
+        assert_eq!(meminfo.used(), 3300000 * 1024);
+        assert_eq!(meminfo.page_cache(), 2500000 * 1024);
+        let parts = meminfo.used()
+            + meminfo.slab_unreclaimable
+            + meminfo.slab_reclaimable
+            + meminfo.buffers
+            + meminfo.page_cache()
+            + meminfo.shared
+            + meminfo.free;
+        assert_eq!(parts, meminfo.total);
+    }
+
+    #[test]
+    fn requires_total() {
+        assert!(parse_meminfo("MemFree: 1000 kB\n").is_none());
+        let meminfo = parse_meminfo("MemTotal: 1000 kB\n").unwrap();
+        assert_eq!((meminfo.free, meminfo.used()), (0, 1000 * 1024));
+    }
+
+    #[test]
+    fn reads_meminfo() {
+        let proc = Fixture::new();
+        assert!(read_meminfo(proc.path()).is_none());
+        proc.write("meminfo", MEMINFO);
+        assert_eq!(read_meminfo(proc.path()).unwrap().buffers, 200000 * 1024);
+    }
+}
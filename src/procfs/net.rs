@@ -0,0 +1,261 @@
+use std::collections::HashSet;
+use std::fs;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+#[derive(Clone, Copy, PartialEq, strum::Display)]
+pub enum Protocol {
+    #[strum(serialize = "TCP")]
+    Tcp,
+    #[strum(serialize = "TCP6")]
+    Tcp6,
+    #[strum(serialize = "UDP")]
+    Udp,
+    #[strum(serialize = "UDP6")]
+    Udp6,
+    #[strum(serialize = "Unix")]
+    Unix,
+}
+
+pub struct Socket {
+    pub protocol: Protocol,
+    pub local: String,
+    pub remote: String,
+    pub state: String,
+    pub tx_queue: u64,
+    pub rx_queue: u64,
+    pub inode: u64,
+}
+
+// Returns the inodes of all the sockets a process holds open, taken from the
+// "socket:[<inode>]" targets of its fd links
+pub fn socket_inodes(root: &Path, pid: u32) -> HashSet<u64> {
+    let Ok(entries) = fs::read_dir(root.join(pid.to_string()).join("fd")) else {
+        return HashSet::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| fs::read_link(entry.path()).ok())
+        .filter_map(|target| {
+            target
+                .to_str()?
+                .strip_prefix("socket:[")?
+                .strip_suffix(']')?
+                .parse()
+                .ok()
+        })
+        .collect()
+}
+
+// Lists the sockets of a process. The tables under /proc/<pid>/net cover the
+// whole network namespace, so they are narrowed down to the process' own inodes.
+pub fn read_sockets(root: &Path, pid: u32) -> Vec<Socket> {
+    let inodes = socket_inodes(root, pid);
+    if inodes.is_empty() {
+        return Vec::new();
+    }
+
+    let net = root.join(pid.to_string()).join("net");
+    let read = |file: &str| fs::read_to_string(net.join(file)).unwrap_or_default();
+    let mut sockets = Vec::new();
+    sockets.extend(parse_inet(&read("tcp"), Protocol::Tcp));
+    sockets.extend(parse_inet(&read("tcp6"), Protocol::Tcp6));
+    sockets.extend(parse_inet(&read("udp"), Protocol::Udp));
+    sockets.extend(parse_inet(&read("udp6"), Protocol::Udp6));
+    sockets.extend(parse_unix(&read("unix")));
+    sockets.retain(|s| inodes.contains(&s.inode));
+    sockets
+}
+
+// Parses a /proc/net/{tcp,tcp6,udp,udp6} table
+pub fn parse_inet(content: &str, protocol: Protocol) -> Vec<Socket> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 {
+                return None;
+            }
+            let (tx_queue, rx_queue) = fields[4].split_once(':')?;
+            Some(Socket {
+                protocol,
+                local: parse_address(fields[1])?,
+                remote: parse_address(fields[2])?,
+                state: tcp_state(fields[3], protocol).to_string(),
+                tx_queue: u64::from_str_radix(tx_queue, 16).ok()?,
+                rx_queue: u64::from_str_radix(rx_queue, 16).ok()?,
+                inode: fields[9].parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+// Parses /proc/net/unix, which has no remote address or queue sizes
+pub fn parse_unix(content: &str) -> Vec<Socket> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 7 {
+                return None;
+            }
+            let kind = match fields[4] {
+                "0001" => "STREAM",
+                "0002" => "DGRAM",
+                "0005" => "SEQPACKET",
+                _ => "UNKNOWN",
+            };
+            let state = match fields[5] {
+                "01" => "UNCONNECTED",
+                "02" => "CONNECTING",
+                "03" => "CONNECTED",
+                "04" => "DISCONNECTING",
+                _ => "UNKNOWN",
+            };
+            Some(Socket {
+                protocol: Protocol::Unix,
+                local: fields.get(7).unwrap_or(&"").to_string(),
+                remote: String::new(),
+                state: format!("{} {}", kind, state),
+                tx_queue: 0,
+                rx_queue: 0,
+                inode: fields[6].parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+// Decodes "0100007F:0035" style addresses. The address words are printed in
+// host byte order, so they are turned back into bytes the same way.
+fn parse_address(field: &str) -> Option<String> {
+    let (address, port) = field.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+    match address.len() {
+        8 => {
+            let word = u32::from_str_radix(address, 16).ok()?;
+            Some(format!("{}:{}", Ipv4Addr::from(word.to_ne_bytes()), port))
+        }
+        32 => {
+            let mut bytes = [0u8; 16];
+            for (i, chunk) in bytes.chunks_mut(4).enumerate() {
+                let word = u32::from_str_radix(&address[i * 8..i * 8 + 8], 16).ok()?;
+                chunk.copy_from_slice(&word.to_ne_bytes());
+            }
+            Some(format!("[{}]:{}", Ipv6Addr::from(bytes), port))
+        }
+        _ => None,
+    }
+}
+
+fn tcp_state(field: &str, protocol: Protocol) -> &'static str {
+    match (field, protocol) {
+        // UDP sockets only ever report these two
+        ("01", Protocol::Udp | Protocol::Udp6) => "CONNECTED",
+        ("07", Protocol::Udp | Protocol::Udp6) => "UNCONNECTED",
+        ("01", _) => "ESTABLISHED",
+        ("02", _) => "SYN_SENT",
+        ("03", _) => "SYN_RECV",
+        ("04", _) => "FIN_WAIT1",
+        ("05", _) => "FIN_WAIT2",
+        ("06", _) => "TIME_WAIT",
+        ("07", _) => "CLOSE",
+        ("08", _) => "CLOSE_WAIT",
+        ("09", _) => "LAST_ACK",
+        ("0A", _) => "LISTEN",
+        ("0B", _) => "CLOSING",
+        _ => "UNKNOWN",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    const TCP: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0CEA 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 1001 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:0CEA 0100007F:D431 01 0000001A:00000002 00:00000000 00000000  1000        0 1002 1 0000000000000000 20 4 30 10 -1
+   2: 0100007F:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 2001 1 0000000000000000 100 0 0 10 0
+";
+
+    const UDP6: &str = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  0: 00000000000000000000000001000000:0035 00000000000000000000000000000000:0000 07 00000000:00000000 00:00000000 00000000     0        0 1003 2 0000000000000000 0
+";
+
+    const UNIX: &str = "Num       RefCount Protocol Flags    Type St Inode Path
+0000000000000000: 00000002 00000000 00010000 0001 01 1004 /run/app.sock
+0000000000000000: 00000003 00000000 00000000 0002 03 1005
+";
+
+    // Host byte order is only little-endian on the machines the fixtures
+    // were captured on
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn parses_inet_tables() {
+        let sockets = parse_inet(TCP, Protocol::Tcp);
+        assert_eq!(sockets.len(), 3);
+        assert_eq!(sockets[0].local, "127.0.0.1:3306");
+        assert_eq!(sockets[0].remote, "0.0.0.0:0");
+        assert_eq!(sockets[0].state, "LISTEN");
+        assert_eq!(sockets[1].remote, "127.0.0.1:54321");
+        assert_eq!(sockets[1].state, "ESTABLISHED");
+        assert_eq!((sockets[1].tx_queue, sockets[1].rx_queue), (26, 2));
+        assert_eq!(sockets[1].inode, 1002);
+
+        let sockets = parse_inet(UDP6, Protocol::Udp6);
+        assert_eq!(sockets.len(), 1);
+        assert_eq!(sockets[0].local, "[::1]:53");
+        assert_eq!(sockets[0].state, "UNCONNECTED");
+    }
+
+    #[test]
+    fn parses_unix_table() {
+        let sockets = parse_unix(UNIX);
+        assert_eq!(sockets.len(), 2);
+        assert_eq!(sockets[0].local, "/run/app.sock");
+        assert_eq!(sockets[0].state, "STREAM UNCONNECTED");
+        assert_eq!(sockets[1].local, "");
+        assert_eq!(sockets[1].state, "DGRAM CONNECTED");
+        assert_eq!(sockets[1].inode, 1005);
+    }
+
+    #[test]
+    fn skips_malformed_lines() {
+        assert!(parse_inet("header\n   0: garbage\n", Protocol::Tcp).is_empty());
+        assert!(parse_unix("header\n0000: 1 2\n").is_empty());
+    }
+
+    #[test]
+    fn reads_sockets_of_a_process() {
+        let proc = Fixture::new();
+        proc.link("42/fd/0", "/dev/null")
+            .link("42/fd/3", "socket:[1001]")
+            .link("42/fd/4", "socket:[1002]")
+            .link("42/fd/5", "socket:[1004]")
+            .link("42/fd/6", "pipe:[1003]")
+            .write("42/net/tcp", TCP)
+            .write("42/net/udp6", UDP6)
+            .write("42/net/unix", UNIX);
+
+        assert_eq!(
+            socket_inodes(proc.path(), 42),
+            HashSet::from([1001, 1002, 1004])
+        );
+        let mut inodes: Vec<u64> = read_sockets(proc.path(), 42)
+            .iter()
+            .map(|s| s.inode)
+            .collect();
+        inodes.sort();
+        // The listener on port 8080 belongs to another process
+        assert_eq!(inodes, vec![1001, 1002, 1004]);
+    }
+
+    #[test]
+    fn reads_nothing_for_a_missing_process() {
+        let proc = Fixture::new();
+        assert!(socket_inodes(proc.path(), 42).is_empty());
+        assert!(read_sockets(proc.path(), 42).is_empty());
+    }
+}
@@ -0,0 +1,124 @@
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Copy, PartialEq, strum::Display, strum::EnumIter, strum::EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum Resource {
+    Cpu,
+    Memory,
+    Io,
+}
+
+// Share of wall time, in percent, that tasks were stalled over the last 10s, 60s and 300s
+#[derive(Clone, Copy, Default)]
+pub struct Stall {
+    pub avg10: f32,
+    pub avg60: f32,
+    pub avg300: f32,
+}
+
+#[derive(Clone)]
+pub struct Pressure {
+    // At least one task stalled
+    pub some: Stall,
+    // All non-idle tasks stalled at once, not reported for the CPU by older kernels
+    pub full: Option<Stall>,
+}
+
+// Reads /proc/pressure/<resource>. Kernels built without PSI, or booted with
+// psi=0, have no such file.
+pub fn read_pressure(root: &Path, resource: Resource) -> Option<Pressure> {
+    let content = fs::read_to_string(root.join("pressure").join(resource.to_string())).ok()?;
+    parse_pressure(&content)
+}
+
+// Parses "some avg10=0.00 avg60=0.00 avg300=0.00 total=0" lines
+pub fn parse_pressure(content: &str) -> Option<Pressure> {
+    let mut some = None;
+    let mut full = None;
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let kind = fields.next();
+        let mut stall = Stall::default();
+        for field in fields {
+            let Some((key, value)) = field.split_once('=') else {
+                continue;
+            };
+            match key {
+                "avg10" => stall.avg10 = value.parse().ok()?,
+                "avg60" => stall.avg60 = value.parse().ok()?,
+                "avg300" => stall.avg300 = value.parse().ok()?,
+                _ => {}
+            }
+        }
+        match kind {
+            Some("some") => some = Some(stall),
+            Some("full") => full = Some(stall),
+            _ => {}
+        }
+    }
+    Some(Pressure { some: some?, full })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn parses_pressure() {
+        let pressure = parse_pressure(
+            "some avg10=1.50 avg60=0.75 avg300=0.10 total=123456\n\
+             full avg10=0.50 avg60=0.25 avg300=0.00 total=23456\n",
+        )
+        .unwrap();
+        assert_eq!(
+            (
+                pressure.some.avg10,
+                pressure.some.avg60,
+                pressure.some.avg300
+            ),
+            (1.5, 0.75, 0.1)
+        );
+        assert_eq!(pressure.full.unwrap().avg10, 0.5);
+    }
+
+    #[test]
+    fn parses_cpu_pressure_of_older_kernels() {
+        let pressure = parse_pressure("some avg10=2.00 avg60=1.00 avg300=0.50 total=1\n").unwrap();
+        assert_eq!(pressure.some.avg10, 2.0);
+        assert!(pressure.full.is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_pressure() {
+        assert!(parse_pressure("").is_none());
+        assert!(parse_pressure("full avg10=0.00 avg60=0.00 avg300=0.00 total=0\n").is_none());
+        assert!(parse_pressure("some avg10=x avg60=0.00 avg300=0.00 total=0\n").is_none());
+    }
+
+    #[test]
+    fn reads_pressure_of_each_resource() {
+        let proc = Fixture::new();
+        proc.write(
+            "pressure/memory",
+            "some avg10=3.00 avg60=0.00 avg300=0.00 total=0\n",
+        )
+        .write(
+            "pressure/io",
+            "some avg10=4.00 avg60=0.00 avg300=0.00 total=0\n",
+        );
+        assert!(read_pressure(proc.path(), Resource::Cpu).is_none());
+        assert_eq!(
+            read_pressure(proc.path(), Resource::Memory)
+                .unwrap()
+                .some
+                .avg10,
+            3.0
+        );
+        assert_eq!(
+            read_pressure(proc.path(), Resource::Io).unwrap().some.avg10,
+            4.0
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use std::fs;
+use std::path::Path;
+
+// Returns the kernel stack of a thread from /proc/<pid>/task/<tid>/stack,
+// innermost frame first. Reading it requires CAP_SYS_ADMIN, so an unreadable
+// file yields an empty stack.
+pub fn read_kernel_stack(root: &Path, pid: u32, tid: u32) -> Vec<String> {
+    fs::read_to_string(task_dir(root, pid, tid).join("stack"))
+        .map(|content| parse_kernel_stack(&content))
+        .unwrap_or_default()
+}
+
+// Parses "[<0>] do_epoll_wait+0x4b5/0x4f0" lines into "do_epoll_wait+0x4b5"
+pub fn parse_kernel_stack(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let frame = line.split_once("] ").map_or(line, |(_, frame)| frame);
+            let frame = frame.split_once('/').map_or(frame, |(frame, _)| frame);
+            (!frame.is_empty()).then(|| frame.to_string())
+        })
+        .collect()
+}
+
+// Returns the kernel function a thread is blocked in, or None when it is running
+pub fn read_wchan(root: &Path, pid: u32, tid: u32) -> Option<String> {
+    let wchan = fs::read_to_string(task_dir(root, pid, tid).join("wchan")).ok()?;
+    let wchan = wchan.trim();
+    (!wchan.is_empty() && wchan != "0").then(|| wchan.to_string())
+}
+
+fn task_dir(root: &Path, pid: u32, tid: u32) -> std::path::PathBuf {
+    root.join(pid.to_string())
+        .join("task")
+        .join(tid.to_string())
+}
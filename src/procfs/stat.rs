@@ -0,0 +1,175 @@
+use std::fs;
+use std::path::Path;
+
+// Cumulative time spent in each state since boot, in clock ticks
+#[derive(Clone, Copy, Default)]
+pub struct CpuTimes {
+    pub user: u64,
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    pub steal: u64,
+    pub guest: u64,
+    pub guest_nice: u64,
+}
+
+// Share of each state, in percent, of the time elapsed between two readings
+#[derive(Clone, Copy, Default)]
+pub struct CpuBreakdown {
+    pub user: f64,
+    pub nice: f64,
+    pub system: f64,
+    pub idle: f64,
+    pub iowait: f64,
+    pub irq: f64,
+    pub softirq: f64,
+    pub steal: f64,
+    // guest and guest_nice
+    pub guest: f64,
+}
+
+impl CpuTimes {
+    // The kernel already counts guest time in user time and guest_nice time in
+    // nice time, so they are subtracted to keep the categories disjoint
+    pub fn breakdown_since(&self, previous: &CpuTimes) -> CpuBreakdown {
+        let delta = |now: u64, then: u64| now.saturating_sub(then) as f64;
+        let guest = delta(self.guest, previous.guest);
+        let guest_nice = delta(self.guest_nice, previous.guest_nice);
+        let user = (delta(self.user, previous.user) - guest).max(0.0);
+        let nice = (delta(self.nice, previous.nice) - guest_nice).max(0.0);
+        let system = delta(self.system, previous.system);
+        let idle = delta(self.idle, previous.idle);
+        let iowait = delta(self.iowait, previous.iowait);
+        let irq = delta(self.irq, previous.irq);
+        let softirq = delta(self.softirq, previous.softirq);
+        let steal = delta(self.steal, previous.steal);
+
+        let total =
+            user + nice + system + idle + iowait + irq + softirq + steal + guest + guest_nice;
+        if total == 0.0 {
+            return CpuBreakdown::default();
+        }
+        let percent = |ticks: f64| ticks / total * 100.0;
+        CpuBreakdown {
+            user: percent(user),
+            nice: percent(nice),
+            system: percent(system),
+            idle: percent(idle),
+            iowait: percent(iowait),
+            irq: percent(irq),
+            softirq: percent(softirq),
+            steal: percent(steal),
+            guest: percent(guest + guest_nice),
+        }
+    }
+}
+
+pub struct CpuStat {
+    pub total: CpuTimes,
+    pub cores: Vec<CpuTimes>,
+}
+
+// Reads the CPU time counters from /proc/stat
+pub fn read_cpu_stat(root: &Path) -> Option<CpuStat> {
+    parse_cpu_stat(&fs::read_to_string(root.join("stat")).ok()?)
+}
+
+// Parses the "cpu" line and the "cpuN" lines that follow it. Older kernels
+// report fewer columns, the missing ones are left at zero.
+pub fn parse_cpu_stat(content: &str) -> Option<CpuStat> {
+    let mut total = None;
+    let mut cores = Vec::new();
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(name) = fields.next().filter(|name| name.starts_with("cpu")) else {
+            continue;
+        };
+        let values: Vec<u64> = fields.map_while(|f| f.parse().ok()).collect();
+        let value = |i: usize| values.get(i).copied().unwrap_or(0);
+        let times = CpuTimes {
+            user: value(0),
+            nice: value(1),
+            system: value(2),
+            idle: value(3),
+            iowait: value(4),
+            irq: value(5),
+            softirq: value(6),
+            steal: value(7),
+            guest: value(8),
+            guest_nice: value(9),
+        };
+        if name == "cpu" {
+            total = Some(times);
+        } else {
+            cores.push(times);
+        }
+    }
+    Some(CpuStat {
+        total: total?,
+        cores,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    const STAT: &str = "cpu  1000 100 500 8000 200 50 30 20 300 40
+cpu0 600 60 300 4000 100 25 15 10 200 20
+cpu1 400 40 200 4000 100 25 15 10 100 20
+intr 12345 0 0
+ctxt 67890
+btime 1700000000
+";
+
+    #[test]
+    fn parses_cpu_stat() {
+        let stat = parse_cpu_stat(STAT).unwrap();
+        assert_eq!(stat.cores.len(), 2);
+        assert_eq!(stat.total.user, 1000);
+        assert_eq!(stat.total.guest_nice, 40);
+        assert_eq!(stat.cores[1].idle, 4000);
+    }
+
+    #[test]
+    fn leaves_missing_columns_at_zero() {
+        let stat = parse_cpu_stat("cpu 10 20 30 40\ncpu0 10 20 30 40\n").unwrap();
+        assert_eq!(stat.total.system, 30);
+        assert_eq!((stat.total.iowait, stat.total.steal), (0, 0));
+        assert!(parse_cpu_stat("intr 1 2 3\n").is_none());
+    }
+
+    #[test]
+    fn breaks_down_without_counting_guest_time_twice() {
+        let then = CpuTimes::default();
+        let now = CpuTimes {
+            user: 500,
+            nice: 100,
+            system: 100,
+            idle: 300,
+            guest: 100,
+            guest_nice: 50,
+            ..CpuTimes::default()
+        };
+        let breakdown = now.breakdown_since(&then);
+        // 1000 ticks once guest time is taken out of user and nice time
+        assert_eq!(breakdown.user, 40.0);
+        assert_eq!(breakdown.nice, 5.0);
+        assert_eq!(breakdown.system, 10.0);
+        assert_eq!(breakdown.idle, 30.0);
+        assert_eq!(breakdown.guest, 15.0);
+        assert_eq!(now.breakdown_since(&now).idle, 0.0);
+    }
+
+    #[test]
+    fn reads_cpu_stat() {
+        let proc = Fixture::new();
+        assert!(read_cpu_stat(proc.path()).is_none());
+        proc.write("stat", STAT);
+        assert_eq!(read_cpu_stat(proc.path()).unwrap().cores.len(), 2);
+    }
+}
@@ -0,0 +1,97 @@
+use std::fs;
+use std::path::Path;
+
+pub struct TaskStat {
+    pub tid: u32,
+    pub name: String,
+    pub state: char,
+    // Parent of the process the thread belongs to
+    pub parent: u32,
+    // utime + stime, in clock ticks
+    pub cpu_time: u64,
+    pub last_cpu: u32,
+}
+
+// Reads the stat file of every thread of a process from /proc/<pid>/task
+pub fn read_tasks(root: &Path, pid: u32) -> Vec<TaskStat> {
+    let Ok(entries) = fs::read_dir(root.join(pid.to_string()).join("task")) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let tid = entry.file_name().to_str()?.parse().ok()?;
+            let content = fs::read_to_string(entry.path().join("stat")).ok()?;
+            parse_stat(tid, &content)
+        })
+        .collect()
+}
+
+// Parses a /proc/<pid>/stat style line. The command name is wrapped in
+// parentheses and may itself contain spaces or parentheses, so the fields are
+// split after its last closing parenthesis.
+pub fn parse_stat(tid: u32, content: &str) -> Option<TaskStat> {
+    let open = content.find('(')?;
+    let close = content.rfind(')')?;
+    let name = content.get(open + 1..close)?.to_string();
+    // fields[0] is field 3 of proc(5), the state
+    let fields: Vec<&str> = content.get(close + 1..)?.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(TaskStat {
+        tid,
+        name,
+        state: fields.first()?.chars().next()?,
+        parent: fields.get(1)?.parse().ok()?,
+        cpu_time: utime + stime,
+        last_cpu: fields.get(36)?.parse().ok()?,
+    })
+}
+
+pub fn clock_ticks_per_second() -> u64 {
+    // SAFETY: sysconf has no preconditions
+    match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+        ticks if ticks > 0 => ticks as u64,
+        _ => 100,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    // Fields 3 to 52 of a stat line, with utime 150, stime 50 and processor 3
+    const FIELDS: &str = "S 1 1234 1234 0 -1 4194560 1000 0 0 0 150 50 0 0 20 0 4 0 \
+                          12345 1000000 200 18446744073709551615 1 1 0 0 0 0 0 0 0 0 0 0 17 3 \
+                          0 0 0 0 0 0 0 0 0 0 0 0 0";
+
+    #[test]
+    fn parses_names_with_spaces_and_parentheses() {
+        let proc = Fixture::new();
+        proc.write("1234/task/1234/stat", &format!("1234 (a) (b) {}\n", FIELDS))
+            .write(
+                "1234/task/1240/stat",
+                &format!("1240 (Web Content) {}\n", FIELDS),
+            )
+            .write("1234/task/1241/stat", "1241 (gone");
+
+        let mut tasks = read_tasks(proc.path(), 1234);
+        tasks.sort_by_key(|t| t.tid);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].tid, 1234);
+        assert_eq!(tasks[0].name, "a) (b");
+        assert_eq!(tasks[0].state, 'S');
+        assert_eq!(tasks[0].parent, 1);
+        assert_eq!(tasks[0].cpu_time, 200);
+        assert_eq!(tasks[0].last_cpu, 3);
+        assert_eq!(tasks[1].name, "Web Content");
+    }
+
+    #[test]
+    fn rejects_truncated_lines() {
+        assert!(parse_stat(1, "1 (init) S 0 1 1").is_none());
+        assert!(parse_stat(1, "").is_none());
+        assert!(read_tasks(Fixture::new().path(), 1).is_empty());
+    }
+}
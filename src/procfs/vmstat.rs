@@ -0,0 +1,63 @@
+use std::fs;
+use std::path::Path;
+
+// Cumulative number of pages swapped in and out since boot
+#[derive(Clone, Copy, Default)]
+pub struct SwapActivity {
+    pub pages_in: u64,
+    pub pages_out: u64,
+}
+
+pub fn read_swap_activity(root: &Path) -> Option<SwapActivity> {
+    parse_swap_activity(&fs::read_to_string(root.join("vmstat")).ok()?)
+}
+
+// Picks the "pswpin 0" and "pswpout 0" lines out of /proc/vmstat
+pub fn parse_swap_activity(content: &str) -> Option<SwapActivity> {
+    let mut activity = SwapActivity::default();
+    for line in content.lines() {
+        match line.split_once(' ') {
+            Some(("pswpin", value)) => activity.pages_in = value.trim().parse().ok()?,
+            Some(("pswpout", value)) => activity.pages_out = value.trim().parse().ok()?,
+            _ => {}
+        }
+    }
+    Some(activity)
+}
+
+pub fn page_size() -> u64 {
+    // SAFETY: sysconf has no preconditions
+    match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+        size if size > 0 => size as u64,
+        _ => 4096,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn parses_swap_activity() {
+        let activity =
+            parse_swap_activity("nr_free_pages 12345\npswpin 42\npswpout 1337\npgfault 9\n")
+                .unwrap();
+        assert_eq!((activity.pages_in, activity.pages_out), (42, 1337));
+    }
+
+    #[test]
+    fn rejects_malformed_counters() {
+        assert!(parse_swap_activity("pswpin many\n").is_none());
+        let activity = parse_swap_activity("nr_free_pages 1\n").unwrap();
+        assert_eq!((activity.pages_in, activity.pages_out), (0, 0));
+    }
+
+    #[test]
+    fn reads_swap_activity() {
+        let proc = Fixture::new();
+        assert!(read_swap_activity(proc.path()).is_none());
+        proc.write("vmstat", "pswpin 1\npswpout 2\n");
+        assert_eq!(read_swap_activity(proc.path()).unwrap().pages_out, 2);
+    }
+}
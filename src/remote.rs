@@ -0,0 +1,616 @@
+pub mod agent;
+pub mod client;
+mod wire;
+
+use crate::container::Container;
+use crate::models::alert::{AlertCondition, AlertDefinition};
+use crate::procfs::{
+    loadavg::LoadAverage,
+    meminfo::MemInfo,
+    pressure::{Pressure, Resource},
+    stat::CpuBreakdown,
+};
+use crate::sysfs::{
+    cgroup::CgroupStat, cpufreq::CpuFrequency, hwmon::Temperature, power_supply::PowerSupply,
+};
+use crate::system_monitor::{self, DiskInfo, NetworkInfo, ProcessInfo};
+use crate::systemd::Unit;
+use crate::utmp::Session;
+use rustls::{ClientConnection, ServerConnection, StreamOwned};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::time::Duration;
+use strum::IntoEnumIterator;
+use wire::{wire_struct, Reader, Wire};
+
+// Version of the wire protocol, bumped whenever the encoding of a message
+// changes. Agents only serve clients speaking the same version.
+pub const VERSION: u16 = 1;
+
+// Start of every Hello, to tell a ptop client from anything else connecting
+const MAGIC: &[u8; 4] = b"PTOP";
+
+// Nothing but a Hello is accepted before the token is checked, and nothing
+// legitimate comes close to these sizes afterwards
+const MAX_HELLO: usize = 4096;
+const MAX_FRAME: usize = 64 * 1024 * 1024;
+
+// Time allowed for the TLS handshake and the Hello exchange, and for writing a
+// frame to a peer that stopped reading
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// How long a connection waits for incoming frames before checking whether it
+// has anything to send
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// Everything the UI reads from a Monitor on every update, as sampled by the
+// agent. Per-process details that are only read on demand, such as sockets or
+// stacks, are not part of it.
+#[derive(Default)]
+pub struct Snapshot {
+    pub host_name: String,
+    pub os_name: String,
+    pub kernel_version: String,
+    pub uptime: u64,
+    pub cpu_name: String,
+    pub cpu_usage: f32,
+    pub per_cpu_usage: Vec<f32>,
+    pub cpu_breakdown: Option<CpuBreakdown>,
+    pub per_cpu_breakdown: Vec<CpuBreakdown>,
+    pub cpu_frequencies: Vec<CpuFrequency>,
+    pub load_average: Option<LoadAverage>,
+    // Only the resources whose pressure is reported
+    pub pressure: Vec<(Resource, Pressure)>,
+    // (memory, swap)
+    pub total_memory: (u64, u64),
+    pub used_memory: (u64, u64),
+    pub meminfo: Option<MemInfo>,
+    pub swap_rates: (f64, f64),
+    pub power_supplies: Vec<PowerSupply>,
+    pub rapl_power: Vec<(String, f64)>,
+    pub disks: Vec<DiskInfo>,
+    pub networks: Vec<NetworkInfo>,
+    pub temperatures: Vec<Temperature>,
+    pub processes: Vec<ProcessInfo>,
+    pub disk_io_rates: (f64, f64),
+    pub sessions: Vec<Session>,
+    // Cgroup path of every process by PID
+    pub process_cgroups: HashMap<u32, String>,
+    pub cgroups: Vec<CgroupStat>,
+    // None when the agent has no container runtime or systemd
+    pub containers: Option<Vec<Container>>,
+    pub units: Option<Vec<Unit>>,
+}
+
+impl Snapshot {
+    pub fn collect(monitor: &system_monitor::Monitor) -> Snapshot {
+        let processes = monitor.get_processes();
+        let process_cgroups = processes
+            .iter()
+            .filter_map(|p| Some((p.pid, monitor.get_process_cgroup(p.pid)?)))
+            .collect();
+        Snapshot {
+            host_name: monitor.get_host_name(),
+            os_name: monitor.get_os_name(),
+            kernel_version: monitor.get_kernel_version(),
+            uptime: monitor.get_uptime(),
+            cpu_name: monitor.get_cpu_name(),
+            cpu_usage: monitor.get_global_cpu_usage(),
+            per_cpu_usage: monitor.get_per_cpu_usage(),
+            cpu_breakdown: monitor.get_cpu_breakdown(),
+            per_cpu_breakdown: monitor.get_per_cpu_breakdown(),
+            cpu_frequencies: monitor.get_cpu_frequencies(),
+            load_average: monitor.get_load_average(),
+            pressure: Resource::iter()
+                .filter_map(|resource| Some((resource, monitor.get_pressure(resource)?)))
+                .collect(),
+            total_memory: monitor.get_total_memory(),
+            used_memory: monitor.get_used_memory(),
+            meminfo: monitor.get_meminfo(),
+            swap_rates: monitor.get_swap_rates(),
+            power_supplies: monitor.get_power_supplies(),
+            rapl_power: monitor.get_rapl_power(),
+            disks: monitor.get_disks(),
+            networks: monitor.get_networks(),
+            temperatures: monitor.get_temperatures(),
+            processes,
+            disk_io_rates: monitor.get_disk_io_rates(),
+            sessions: monitor.get_sessions(),
+            process_cgroups,
+            cgroups: monitor.get_cgroups(),
+            containers: monitor
+                .has_container_runtime()
+                .then(|| monitor.get_containers()),
+            units: monitor.has_systemd().then(|| monitor.get_units()),
+        }
+    }
+}
+
+wire_struct!(Snapshot {
+    host_name,
+    os_name,
+    kernel_version,
+    uptime,
+    cpu_name,
+    cpu_usage,
+    per_cpu_usage,
+    cpu_breakdown,
+    per_cpu_breakdown,
+    cpu_frequencies,
+    load_average,
+    pressure,
+    total_memory,
+    used_memory,
+    meminfo,
+    swap_rates,
+    power_supplies,
+    rapl_power,
+    disks,
+    networks,
+    temperatures,
+    processes,
+    disk_io_rates,
+    sessions,
+    process_cgroups,
+    cgroups,
+    containers,
+    units,
+});
+
+// Messages from the client to the agent. Every request but the Hello gets a
+// Done in return, in the order they were sent.
+pub enum Request {
+    // First message of every connection. Its encoding must never change, so
+    // that agents can tell clients of other versions what they speak.
+    Hello { version: u16, token: String },
+    Terminate(u32),
+    RestartUnit(String),
+    StopUnit(String),
+    AddAlert(AlertDefinition),
+}
+
+impl Wire for Request {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Request::Hello { version, token } => {
+                0u8.encode(out);
+                out.extend_from_slice(MAGIC);
+                version.encode(out);
+                token.encode(out);
+            }
+            Request::Terminate(pid) => {
+                1u8.encode(out);
+                pid.encode(out);
+            }
+            Request::RestartUnit(name) => {
+                2u8.encode(out);
+                name.encode(out);
+            }
+            Request::StopUnit(name) => {
+                3u8.encode(out);
+                name.encode(out);
+            }
+            Request::AddAlert(definition) => {
+                4u8.encode(out);
+                definition.encode(out);
+            }
+        }
+    }
+
+    fn decode(input: &mut Reader) -> Option<Self> {
+        Some(match u8::decode(input)? {
+            0 => {
+                if input.take(MAGIC.len())? != MAGIC {
+                    return None;
+                }
+                Request::Hello {
+                    version: u16::decode(input)?,
+                    token: String::decode(input)?,
+                }
+            }
+            1 => Request::Terminate(u32::decode(input)?),
+            2 => Request::RestartUnit(String::decode(input)?),
+            3 => Request::StopUnit(String::decode(input)?),
+            4 => Request::AddAlert(AlertDefinition::decode(input)?),
+            _ => return None,
+        })
+    }
+}
+
+// Messages from the agent to the client
+pub enum Response {
+    // Reply to a Hello with the right token and version
+    Welcome,
+    // Reply to any other Hello, after which the agent closes the connection
+    Refused(String),
+    Snapshot(Box<Snapshot>),
+    // Outcome of a request
+    Done(Result<(), String>),
+}
+
+impl Wire for Response {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Response::Welcome => 0u8.encode(out),
+            Response::Refused(reason) => {
+                1u8.encode(out);
+                reason.encode(out);
+            }
+            Response::Snapshot(snapshot) => {
+                2u8.encode(out);
+                snapshot.encode(out);
+            }
+            Response::Done(result) => {
+                3u8.encode(out);
+                result.clone().err().encode(out);
+            }
+        }
+    }
+
+    fn decode(input: &mut Reader) -> Option<Self> {
+        Some(match u8::decode(input)? {
+            0 => Response::Welcome,
+            1 => Response::Refused(String::decode(input)?),
+            2 => Response::Snapshot(Box::new(Snapshot::decode(input)?)),
+            3 => Response::Done(match Option::<String>::decode(input)? {
+                Some(error) => Err(error),
+                None => Ok(()),
+            }),
+            _ => return None,
+        })
+    }
+}
+
+wire_struct!(AlertDefinition {
+    pid,
+    name,
+    condition,
+});
+
+impl Wire for AlertCondition {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let (tag, threshold) = match self {
+            AlertCondition::CpuUsage(threshold) => (0u8, *threshold),
+            AlertCondition::MemoryUsage(threshold) => (1, *threshold),
+            AlertCondition::WriteRate(threshold) => (2, *threshold),
+            AlertCondition::Exit() => (3, 0.0),
+            AlertCondition::DiskUsage(threshold) => (4, *threshold),
+            AlertCondition::Temperature(threshold) => (5, *threshold),
+            AlertCondition::Pressure(threshold) => (6, *threshold),
+            AlertCondition::BatteryLow(threshold) => (7, *threshold),
+        };
+        tag.encode(out);
+        threshold.encode(out);
+    }
+
+    fn decode(input: &mut Reader) -> Option<Self> {
+        let (tag, threshold) = <(u8, f32)>::decode(input)?;
+        Some(match tag {
+            0 => AlertCondition::CpuUsage(threshold),
+            1 => AlertCondition::MemoryUsage(threshold),
+            2 => AlertCondition::WriteRate(threshold),
+            3 => AlertCondition::Exit(),
+            4 => AlertCondition::DiskUsage(threshold),
+            5 => AlertCondition::Temperature(threshold),
+            6 => AlertCondition::Pressure(threshold),
+            7 => AlertCondition::BatteryLow(threshold),
+            _ => return None,
+        })
+    }
+}
+
+// Reads the shared secret of the agent and its clients, the first line of a
+// file that should only be readable by them
+pub fn read_token(path: &Path) -> io::Result<String> {
+    let content = fs::read_to_string(path)?;
+    let token = content.lines().next().unwrap_or_default().trim();
+    if token.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the token file is empty",
+        ));
+    }
+    Ok(token.to_string())
+}
+
+// Connection to the other end, over TLS when configured
+enum Stream {
+    Plain(TcpStream),
+    Client(Box<StreamOwned<ClientConnection, TcpStream>>),
+    Server(Box<StreamOwned<ServerConnection, TcpStream>>),
+}
+
+impl Stream {
+    fn tcp(&self) -> &TcpStream {
+        match self {
+            Stream::Plain(stream) => stream,
+            Stream::Client(stream) => stream.get_ref(),
+            Stream::Server(stream) => stream.get_ref(),
+        }
+    }
+
+    // Blocks for the whole handshake until it completes, then only polls for
+    // incoming frames
+    fn set_polling(&self, polling: bool) -> io::Result<()> {
+        let timeout = if polling {
+            POLL_INTERVAL
+        } else {
+            HANDSHAKE_TIMEOUT
+        };
+        self.tcp().set_read_timeout(Some(timeout))?;
+        self.tcp().set_write_timeout(Some(HANDSHAKE_TIMEOUT))
+    }
+
+    fn send(&mut self, message: &impl Wire) -> io::Result<()> {
+        self.send_frame(&wire::encode(message))
+    }
+
+    // Writes a frame, its length followed by the encoded message
+    fn send_frame(&mut self, payload: &[u8]) -> io::Result<()> {
+        let mut frame = Vec::with_capacity(4 + payload.len());
+        frame.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        frame.extend_from_slice(payload);
+        self.write_all(&frame)?;
+        self.flush()
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Plain(stream) => stream.read(buf),
+            Stream::Client(stream) => stream.read(buf),
+            Stream::Server(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Plain(stream) => stream.write(buf),
+            Stream::Client(stream) => stream.write(buf),
+            Stream::Server(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Plain(stream) => stream.flush(),
+            Stream::Client(stream) => stream.flush(),
+            Stream::Server(stream) => stream.flush(),
+        }
+    }
+}
+
+// Splits the bytes read from a stream into frames, keeping a frame that is
+// only partly received for the next read
+struct FrameReader {
+    buffer: Vec<u8>,
+}
+
+impl FrameReader {
+    fn new() -> Self {
+        FrameReader { buffer: Vec::new() }
+    }
+
+    // Returns the next complete frame, or None when the read timed out first
+    fn read(&mut self, stream: &mut Stream, limit: usize) -> io::Result<Option<Vec<u8>>> {
+        loop {
+            if let Some(header) = self.buffer.first_chunk::<4>() {
+                let length = u32::from_be_bytes(*header) as usize;
+                if length > limit {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("frame of {} bytes is too large", length),
+                    ));
+                }
+                if self.buffer.len() >= 4 + length {
+                    let frame = self.buffer[4..4 + length].to_vec();
+                    self.buffer.drain(..4 + length);
+                    return Ok(Some(frame));
+                }
+            }
+            let mut chunk = [0; 16 * 1024];
+            match stream.read(&mut chunk) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "connection closed",
+                    ))
+                }
+                Ok(count) => self.buffer.extend_from_slice(&chunk[..count]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e)
+                    if e.kind() == io::ErrorKind::WouldBlock
+                        || e.kind() == io::ErrorKind::TimedOut =>
+                {
+                    return Ok(None)
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    // Returns the next frame decoded, failing when none arrives in time
+    fn receive<T: Wire>(&mut self, stream: &mut Stream, limit: usize) -> io::Result<T> {
+        let frame = self
+            .read(stream, limit)?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "no reply in time"))?;
+        decode(&frame)
+    }
+}
+
+fn decode<T: Wire>(frame: &[u8]) -> io::Result<T> {
+    wire::decode(frame)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed message"))
+}
+
+fn invalid_pem(path: &Path, e: impl std::fmt::Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{}: {}", path.display(), e),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: Wire>(value: &T) -> T {
+        let bytes = wire::encode(value);
+        let decoded: T = wire::decode(&bytes).unwrap();
+        assert_eq!(wire::encode(&decoded), bytes);
+        decoded
+    }
+
+    // A snapshot touching every kind of field, with a single cgroup so that
+    // its encoding does not depend on the order of a map
+    fn snapshot() -> Snapshot {
+        Snapshot {
+            host_name: "host".to_string(),
+            uptime: 3600,
+            cpu_usage: 12.5,
+            per_cpu_usage: vec![10.0, 15.0],
+            load_average: Some(LoadAverage {
+                one: 0.5,
+                five: 0.25,
+                fifteen: 0.125,
+                running: 2,
+                total: 300,
+            }),
+            total_memory: (8 << 30, 2 << 30),
+            rapl_power: vec![("package-0".to_string(), 12.5)],
+            process_cgroups: HashMap::from([(1, "/init.scope".to_string())]),
+            containers: Some(vec![Container {
+                id: "4f0c9e1a2b3c".to_string(),
+                name: "web".to_string(),
+                image: "nginx".to_string(),
+                state: "running".to_string(),
+                status: "Up 2 hours".to_string(),
+            }]),
+            units: None,
+            ..Snapshot::default()
+        }
+    }
+
+    #[test]
+    fn round_trips_snapshots() {
+        let decoded = round_trip(&snapshot());
+        assert_eq!(decoded.host_name, "host");
+        assert_eq!(decoded.per_cpu_usage, vec![10.0, 15.0]);
+        assert_eq!(decoded.load_average.map(|l| l.total), Some(300));
+        assert_eq!(decoded.process_cgroups[&1], "/init.scope");
+        assert_eq!(decoded.containers.unwrap()[0].name, "web");
+        assert!(decoded.units.is_none());
+
+        let response = Response::Snapshot(Box::default());
+        assert!(matches!(round_trip(&response), Response::Snapshot(_)));
+
+        // A snapshot of this host, whose cgroup map is compared apart
+        let mut snapshot = Snapshot::collect(&system_monitor::Monitor::new());
+        let decoded: Snapshot = wire::decode(&wire::encode(&snapshot)).unwrap();
+        assert_eq!(decoded.process_cgroups, snapshot.process_cgroups);
+        assert_eq!(decoded.processes.len(), snapshot.processes.len());
+        snapshot.process_cgroups.clear();
+        round_trip(&snapshot);
+    }
+
+    #[test]
+    fn round_trips_requests_and_responses() {
+        let request = Request::Hello {
+            version: VERSION,
+            token: "secret".to_string(),
+        };
+        assert!(matches!(
+            round_trip(&request),
+            Request::Hello { version: VERSION, token } if token == "secret"
+        ));
+        assert!(matches!(
+            round_trip(&Request::Terminate(42)),
+            Request::Terminate(42)
+        ));
+        assert!(matches!(
+            round_trip(&Request::RestartUnit("cron.service".to_string())),
+            Request::RestartUnit(name) if name == "cron.service"
+        ));
+        assert!(matches!(
+            round_trip(&Request::StopUnit("cron.service".to_string())),
+            Request::StopUnit(name) if name == "cron.service"
+        ));
+        let alert = Request::AddAlert(AlertDefinition {
+            pid: None,
+            name: "/".to_string(),
+            condition: AlertCondition::DiskUsage(90.0),
+        });
+        assert!(matches!(
+            round_trip(&alert),
+            Request::AddAlert(AlertDefinition {
+                pid: None,
+                condition: AlertCondition::DiskUsage(90.0),
+                ..
+            })
+        ));
+
+        assert!(matches!(round_trip(&Response::Welcome), Response::Welcome));
+        assert!(matches!(
+            round_trip(&Response::Refused("wrong token".to_string())),
+            Response::Refused(reason) if reason == "wrong token"
+        ));
+        assert!(matches!(
+            round_trip(&Response::Done(Ok(()))),
+            Response::Done(Ok(()))
+        ));
+        assert!(matches!(
+            round_trip(&Response::Done(Err("failed".to_string()))),
+            Response::Done(Err(e)) if e == "failed"
+        ));
+    }
+
+    #[test]
+    fn rejects_truncated_messages() {
+        let bytes = wire::encode(&Response::Snapshot(Box::new(snapshot())));
+        for length in 0..bytes.len() {
+            assert!(
+                wire::decode::<Response>(&bytes[..length]).is_none(),
+                "{}",
+                length
+            );
+        }
+        // Nor may anything follow a message
+        let mut bytes = wire::encode(&Request::Terminate(1));
+        bytes.push(0);
+        assert!(wire::decode::<Request>(&bytes).is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_messages() {
+        // A count or length far beyond the bytes that follow
+        let mut bytes = vec![2];
+        bytes.extend_from_slice(&u32::MAX.to_be_bytes());
+        assert!(wire::decode::<Response>(&bytes).is_none());
+        let mut bytes = wire::encode(&Response::Snapshot(Box::default()));
+        // The host name is first, then the OS name
+        bytes[5..9].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(wire::decode::<Response>(&bytes).is_none());
+        let mut bytes = 0x0fff_ffffu32.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&[0; 64]);
+        assert!(wire::decode::<Vec<u64>>(&bytes).is_none());
+
+        // Unknown tags, and a Hello without the magic
+        assert!(wire::decode::<Request>(&[9]).is_none());
+        assert!(wire::decode::<Response>(&[4]).is_none());
+        let mut hello = wire::encode(&Request::Hello {
+            version: VERSION,
+            token: "secret".to_string(),
+        });
+        hello[1] = b'X';
+        assert!(wire::decode::<Request>(&hello).is_none());
+        // Options and booleans are 0 or 1
+        assert!(wire::decode::<Option<u8>>(&[2, 0]).is_none());
+        assert!(wire::decode::<bool>(&[2]).is_none());
+    }
+}
@@ -0,0 +1,317 @@
+use super::{
+    decode, invalid_pem, FrameReader, Request, Response, Snapshot, Stream, HANDSHAKE_TIMEOUT,
+    MAX_FRAME, MAX_HELLO, VERSION,
+};
+use crate::models::alert::AlertTable;
+use crate::system_monitor;
+use rustls::pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+use rustls::{ServerConfig, ServerConnection, StreamOwned};
+use std::io;
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// Clients beyond this many are turned away, each one costs a thread
+const MAX_CONNECTIONS: usize = 32;
+
+// Connections that have not shown the token yet are counted apart, so that
+// strangers cannot take the places of the clients
+const MAX_HANDSHAKES: usize = 8;
+
+// Latest snapshot frame, numbered so that every connection sends each one once
+type LatestFrame = Arc<Mutex<(u64, Arc<Vec<u8>>)>>;
+
+// A request received by a connection, and where to send its outcome
+type Pending = (Request, Sender<Result<(), String>>);
+
+// Loads the certificate chain and private key the agent serves TLS with
+pub fn tls_config(cert: &Path, key: &Path) -> io::Result<Arc<ServerConfig>> {
+    let chain = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| invalid_pem(cert, e))?;
+    let key_der = PrivateKeyDer::from_pem_file(key).map_err(|e| invalid_pem(key, e))?;
+    let config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(chain, key_der)
+        .map_err(|e| invalid_pem(cert, e))?;
+    Ok(Arc::new(config))
+}
+
+// Streams a snapshot of this host to every client on each publish, and queues
+// the requests of the clients for the main loop
+pub struct Agent {
+    address: SocketAddr,
+    latest: LatestFrame,
+    requests: Receiver<Pending>,
+}
+
+// Accepts clients on the given address, e.g. "0.0.0.0:9185", from background
+// threads. Clients must present the token, and connect over TLS when a
+// configuration is given.
+pub fn serve(address: &str, token: String, tls: Option<Arc<ServerConfig>>) -> io::Result<Agent> {
+    let listener = TcpListener::bind(address)?;
+    let address = listener.local_addr()?;
+    let latest: LatestFrame = Arc::new(Mutex::new((0, Arc::new(Vec::new()))));
+    let (sender, requests) = mpsc::channel();
+    let shared = latest.clone();
+    thread::spawn(move || {
+        let handshakes = Arc::new(AtomicUsize::new(0));
+        let connections = Arc::new(AtomicUsize::new(0));
+        for stream in listener.incoming().flatten() {
+            let Ok(peer) = stream.peer_addr() else {
+                continue;
+            };
+            let Some(handshake) = Slot::take(&handshakes, MAX_HANDSHAKES) else {
+                eprintln!("{} refused: too many pending connections", peer);
+                continue;
+            };
+            let connection = Connection {
+                peer,
+                token: token.clone(),
+                latest: shared.clone(),
+                requests: sender.clone(),
+                connections: connections.clone(),
+            };
+            let tls = tls.clone();
+            thread::spawn(move || {
+                match connection.run(stream, tls, handshake) {
+                    // Clients that exit do not bother closing TLS properly
+                    Ok(()) => eprintln!("{} disconnected", peer),
+                    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                        eprintln!("{} disconnected", peer)
+                    }
+                    Err(e) => eprintln!("{} disconnected: {}", peer, e),
+                }
+            });
+        }
+    });
+    Ok(Agent {
+        address,
+        latest,
+        requests,
+    })
+}
+
+impl Agent {
+    // The address the agent listens on, with the port picked when given 0
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    // Sends a new snapshot of the host to the clients
+    pub fn publish(&self, monitor: &system_monitor::Monitor) {
+        let frame = super::wire::encode(&Response::Snapshot(Box::new(Snapshot::collect(monitor))));
+        let mut latest = self.latest.lock().unwrap();
+        *latest = (latest.0 + 1, Arc::new(frame));
+    }
+
+    // Carries out the requests received since the last call
+    pub fn handle_requests(&self, monitor: &mut system_monitor::Monitor, alerts: &mut AlertTable) {
+        while let Ok((request, reply)) = self.requests.try_recv() {
+            let result = match request {
+                Request::Terminate(pid) => {
+                    monitor.terminate_process(pid);
+                    Ok(())
+                }
+                Request::RestartUnit(name) => {
+                    monitor.restart_unit(&name).map_err(|e| e.to_string())
+                }
+                Request::StopUnit(name) => monitor.stop_unit(&name).map_err(|e| e.to_string()),
+                Request::AddAlert(definition) => {
+                    alerts.add_alert(definition);
+                    Ok(())
+                }
+                // Connections only pass on the Hello they start with
+                Request::Hello { .. } => Err("already connected".to_string()),
+            };
+            // The client may have disconnected in the meantime
+            let _ = reply.send(result);
+        }
+    }
+}
+
+// One place in a limited count, given back when dropped
+struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    fn take(count: &Arc<AtomicUsize>, max: usize) -> Option<Slot> {
+        if count.fetch_add(1, Ordering::SeqCst) >= max {
+            count.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(Slot(count.clone()))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+struct Connection {
+    peer: SocketAddr,
+    token: String,
+    latest: LatestFrame,
+    requests: Sender<Pending>,
+    connections: Arc<AtomicUsize>,
+}
+
+impl Connection {
+    fn run(
+        self,
+        tcp: TcpStream,
+        tls: Option<Arc<ServerConfig>>,
+        handshake: Slot,
+    ) -> io::Result<()> {
+        // The timeouts only bound each read, so a peer trickling in its Hello
+        // byte by byte is cut off once the whole handshake took too long
+        let authenticated = Arc::new(AtomicBool::new(false));
+        let deadline = (tcp.try_clone()?, authenticated.clone());
+        thread::spawn(move || {
+            thread::sleep(HANDSHAKE_TIMEOUT);
+            let (tcp, authenticated) = deadline;
+            if !authenticated.load(Ordering::SeqCst) {
+                let _ = tcp.shutdown(Shutdown::Both);
+            }
+        });
+
+        let mut stream = match tls {
+            Some(config) => {
+                let connection = ServerConnection::new(config).map_err(io::Error::other)?;
+                Stream::Server(Box::new(StreamOwned::new(connection, tcp)))
+            }
+            None => Stream::Plain(tcp),
+        };
+        stream.set_polling(false)?;
+        let mut reader = FrameReader::new();
+        let refusal = match reader.receive(&mut stream, MAX_HELLO)? {
+            Request::Hello { version, .. } if version != VERSION => Some(format!(
+                "the agent speaks protocol version {}, not {}",
+                VERSION, version
+            )),
+            Request::Hello { token, .. } if !same_token(&token, &self.token) => {
+                Some("wrong token".to_string())
+            }
+            Request::Hello { .. } => None,
+            _ => Some("expected a Hello".to_string()),
+        };
+        let slot = match refusal {
+            Some(reason) => Err(reason),
+            None => Slot::take(&self.connections, MAX_CONNECTIONS)
+                .ok_or_else(|| "too many clients".to_string()),
+        };
+        let _slot = match slot {
+            Ok(slot) => slot,
+            Err(reason) => {
+                eprintln!("{} refused: {}", self.peer, reason);
+                return stream.send(&Response::Refused(reason));
+            }
+        };
+        stream.send(&Response::Welcome)?;
+        authenticated.store(true, Ordering::SeqCst);
+        drop(handshake);
+        stream.set_polling(true)?;
+        eprintln!("{} connected", self.peer);
+
+        let (reply, replies) = mpsc::channel();
+        let mut sent = 0;
+        loop {
+            while let Some(frame) = reader.read(&mut stream, MAX_FRAME)? {
+                if self
+                    .requests
+                    .send((decode(&frame)?, reply.clone()))
+                    .is_err()
+                {
+                    // The agent is shutting down
+                    return Ok(());
+                }
+            }
+            while let Ok(result) = replies.try_recv() {
+                stream.send(&Response::Done(result))?;
+            }
+            let (number, frame) = self.latest.lock().unwrap().clone();
+            if number > sent {
+                stream.send_frame(&frame)?;
+                sent = number;
+            }
+        }
+    }
+}
+
+// Compares in constant time, so that the time taken to refuse a token does not
+// tell how much of it was right
+fn same_token(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::wire;
+    use std::io::{Read, Write};
+    use std::time::{Duration, Instant};
+
+    fn hello(address: SocketAddr, token: &str) -> io::Result<Response> {
+        let mut stream = TcpStream::connect(address)?;
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        let payload = wire::encode(&Request::Hello {
+            version: VERSION,
+            token: token.to_string(),
+        });
+        stream.write_all(&(payload.len() as u32).to_be_bytes())?;
+        stream.write_all(&payload)?;
+        let mut length = [0; 4];
+        stream.read_exact(&mut length)?;
+        let mut frame = vec![0; u32::from_be_bytes(length) as usize];
+        stream.read_exact(&mut frame)?;
+        decode(&frame)
+    }
+
+    #[test]
+    fn checks_the_token() {
+        let agent = serve("127.0.0.1:0", "secret".to_string(), None).unwrap();
+        assert!(agent.local_addr().ip().is_loopback());
+        assert!(matches!(
+            hello(agent.local_addr(), "secret"),
+            Ok(Response::Welcome)
+        ));
+        assert!(matches!(
+            hello(agent.local_addr(), "guess"),
+            Ok(Response::Refused(reason)) if reason == "wrong token"
+        ));
+    }
+
+    #[test]
+    fn limits_pending_connections() {
+        let agent = serve("127.0.0.1:0", "secret".to_string(), None).unwrap();
+        let address = agent.local_addr();
+        let idle: Vec<TcpStream> = (0..MAX_HANDSHAKES)
+            .map(|_| TcpStream::connect(address).unwrap())
+            .collect();
+
+        // Strangers that say nothing keep clients out, but only up to the limit
+        let mut refused = TcpStream::connect(address).unwrap();
+        refused
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        assert_eq!(refused.read(&mut [0; 1]).unwrap(), 0);
+        assert!(hello(address, "secret").is_err());
+
+        // Their places are given back once they leave
+        drop(idle);
+        let start = Instant::now();
+        while !matches!(hello(address, "secret"), Ok(Response::Welcome)) {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use super::{
+    invalid_pem, FrameReader, Request, Response, Snapshot, Stream, MAX_FRAME, MAX_HELLO, VERSION,
+};
+use rustls::pki_types::{pem::PemObject, CertificateDer, ServerName};
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::fmt;
+use std::io;
+use std::net::TcpStream;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// The agent publishes every second, a connection that stays silent for much
+// longer than that is dead
+const SILENCE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Time between two attempts to reconnect to the agent
+const RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+// Time to wait for the outcome of a request before giving up on it
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+// A request for the connection thread, and where to send its outcome when
+// the caller waits for it
+type Pending = (Request, Option<Sender<Result<(), String>>>);
+
+// Loads the certificate the agent is verified with, either its own
+// self-signed certificate or the authority that signed it
+pub fn tls_config(ca: &Path) -> io::Result<Arc<ClientConfig>> {
+    let mut roots = RootCertStore::empty();
+    for cert in CertificateDer::pem_file_iter(ca).map_err(|e| invalid_pem(ca, e))? {
+        roots
+            .add(cert.map_err(|e| invalid_pem(ca, e))?)
+            .map_err(|e| invalid_pem(ca, e))?;
+    }
+    let config = ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+pub struct Config {
+    // e.g. "db-1.example.com:9185"
+    pub address: String,
+    pub token: String,
+    pub tls: Option<Arc<ClientConfig>>,
+}
+
+#[derive(Clone)]
+pub enum Status {
+    Connected,
+    // Retrying after losing the connection
+    Disconnected(String),
+    // The agent refused the client, e.g. for a wrong token, which retrying
+    // will not fix
+    Refused(String),
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Status::Connected => write!(f, "connected"),
+            Status::Disconnected(e) => write!(f, "reconnecting, {}", e),
+            Status::Refused(reason) => write!(f, "refused, {}", reason),
+        }
+    }
+}
+
+// State shared with the connection thread
+struct Shared {
+    // Latest snapshot not yet taken by update
+    snapshot: Option<Snapshot>,
+    status: Status,
+}
+
+// A connection the agent welcomed the client on
+struct Connection {
+    stream: Stream,
+    // Holds the start of what the agent sent next
+    reader: FrameReader,
+    // First snapshot sent by the agent, until handed over to the UI
+    snapshot: Option<Snapshot>,
+}
+
+// Connection to an agent, kept up by a background thread, and the latest
+// snapshot it sent
+pub struct Client {
+    address: String,
+    shared: Arc<Mutex<Shared>>,
+    requests: Sender<Pending>,
+    snapshot: Snapshot,
+}
+
+impl Client {
+    // Connects to the agent, failing when the first attempt does so that
+    // mistakes in the address or the token show right away. The connection is
+    // retried in the background after that.
+    pub fn connect(config: Config) -> io::Result<Client> {
+        let mut connection = open(&config)?;
+        let snapshot = connection.snapshot.take().unwrap_or_default();
+        let shared = Arc::new(Mutex::new(Shared {
+            snapshot: None,
+            status: Status::Connected,
+        }));
+        let (requests, receiver) = mpsc::channel();
+        let address = config.address.clone();
+        let state = shared.clone();
+        thread::spawn(move || keep_connected(config, connection, state, receiver));
+        Ok(Client {
+            address,
+            shared,
+            requests,
+            snapshot,
+        })
+    }
+
+    // Takes the latest snapshot received, keeping the previous one until the
+    // agent sends another
+    pub fn update(&mut self) {
+        if let Some(snapshot) = self.shared.lock().unwrap().snapshot.take() {
+            self.snapshot = snapshot;
+        }
+    }
+
+    pub fn snapshot(&self) -> &Snapshot {
+        &self.snapshot
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn status(&self) -> Status {
+        self.shared.lock().unwrap().status.clone()
+    }
+
+    // Sends a request without waiting for its outcome
+    pub fn send(&self, request: Request) {
+        // The connection thread only stops with the process
+        let _ = self.requests.send((request, None));
+    }
+
+    // Sends a request and waits for the agent to carry it out
+    pub fn request(&self, request: Request) -> io::Result<()> {
+        let (reply, outcome) = mpsc::channel();
+        let _ = self.requests.send((request, Some(reply)));
+        match outcome.recv_timeout(REQUEST_TIMEOUT) {
+            Ok(result) => result.map_err(io::Error::other),
+            Err(RecvTimeoutError::Timeout) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "the agent did not reply in time",
+            )),
+            Err(RecvTimeoutError::Disconnected) => Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "not connected to the agent",
+            )),
+        }
+    }
+}
+
+// Connects and says Hello, returning once the agent welcomed the client and
+// sent a first snapshot
+fn open(config: &Config) -> io::Result<Connection> {
+    let tcp = TcpStream::connect(&config.address)?;
+    let mut stream = match &config.tls {
+        Some(tls) => {
+            let name = ServerName::try_from(host(&config.address).to_string())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let connection = ClientConnection::new(tls.clone(), name).map_err(io::Error::other)?;
+            Stream::Client(Box::new(StreamOwned::new(connection, tcp)))
+        }
+        None => Stream::Plain(tcp),
+    };
+    // Each end of a TLS mismatch only sees what looks like garbage to it
+    let hint = |e: io::Error| match (e.kind(), &config.tls) {
+        (io::ErrorKind::InvalidData, None) => {
+            io::Error::new(e.kind(), format!("{}, the agent may be serving TLS", e))
+        }
+        (io::ErrorKind::UnexpectedEof, Some(_)) => {
+            io::Error::new(e.kind(), format!("{}, the agent may not be serving TLS", e))
+        }
+        _ => e,
+    };
+    stream.set_polling(false)?;
+    stream
+        .send(&Request::Hello {
+            version: VERSION,
+            token: config.token.clone(),
+        })
+        .map_err(hint)?;
+    let mut reader = FrameReader::new();
+    let welcome = reader.receive(&mut stream, MAX_HELLO).map_err(hint)?;
+    match welcome {
+        Response::Welcome => {}
+        Response::Refused(reason) => {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, reason))
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "expected a Welcome",
+            ))
+        }
+    }
+    // The agent sends its latest snapshot right away, or within a second when
+    // it just started
+    match reader.receive(&mut stream, MAX_FRAME)? {
+        Response::Snapshot(snapshot) => {
+            stream.set_polling(true)?;
+            Ok(Connection {
+                stream,
+                reader,
+                snapshot: Some(*snapshot),
+            })
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "expected a Snapshot",
+        )),
+    }
+}
+
+// The host part of an address, which the certificate of the agent must name
+fn host(address: &str) -> &str {
+    let host = address.rsplit_once(':').map_or(address, |(host, _)| host);
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+fn keep_connected(
+    config: Config,
+    connection: Connection,
+    shared: Arc<Mutex<Shared>>,
+    requests: Receiver<Pending>,
+) {
+    let mut connection = Some(connection);
+    loop {
+        let status = match connection.take().map_or_else(|| open(&config), Ok) {
+            Ok(mut connection) => {
+                {
+                    let mut state = shared.lock().unwrap();
+                    state.status = Status::Connected;
+                    // The snapshot of the first connection went to connect
+                    if let Some(snapshot) = connection.snapshot.take() {
+                        state.snapshot = Some(snapshot);
+                    }
+                }
+                let Err(e) = receive(connection, &shared, &requests);
+                Status::Disconnected(e.to_string())
+            }
+            Err(e) if e.kind() == io::ErrorKind::PermissionDenied => Status::Refused(e.to_string()),
+            Err(e) => Status::Disconnected(e.to_string()),
+        };
+        let refused = matches!(status, Status::Refused(_));
+        shared.lock().unwrap().status = status;
+        // Requests made while disconnected are dropped rather than sent on
+        // reconnection, as a PID may belong to another process by then
+        while requests.try_recv().is_ok() {}
+        if refused {
+            return;
+        }
+        thread::sleep(RETRY_INTERVAL);
+    }
+}
+
+// Passes on snapshots and requests, only returning once the connection fails
+fn receive(
+    connection: Connection,
+    shared: &Mutex<Shared>,
+    requests: &Receiver<Pending>,
+) -> io::Result<Infallible> {
+    let Connection {
+        mut stream,
+        mut reader,
+        ..
+    } = connection;
+    // Replies expected from the agent, in order
+    let mut pending: VecDeque<Option<Sender<Result<(), String>>>> = VecDeque::new();
+    let mut last_frame = Instant::now();
+    loop {
+        while let Some(frame) = reader.read(&mut stream, MAX_FRAME)? {
+            last_frame = Instant::now();
+            match super::decode(&frame)? {
+                Response::Snapshot(snapshot) => shared.lock().unwrap().snapshot = Some(*snapshot),
+                Response::Done(result) => {
+                    if let Some(Some(reply)) = pending.pop_front() {
+                        // The caller may have given up waiting
+                        let _ = reply.send(result);
+                    }
+                }
+                Response::Welcome | Response::Refused(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "unexpected message",
+                    ))
+                }
+            }
+        }
+        if last_frame.elapsed() > SILENCE_TIMEOUT {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "the agent stopped sending",
+            ));
+        }
+        while let Ok((request, reply)) = requests.try_recv() {
+            stream.send(&request)?;
+            pending.push_back(reply);
+        }
+    }
+}
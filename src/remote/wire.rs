@@ -0,0 +1,422 @@
+// Binary encoding of the messages exchanged with an agent. Integers and
+// floats are big-endian, lengths and counts are u32, and the fields of a
+// struct follow each other in declaration order without any tag, so that
+// changing any of them requires bumping remote::VERSION.
+
+use crate::container::Container;
+use crate::procfs::{
+    loadavg::LoadAverage,
+    meminfo::MemInfo,
+    pressure::{Pressure, Resource, Stall},
+    stat::CpuBreakdown,
+};
+use crate::sysfs::{
+    cgroup::CgroupStat, cpufreq::CpuFrequency, hwmon::Temperature, power_supply::PowerSupply,
+};
+use crate::system_monitor::{DiskInfo, NetworkInfo, ProcessInfo};
+use crate::systemd::Unit;
+use crate::utmp::Session;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use sysinfo::ProcessStatus;
+
+pub trait Wire: Sized {
+    fn encode(&self, out: &mut Vec<u8>);
+    fn decode(input: &mut Reader) -> Option<Self>;
+}
+
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes }
+    }
+
+    pub fn take(&mut self, count: usize) -> Option<&'a [u8]> {
+        if count > self.bytes.len() {
+            return None;
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Some(taken)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+}
+
+// Encodes a value into a new buffer
+pub fn encode<T: Wire>(value: &T) -> Vec<u8> {
+    let mut out = Vec::new();
+    value.encode(&mut out);
+    out
+}
+
+// Decodes a value that must span the whole buffer
+pub fn decode<T: Wire>(bytes: &[u8]) -> Option<T> {
+    let mut input = Reader::new(bytes);
+    let value = T::decode(&mut input)?;
+    input.is_empty().then_some(value)
+}
+
+macro_rules! wire_number {
+    ($($type:ty),*) => {
+        $(
+            impl Wire for $type {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_be_bytes());
+                }
+
+                fn decode(input: &mut Reader) -> Option<Self> {
+                    let bytes = input.take(std::mem::size_of::<$type>())?;
+                    Some(<$type>::from_be_bytes(bytes.try_into().ok()?))
+                }
+            }
+        )*
+    };
+}
+
+wire_number!(u8, u16, u32, u64, f32, f64);
+
+macro_rules! wire_struct {
+    ($type:ty { $($field:ident),* $(,)? }) => {
+        impl Wire for $type {
+            fn encode(&self, out: &mut Vec<u8>) {
+                $(self.$field.encode(out);)*
+            }
+
+            fn decode(input: &mut Reader) -> Option<Self> {
+                Some(Self {
+                    $($field: Wire::decode(input)?,)*
+                })
+            }
+        }
+    };
+}
+
+pub(crate) use wire_struct;
+
+impl Wire for usize {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u64).encode(out);
+    }
+
+    fn decode(input: &mut Reader) -> Option<Self> {
+        u64::decode(input)?.try_into().ok()
+    }
+}
+
+impl Wire for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (*self as u8).encode(out);
+    }
+
+    fn decode(input: &mut Reader) -> Option<Self> {
+        match u8::decode(input)? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl Wire for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len() as u32).encode(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(input: &mut Reader) -> Option<Self> {
+        let length = u32::decode(input)? as usize;
+        String::from_utf8(input.take(length)?.to_vec()).ok()
+    }
+}
+
+impl<T: Wire> Wire for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len() as u32).encode(out);
+        for item in self {
+            item.encode(out);
+        }
+    }
+
+    fn decode(input: &mut Reader) -> Option<Self> {
+        let count = u32::decode(input)? as usize;
+        // Every item takes at least a byte, a larger count can only be garbage
+        if count > input.bytes.len() {
+            return None;
+        }
+        (0..count).map(|_| T::decode(input)).collect()
+    }
+}
+
+impl<T: Wire> Wire for Option<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Some(value) => {
+                1u8.encode(out);
+                value.encode(out);
+            }
+            None => 0u8.encode(out),
+        }
+    }
+
+    fn decode(input: &mut Reader) -> Option<Self> {
+        match u8::decode(input)? {
+            0 => Some(None),
+            1 => Some(Some(T::decode(input)?)),
+            _ => None,
+        }
+    }
+}
+
+impl<A: Wire, B: Wire> Wire for (A, B) {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.0.encode(out);
+        self.1.encode(out);
+    }
+
+    fn decode(input: &mut Reader) -> Option<Self> {
+        Some((A::decode(input)?, B::decode(input)?))
+    }
+}
+
+impl Wire for HashMap<u32, String> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len() as u32).encode(out);
+        for (key, value) in self {
+            key.encode(out);
+            value.encode(out);
+        }
+    }
+
+    fn decode(input: &mut Reader) -> Option<Self> {
+        Some(Vec::<(u32, String)>::decode(input)?.into_iter().collect())
+    }
+}
+
+// Whole seconds since the Unix epoch
+impl Wire for SystemTime {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let seconds = self
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        seconds.encode(out);
+    }
+
+    fn decode(input: &mut Reader) -> Option<Self> {
+        UNIX_EPOCH.checked_add(Duration::from_secs(u64::decode(input)?))
+    }
+}
+
+impl Wire for Resource {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.to_string().encode(out);
+    }
+
+    fn decode(input: &mut Reader) -> Option<Self> {
+        String::decode(input)?.parse().ok()
+    }
+}
+
+impl Wire for ProcessStatus {
+    fn encode(&self, out: &mut Vec<u8>) {
+        let (tag, code) = match self {
+            ProcessStatus::Idle => (0u8, 0),
+            ProcessStatus::Run => (1, 0),
+            ProcessStatus::Sleep => (2, 0),
+            ProcessStatus::Stop => (3, 0),
+            ProcessStatus::Zombie => (4, 0),
+            ProcessStatus::Tracing => (5, 0),
+            ProcessStatus::Dead => (6, 0),
+            ProcessStatus::Wakekill => (7, 0),
+            ProcessStatus::Waking => (8, 0),
+            ProcessStatus::Parked => (9, 0),
+            ProcessStatus::LockBlocked => (10, 0),
+            ProcessStatus::UninterruptibleDiskSleep => (11, 0),
+            ProcessStatus::Unknown(code) => (12, *code),
+        };
+        tag.encode(out);
+        code.encode(out);
+    }
+
+    fn decode(input: &mut Reader) -> Option<Self> {
+        let (tag, code) = <(u8, u32)>::decode(input)?;
+        Some(match tag {
+            0 => ProcessStatus::Idle,
+            1 => ProcessStatus::Run,
+            2 => ProcessStatus::Sleep,
+            3 => ProcessStatus::Stop,
+            4 => ProcessStatus::Zombie,
+            5 => ProcessStatus::Tracing,
+            6 => ProcessStatus::Dead,
+            7 => ProcessStatus::Wakekill,
+            8 => ProcessStatus::Waking,
+            9 => ProcessStatus::Parked,
+            10 => ProcessStatus::LockBlocked,
+            11 => ProcessStatus::UninterruptibleDiskSleep,
+            12 => ProcessStatus::Unknown(code),
+            _ => return None,
+        })
+    }
+}
+
+wire_struct!(CpuBreakdown {
+    user,
+    nice,
+    system,
+    idle,
+    iowait,
+    irq,
+    softirq,
+    steal,
+    guest,
+});
+
+wire_struct!(CpuFrequency {
+    core,
+    current,
+    min,
+    max,
+    governor,
+});
+
+wire_struct!(LoadAverage {
+    one,
+    five,
+    fifteen,
+    running,
+    total,
+});
+
+wire_struct!(Stall {
+    avg10,
+    avg60,
+    avg300,
+});
+
+wire_struct!(Pressure { some, full });
+
+wire_struct!(MemInfo {
+    total,
+    free,
+    available,
+    buffers,
+    cached,
+    shared,
+    slab_reclaimable,
+    slab_unreclaimable,
+    dirty,
+    writeback,
+    huge_pages_total,
+    huge_pages_free,
+    committed,
+    commit_limit,
+    swap_total,
+    swap_free,
+});
+
+wire_struct!(PowerSupply {
+    name,
+    kind,
+    online,
+    status,
+    capacity,
+    power,
+    time_to_empty,
+    time_to_full,
+});
+
+wire_struct!(DiskInfo {
+    name,
+    file_system,
+    mount_point,
+    total_space,
+    available_space,
+    total_inodes,
+    free_inodes,
+});
+
+wire_struct!(NetworkInfo {
+    name,
+    receive_rate,
+    transmit_rate,
+    total_received,
+    total_transmitted,
+    packets_received,
+    packets_transmitted,
+    errors_received,
+    errors_transmitted,
+    addresses,
+});
+
+wire_struct!(Temperature {
+    label,
+    current,
+    max,
+    critical,
+});
+
+wire_struct!(ProcessInfo {
+    pid,
+    parent,
+    thread,
+    name,
+    uid,
+    user,
+    cpu_usage,
+    memory,
+    run_time,
+    status,
+    disk_read,
+    disk_written,
+    disk_read_rate,
+    disk_write_rate,
+    connections,
+});
+
+wire_struct!(Session {
+    user,
+    line,
+    host,
+    pid,
+    login_time,
+});
+
+wire_struct!(CgroupStat {
+    path,
+    cpu_usage,
+    throttled_periods,
+    throttled_time,
+    memory_current,
+    memory_max,
+    memory_high,
+    pids_current,
+    io_read,
+    io_written,
+});
+
+wire_struct!(Container {
+    id,
+    name,
+    image,
+    state,
+    status,
+});
+
+wire_struct!(Unit {
+    name,
+    description,
+    load_state,
+    active_state,
+    sub_state,
+    main_pid,
+    tasks,
+    memory,
+    cpu_usage,
+    control_group,
+});
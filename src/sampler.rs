@@ -0,0 +1,253 @@
+mod elf;
+
+use crate::procfs;
+use elf::ElfFile;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// Deepest user-space stack walked, in frames
+const MAX_FRAMES: usize = 64;
+
+pub struct Profile {
+    pub samples: usize,
+    // Distinct stacks (innermost frame first) and how often they were seen,
+    // most frequent first
+    pub stacks: Vec<(usize, Vec<String>)>,
+    // Reasons why part of the stacks could not be collected
+    pub notes: Vec<String>,
+}
+
+// Samples the stacks of all the threads of a process a number of times and
+// aggregates identical stacks. Kernel stacks come from procfs, user-space stacks
+// are walked through ptrace by following frame pointers where permitted. Frames
+// are reduced to their function so that samples landing at different offsets
+// within the same function aggregate together.
+pub fn sample(root: &Path, pid: u32, samples: usize, interval: Duration) -> Profile {
+    let mut symbolizer = Symbolizer::new(root, pid);
+    let mut counts: HashMap<Vec<String>, usize> = HashMap::new();
+    let mut notes = Vec::new();
+
+    // Waiting for ptrace stops of a child of ptop, such as a supervised
+    // command, could reap its exit status before the supervisor does
+    let child = procfs::task::read_tasks(root, pid)
+        .iter()
+        .any(|task| task.parent == std::process::id());
+    if child {
+        notes.push("User-space stacks are not sampled for commands run by ptop".to_string());
+    }
+
+    for i in 0..samples {
+        if i > 0 {
+            std::thread::sleep(interval);
+        }
+        for task in procfs::task::read_tasks(root, pid) {
+            let mut stack: Vec<String> = procfs::stack::read_kernel_stack(root, pid, task.tid)
+                .into_iter()
+                .map(|frame| format!("[kernel] {}", frame.split('+').next().unwrap_or_default()))
+                .collect();
+            if stack.is_empty() {
+                if let Some(wchan) = procfs::stack::read_wchan(root, pid, task.tid) {
+                    stack.push(format!("[kernel] {}", wchan));
+                }
+            }
+
+            if !child {
+                match user_stack(task.tid) {
+                    Ok(frames) => stack.extend(
+                        frames
+                            .iter()
+                            .enumerate()
+                            // Return addresses point past the call, look up the call itself
+                            .map(|(i, &address)| symbolizer.symbolize(address - (i > 0) as u64)),
+                    ),
+                    Err(e) => {
+                        let note = format!("User-space stacks unavailable: {}", e);
+                        if !notes.contains(&note) {
+                            notes.push(note);
+                        }
+                    }
+                }
+            }
+
+            if stack.is_empty() {
+                stack.push(format!("{} ({})", task.name, task.state));
+            }
+            *counts.entry(stack).or_insert(0) += 1;
+        }
+    }
+
+    let mut stacks: Vec<(usize, Vec<String>)> = counts
+        .into_iter()
+        .map(|(stack, count)| (count, stack))
+        .collect();
+    stacks.sort_by_key(|(count, _)| std::cmp::Reverse(*count));
+    Profile {
+        samples,
+        stacks,
+        notes,
+    }
+}
+
+// Resolves user-space addresses to "symbol (file)" using the memory map
+// of the process and the symbol tables of the mapped files
+struct Symbolizer {
+    // The root directory of the process, as seen through procfs, so that files
+    // are looked up in its mount namespace
+    root: PathBuf,
+    mappings: Vec<procfs::maps::Mapping>,
+    files: HashMap<String, Option<ElfFile>>,
+}
+
+impl Symbolizer {
+    fn new(root: &Path, pid: u32) -> Self {
+        // maps has the same header lines as smaps, without the counters
+        let mappings = fs::read_to_string(root.join(pid.to_string()).join("maps"))
+            .map(|content| procfs::maps::parse_smaps(&content))
+            .unwrap_or_default();
+        Symbolizer {
+            root: root.join(pid.to_string()).join("root"),
+            mappings,
+            files: HashMap::new(),
+        }
+    }
+
+    fn symbolize(&mut self, address: u64) -> String {
+        let Some(mapping) = self
+            .mappings
+            .iter()
+            .find(|m| address >= m.start && address < m.end)
+        else {
+            return format!("{:#x}", address);
+        };
+        let file_name = mapping.path.rsplit('/').next().unwrap_or_default();
+        let root = &self.root;
+        let elf = self.files.entry(mapping.path.clone()).or_insert_with(|| {
+            // Anonymous and special mappings such as [heap] have no file
+            let path = mapping.path.strip_prefix('/')?;
+            ElfFile::load(&root.join(path))
+        });
+        let symbol = elf.as_ref().and_then(|elf| {
+            let address = elf.offset_to_address(address - mapping.start + mapping.offset)?;
+            elf.symbolize(address)
+        });
+        match symbol {
+            Some(name) => format!("{} ({})", name, file_name),
+            None if file_name.is_empty() => format!("{:#x}", address),
+            None => format!("{:#x} ({})", address, file_name),
+        }
+    }
+}
+
+// Stops a thread with ptrace and walks its frame pointer chain, innermost
+// return address first
+#[cfg(target_arch = "x86_64")]
+fn user_stack(tid: u32) -> Result<Vec<u64>, String> {
+    use std::io::Error;
+    use std::ptr::null_mut;
+
+    let tid = tid as libc::pid_t;
+    let none = null_mut::<libc::c_void>();
+    // SAFETY: the ptrace requests only ever write into the local regs struct
+    unsafe {
+        if libc::ptrace(libc::PTRACE_SEIZE, tid, none, none) != 0 {
+            return Err(Error::last_os_error().to_string());
+        }
+        let result = (|| {
+            if libc::ptrace(libc::PTRACE_INTERRUPT, tid, none, none) != 0 {
+                return Err(Error::last_os_error().to_string());
+            }
+            let mut status = 0;
+            if libc::waitpid(tid, &mut status, libc::__WALL) != tid {
+                return Err(Error::last_os_error().to_string());
+            }
+            let mut regs: libc::user_regs_struct = std::mem::zeroed();
+            if libc::ptrace(libc::PTRACE_GETREGS, tid, none, &mut regs) != 0 {
+                return Err(Error::last_os_error().to_string());
+            }
+
+            let peek = |address: u64| {
+                *libc::__errno_location() = 0;
+                let word = libc::ptrace(libc::PTRACE_PEEKDATA, tid, address as usize, none);
+                (word != -1 || *libc::__errno_location() == 0).then_some(word as u64)
+            };
+            let mut frames = vec![regs.rip];
+            let mut frame_pointer = regs.rbp;
+            while frames.len() < MAX_FRAMES && frame_pointer != 0 && frame_pointer.is_multiple_of(8)
+            {
+                let (Some(next), Some(return_address)) =
+                    (peek(frame_pointer), peek(frame_pointer + 8))
+                else {
+                    break;
+                };
+                if return_address == 0 {
+                    break;
+                }
+                frames.push(return_address);
+                // The stack grows down, so callers' frames must be at higher addresses
+                if next <= frame_pointer {
+                    break;
+                }
+                frame_pointer = next;
+            }
+            Ok(frames)
+        })();
+        libc::ptrace(libc::PTRACE_DETACH, tid, none, none);
+        result
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn user_stack(_tid: u32) -> Result<Vec<u64>, String> {
+    Err("stack walking is only supported on x86_64".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn loads_files_from_the_process_root() {
+        let proc = Fixture::new();
+        proc.write(
+            "42/maps",
+            "00400000-00500000 r-xp 00000000 08:01 1 /usr/bin/app\n\
+             00600000-00700000 r-xp 00000000 08:01 2 /usr/lib/libhost.so\n\
+             00800000-00900000 rw-p 00000000 00:00 0 [heap]\n",
+        )
+        .link("42/root/usr/bin/app", "/proc/self/exe");
+
+        let mut symbolizer = Symbolizer::new(proc.path(), 42);
+        assert!(symbolizer.symbolize(0x400000).ends_with("(app)"));
+        assert_eq!(symbolizer.symbolize(0x600010), "0x600010 (libhost.so)");
+        assert_eq!(symbolizer.symbolize(0x800000), "0x800000 ([heap])");
+        assert_eq!(symbolizer.symbolize(0x10), "0x10");
+        assert!(symbolizer.files["/usr/bin/app"].is_some());
+        // Not the file of the same name outside the process' mount namespace
+        assert!(symbolizer.files["/usr/lib/libhost.so"].is_none());
+        assert!(symbolizer.files["[heap]"].is_none());
+    }
+
+    #[test]
+    fn leaves_children_of_ptop_alone() {
+        let proc = Fixture::new();
+        // Thread IDs no process can have, should anything try to trace them
+        let stat = format!(
+            "(job) S {} 1 1 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 1 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 17 0 \
+             0 0 0 0 0 0 0 0 0 0 0 0 0",
+            std::process::id()
+        );
+        proc.write("4194304/task/4194304/stat", &format!("4194304 {}", stat))
+            .write("4194304/task/4194305/stat", &format!("4194305 {}", stat));
+
+        let profile = sample(proc.path(), 4194304, 2, Duration::ZERO);
+        assert_eq!(profile.samples, 2);
+        assert_eq!(profile.stacks, vec![(4, vec!["job (S)".to_string()])]);
+        assert_eq!(
+            profile.notes,
+            vec!["User-space stacks are not sampled for commands run by ptop"]
+        );
+    }
+}
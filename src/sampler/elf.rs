@@ -0,0 +1,181 @@
+use std::fs;
+use std::path::Path;
+
+pub struct Symbol {
+    pub address: u64,
+    pub size: u64,
+    pub name: String,
+}
+
+// The parts of a 64-bit little-endian ELF file needed to symbolize addresses
+pub struct ElfFile {
+    // (file offset, file size, virtual address) of each PT_LOAD segment
+    segments: Vec<(u64, u64, u64)>,
+    // Function symbols sorted by address
+    symbols: Vec<Symbol>,
+}
+
+const PT_LOAD: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const SHT_DYNSYM: u32 = 11;
+const STT_FUNC: u8 = 2;
+
+// Offsets come from the file itself, so a crafted one may point anywhere and
+// all arithmetic on them is checked
+fn slice(data: &[u8], at: usize, length: usize) -> Option<&[u8]> {
+    data.get(at..at.checked_add(length)?)
+}
+
+fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(slice(data, at, 2)?.try_into().ok()?))
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(slice(data, at, 4)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(slice(data, at, 8)?.try_into().ok()?))
+}
+
+// Offset of the index-th entry of a table
+fn entry(table: usize, index: usize, size: usize) -> Option<usize> {
+    table.checked_add(index.checked_mul(size)?)
+}
+
+fn read_str(data: &[u8], at: usize) -> Option<String> {
+    let bytes = data.get(at..)?;
+    let end = bytes.iter().position(|&b| b == 0)?;
+    Some(String::from_utf8_lossy(&bytes[..end]).into_owned())
+}
+
+impl ElfFile {
+    pub fn load(path: &Path) -> Option<ElfFile> {
+        ElfFile::parse(&fs::read(path).ok()?)
+    }
+
+    pub fn parse(data: &[u8]) -> Option<ElfFile> {
+        // Only ELFCLASS64 / ELFDATA2LSB is supported
+        if data.get(0..4)? != b"\x7fELF" || *data.get(4)? != 2 || *data.get(5)? != 1 {
+            return None;
+        }
+        let phoff = read_u64(data, 0x20)? as usize;
+        let shoff = read_u64(data, 0x28)? as usize;
+        let phentsize = read_u16(data, 0x36)? as usize;
+        let phnum = read_u16(data, 0x38)? as usize;
+        let shentsize = read_u16(data, 0x3a)? as usize;
+        let shnum = read_u16(data, 0x3c)? as usize;
+
+        let segments = (0..phnum)
+            .filter_map(|i| {
+                let at = entry(phoff, i, phentsize)?;
+                (read_u32(data, at)? == PT_LOAD).then_some((
+                    read_u64(data, at.checked_add(8)?)?,
+                    read_u64(data, at.checked_add(32)?)?,
+                    read_u64(data, at.checked_add(16)?)?,
+                ))
+            })
+            .collect();
+
+        let mut symbols = Vec::new();
+        for i in 0..shnum {
+            let at = entry(shoff, i, shentsize)?;
+            let kind = read_u32(data, at.checked_add(4)?)?;
+            if kind != SHT_SYMTAB && kind != SHT_DYNSYM {
+                continue;
+            }
+            let offset = read_u64(data, at.checked_add(24)?)? as usize;
+            let size = read_u64(data, at.checked_add(32)?)? as usize;
+            let entsize = read_u64(data, at.checked_add(56)?)? as usize;
+            // sh_link points at the string table holding the symbol names
+            let link = read_u32(data, at.checked_add(40)?)? as usize;
+            let strtab = read_u64(data, entry(shoff, link, shentsize)?.checked_add(24)?)? as usize;
+            if entsize == 0 {
+                continue;
+            }
+            let table = slice(data, offset, size)?;
+            for sym in (0..table.len()).step_by(entsize) {
+                let sym = offset + sym;
+                let info = *data.get(sym.checked_add(4)?)?;
+                let address = read_u64(data, sym.checked_add(8)?)?;
+                if info & 0xf != STT_FUNC || address == 0 {
+                    continue;
+                }
+                symbols.push(Symbol {
+                    address,
+                    size: read_u64(data, sym.checked_add(16)?)?,
+                    name: read_str(data, strtab.checked_add(read_u32(data, sym)? as usize)?)?,
+                });
+            }
+        }
+        symbols.sort_by_key(|s| s.address);
+
+        Some(ElfFile { segments, symbols })
+    }
+
+    // Translates an offset into the file to the virtual address it is linked at
+    pub fn offset_to_address(&self, offset: u64) -> Option<u64> {
+        self.segments
+            .iter()
+            .find(|(start, size, _)| offset >= *start && offset - start < *size)
+            .and_then(|(start, _, address)| address.checked_add(offset - start))
+    }
+
+    pub fn symbolize(&self, address: u64) -> Option<&str> {
+        let index = self.symbols.partition_point(|s| s.address <= address);
+        let symbol = self.symbols.get(index.checked_sub(1)?)?;
+        // Symbols without a size are accepted, they are usually hand-written assembly
+        (symbol.size == 0 || address - symbol.address < symbol.size).then_some(symbol.name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An ELF header followed by a single symbol table section header
+    fn with_symtab(offset: u64, size: u64, entsize: u64) -> Vec<u8> {
+        let mut data = vec![0u8; 128];
+        data[0..4].copy_from_slice(b"\x7fELF");
+        data[4] = 2;
+        data[5] = 1;
+        data[0x28..0x30].copy_from_slice(&64u64.to_le_bytes());
+        data[0x3a..0x3c].copy_from_slice(&64u16.to_le_bytes());
+        data[0x3c..0x3e].copy_from_slice(&1u16.to_le_bytes());
+        data[64 + 4..64 + 8].copy_from_slice(&SHT_SYMTAB.to_le_bytes());
+        data[64 + 24..64 + 32].copy_from_slice(&offset.to_le_bytes());
+        data[64 + 32..64 + 40].copy_from_slice(&size.to_le_bytes());
+        data[64 + 56..64 + 64].copy_from_slice(&entsize.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn rejects_truncated_files() {
+        assert!(ElfFile::parse(b"").is_none());
+        assert!(ElfFile::parse(b"\x7fELF").is_none());
+        assert!(ElfFile::parse(b"\x7fELF\x02").is_none());
+        assert!(ElfFile::parse(b"\x7fELF\x02\x01").is_none());
+        assert!(ElfFile::parse(&with_symtab(0, 0, 24)[..100]).is_none());
+    }
+
+    #[test]
+    fn rejects_out_of_range_tables() {
+        assert!(ElfFile::parse(&with_symtab(u64::MAX - 4, 16, 24)).is_none());
+        assert!(ElfFile::parse(&with_symtab(64, u64::MAX, 24)).is_none());
+        let mut data = with_symtab(0, 0, 24);
+        data[0x28..0x30].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(ElfFile::parse(&data).is_none());
+    }
+
+    #[test]
+    fn symbolizes_own_functions() {
+        let elf = ElfFile::load(Path::new("/proc/self/exe")).unwrap();
+        let symbol = elf.symbols.iter().find(|s| s.size > 1).unwrap();
+        assert_eq!(elf.symbolize(symbol.address), Some(symbol.name.as_str()));
+        assert_eq!(
+            elf.symbolize(symbol.address + symbol.size - 1),
+            Some(symbol.name.as_str())
+        );
+        assert!(elf.symbolize(0).is_none());
+    }
+}
@@ -0,0 +1,178 @@
+use crate::system_monitor;
+use humansize::{format_size, BINARY};
+use humantime::format_duration;
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Read};
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Number of output lines kept from the supervised command
+const MAX_OUTPUT_LINES: usize = 1000;
+
+pub type OutputBuffer = Arc<Mutex<VecDeque<String>>>;
+
+// A command launched by ptop, whose output is captured and whose exit status
+// and resource usage are collected when it exits
+pub struct Supervisor {
+    child: Child,
+    pub pid: u32,
+    pub name: String,
+    pub output: OutputBuffer,
+    start: Instant,
+    wall_time: Option<Duration>,
+    exit_status: Option<ExitStatus>,
+    // CPU time and max RSS the kernel accounted to the command, once reaped
+    usage: Option<(Duration, u64)>,
+    peak_rss: u64,
+}
+
+impl Supervisor {
+    pub fn spawn(command: &[String]) -> io::Result<Supervisor> {
+        let mut child = Command::new(&command[0])
+            .args(&command[1..])
+            // The terminal belongs to the UI
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let output: OutputBuffer = Arc::new(Mutex::new(VecDeque::new()));
+        if let Some(stdout) = child.stdout.take() {
+            tail(stdout, output.clone(), "");
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tail(stderr, output.clone(), "[stderr] ");
+        }
+
+        Ok(Supervisor {
+            pid: child.id(),
+            name: command.join(" "),
+            child,
+            output,
+            start: Instant::now(),
+            wall_time: None,
+            exit_status: None,
+            usage: None,
+            peak_rss: 0,
+        })
+    }
+
+    // Tracks the peak memory of the process tree and reaps the command once it
+    // has exited. Returns the exit status on the update where the exit is seen.
+    pub fn poll(&mut self, monitor: &system_monitor::Monitor) -> Option<ExitStatus> {
+        if self.exit_status.is_some() {
+            return None;
+        }
+        let tree = monitor.get_process_tree(self.pid);
+        let rss: u64 = monitor
+            .get_processes()
+            .iter()
+            .filter(|p| !p.thread && tree.contains(&p.pid))
+            .map(|p| p.memory)
+            .sum();
+        self.peak_rss = self.peak_rss.max(rss);
+
+        let status = self.reap(false)?;
+        let summary = self.summary();
+        let mut output = self.output.lock().unwrap();
+        output.extend(summary.lines().map(|l| format!("[ptop] {}", l)));
+        Some(status)
+    }
+
+    // Kills the command if it is still running when ptop quits
+    pub fn stop(&mut self) {
+        if self.exit_status.is_none() {
+            let _ = self.child.kill();
+            self.reap(true);
+        }
+    }
+
+    // Waits for the command to exit, only if it already has unless blocking,
+    // and collects its exit status and resource usage. The rusage of wait4 only
+    // covers the command and its descendants, unlike RUSAGE_CHILDREN which
+    // counts every child ptop ever reaped.
+    fn reap(&mut self, block: bool) -> Option<ExitStatus> {
+        let mut status = 0;
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        let options = if block { 0 } else { libc::WNOHANG };
+        // SAFETY: wait4 only writes into the provided status and struct
+        let pid = unsafe { libc::wait4(self.pid as libc::pid_t, &mut status, options, &mut usage) };
+        if pid != self.pid as libc::pid_t {
+            return None;
+        }
+        let cpu_time = Duration::from_secs((usage.ru_utime.tv_sec + usage.ru_stime.tv_sec) as u64)
+            + Duration::from_micros((usage.ru_utime.tv_usec + usage.ru_stime.tv_usec) as u64);
+        let status = ExitStatus::from_raw(status);
+        self.wall_time = Some(self.start.elapsed());
+        self.exit_status = Some(status);
+        self.usage = Some((cpu_time, usage.ru_maxrss as u64 * 1024));
+        Some(status)
+    }
+
+    pub fn summary(&self) -> String {
+        // The kernel accounting also covers what was missed between samples
+        let (cpu_time, max_rss) = match self.usage {
+            Some((cpu_time, max_rss)) => (
+                format_duration(round_to_millis(cpu_time)).to_string(),
+                max_rss,
+            ),
+            None => ("unknown".to_string(), 0),
+        };
+        let peak_rss = self.peak_rss.max(max_rss);
+
+        let status = match self.exit_status {
+            Some(status) => status.to_string(),
+            None => "still running".to_string(),
+        };
+        format!(
+            "{} ({}): {}\nPeak RSS: {}\nCPU time: {}\nWall time: {}",
+            self.name,
+            self.pid,
+            status,
+            format_size(peak_rss, BINARY),
+            cpu_time,
+            format_duration(round_to_millis(
+                self.wall_time.unwrap_or_else(|| self.start.elapsed())
+            )),
+        )
+    }
+}
+
+fn round_to_millis(duration: Duration) -> Duration {
+    Duration::from_millis(duration.as_millis() as u64)
+}
+
+fn tail(stream: impl Read + Send + 'static, output: OutputBuffer, prefix: &'static str) {
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            let mut output = output.lock().unwrap();
+            if output.len() == MAX_OUTPUT_LINES {
+                output.pop_front();
+            }
+            output.push_back(format!("{}{}", prefix, line));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_usage_of_the_command_only() {
+        let mut supervisor = Supervisor::spawn(&["true".to_string()]).unwrap();
+        // An unrelated child burning CPU, which RUSAGE_CHILDREN would count too
+        Command::new("sh")
+            .args(["-c", "i=0; while [ $i -lt 300000 ]; do i=$((i+1)); done"])
+            .status()
+            .unwrap();
+        supervisor.stop();
+        assert!(supervisor.exit_status.unwrap().success());
+        let (cpu_time, _) = supervisor.usage.unwrap();
+        assert!(cpu_time < Duration::from_millis(100), "{:?}", cpu_time);
+        assert!(!supervisor.summary().contains("unknown"));
+    }
+}
@@ -0,0 +1,6 @@
+pub mod block;
+pub mod cgroup;
+pub mod cpufreq;
+pub mod hwmon;
+pub mod power_supply;
+pub mod rapl;
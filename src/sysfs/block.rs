@@ -0,0 +1,38 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+// Returns the names of the block devices backed by a device, from root
+// (normally /sys/block). Partitions are not listed there, and virtual devices
+// (loop, device mapper, md, zram) have no device link, so that the I/O of
+// each disk is only counted once.
+pub fn read_physical_disks(root: &Path) -> HashSet<String> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return HashSet::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.path().join("device").symlink_metadata().is_ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn reads_physical_disks() {
+        let block = Fixture::new();
+        block
+            .link("vda/device", "../../../virtio1")
+            .link("nvme0n1/device", "../../nvme0")
+            .write("loop0/size", "0")
+            .write("dm-0/size", "0");
+        assert_eq!(
+            read_physical_disks(block.path()),
+            HashSet::from(["vda".to_string(), "nvme0n1".to_string()])
+        );
+    }
+}
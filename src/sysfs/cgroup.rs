@@ -0,0 +1,203 @@
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone)]
+pub struct CgroupStat {
+    // Path relative to the cgroup root, "/" for the root itself
+    pub path: String,
+    // Total CPU time in microseconds
+    pub cpu_usage: u64,
+    pub throttled_periods: u64,
+    // Total time throttled by the CPU quota, in microseconds
+    pub throttled_time: u64,
+    // Memory values in bytes, limits are None when unlimited or when the
+    // memory controller is not enabled for the cgroup
+    pub memory_current: Option<u64>,
+    pub memory_max: Option<u64>,
+    pub memory_high: Option<u64>,
+    pub pids_current: Option<u64>,
+    pub io_read: u64,
+    pub io_written: u64,
+}
+
+// Walks a cgroup v2 hierarchy mounted at root (normally /sys/fs/cgroup) and
+// reads the stats of every cgroup, parents before their children
+pub fn read_cgroups(root: &Path) -> Vec<CgroupStat> {
+    let mut cgroups = Vec::new();
+    if root.join("cgroup.procs").exists() {
+        walk(root, "/", &mut cgroups);
+    }
+    cgroups
+}
+
+fn walk(dir: &Path, path: &str, cgroups: &mut Vec<CgroupStat>) {
+    cgroups.push(read_cgroup(dir, path));
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut children: Vec<_> = entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .collect();
+    children.sort_by_key(|e| e.file_name());
+    for child in children {
+        let name = child.file_name().to_string_lossy().into_owned();
+        let child_path = if path == "/" {
+            format!("/{}", name)
+        } else {
+            format!("{}/{}", path, name)
+        };
+        walk(&child.path(), &child_path, cgroups);
+    }
+}
+
+fn read_cgroup(dir: &Path, path: &str) -> CgroupStat {
+    let read = |file: &str| fs::read_to_string(dir.join(file)).ok();
+    let (cpu_usage, throttled_periods, throttled_time) = read("cpu.stat")
+        .map(|content| parse_cpu_stat(&content))
+        .unwrap_or_default();
+    let (io_read, io_written) = read("io.stat")
+        .map(|content| parse_io_stat(&content))
+        .unwrap_or_default();
+    CgroupStat {
+        path: path.to_string(),
+        cpu_usage,
+        throttled_periods,
+        throttled_time,
+        memory_current: read("memory.current").and_then(|content| parse_limit(&content)),
+        memory_max: read("memory.max").and_then(|content| parse_limit(&content)),
+        memory_high: read("memory.high").and_then(|content| parse_limit(&content)),
+        pids_current: read("pids.current").and_then(|content| parse_limit(&content)),
+        io_read,
+        io_written,
+    }
+}
+
+// Returns (usage_usec, nr_throttled, throttled_usec) from cpu.stat
+pub fn parse_cpu_stat(content: &str) -> (u64, u64, u64) {
+    let mut stat = (0, 0, 0);
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once(' ') else {
+            continue;
+        };
+        let value = value.trim().parse().unwrap_or(0);
+        match key {
+            "usage_usec" => stat.0 = value,
+            "nr_throttled" => stat.1 = value,
+            "throttled_usec" => stat.2 = value,
+            _ => {}
+        }
+    }
+    stat
+}
+
+// Sums rbytes and wbytes over the devices of io.stat, whose lines look like
+// "8:0 rbytes=1024 wbytes=0 rios=1 wios=0 dbytes=0 dios=0"
+pub fn parse_io_stat(content: &str) -> (u64, u64) {
+    let mut stat = (0, 0);
+    for field in content.split_whitespace() {
+        match field.split_once('=') {
+            Some(("rbytes", value)) => stat.0 += value.parse().unwrap_or(0),
+            Some(("wbytes", value)) => stat.1 += value.parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+    stat
+}
+
+// Parses a single value file, where "max" means unlimited
+pub fn parse_limit(content: &str) -> Option<u64> {
+    content.trim().parse().ok()
+}
+
+// Extracts the short ID of a container from the cgroup path of one of its
+// processes, as laid out by Docker, Podman and containerd, e.g.
+// "/system.slice/docker-<id>.scope", "/docker/<id>" or
+// "/machine.slice/libpod-<id>.scope"
+pub fn container_id(path: &str) -> Option<String> {
+    path.split('/').rev().find_map(|component| {
+        let id = component.strip_suffix(".scope").unwrap_or(component);
+        let id = ["docker-", "libpod-", "cri-containerd-", "crio-"]
+            .iter()
+            .find_map(|prefix| id.strip_prefix(prefix))
+            .unwrap_or(id);
+        (id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit())).then(|| id[..12].to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    const ID: &str = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    #[test]
+    fn walks_the_hierarchy() {
+        let cgroup = Fixture::new();
+        cgroup
+            .write("cgroup.procs", "1\n")
+            .write("cpu.stat", "usage_usec 9000\n")
+            .write(
+                "system.slice/cpu.stat",
+                "usage_usec 5000\nuser_usec 4000\nsystem_usec 1000\nnr_periods 10\nnr_throttled 3\nthrottled_usec 1500\n",
+            )
+            .write("system.slice/memory.current", "1048576\n")
+            .write("system.slice/memory.max", "max\n")
+            .write("system.slice/memory.high", "2097152\n")
+            .write("system.slice/pids.current", "12\n")
+            .write(
+                "system.slice/io.stat",
+                "8:0 rbytes=1024 wbytes=2048 rios=1 wios=2 dbytes=0 dios=0\n259:0 rbytes=1 wbytes=2 rios=1 wios=1 dbytes=0 dios=0\n",
+            )
+            .write("system.slice/sshd.service/cgroup.procs", "42\n")
+            .write("init.scope/cgroup.procs", "1\n");
+
+        let cgroups = read_cgroups(cgroup.path());
+        let paths: Vec<&str> = cgroups.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/",
+                "/init.scope",
+                "/system.slice",
+                "/system.slice/sshd.service"
+            ]
+        );
+        let slice = &cgroups[2];
+        assert_eq!(slice.cpu_usage, 5000);
+        assert_eq!((slice.throttled_periods, slice.throttled_time), (3, 1500));
+        assert_eq!(slice.memory_current, Some(1048576));
+        assert_eq!(slice.memory_max, None);
+        assert_eq!(slice.memory_high, Some(2097152));
+        assert_eq!(slice.pids_current, Some(12));
+        assert_eq!((slice.io_read, slice.io_written), (1025, 2050));
+        // Controllers not enabled for a cgroup leave its values empty
+        assert_eq!(cgroups[3].cpu_usage, 0);
+        assert_eq!(cgroups[3].memory_current, None);
+    }
+
+    #[test]
+    fn reads_nothing_without_cgroup_v2() {
+        let cgroup = Fixture::new();
+        cgroup.write("cpu/cpu.shares", "1024\n");
+        assert!(read_cgroups(cgroup.path()).is_empty());
+    }
+
+    #[test]
+    fn extracts_container_ids() {
+        for path in [
+            format!("/system.slice/docker-{}.scope", ID),
+            format!("/docker/{}", ID),
+            format!("/machine.slice/libpod-{}.scope/container", ID),
+            format!("/kubepods/burstable/pod1/cri-containerd-{}.scope", ID),
+        ] {
+            assert_eq!(container_id(&path).as_deref(), Some(&ID[..12]), "{}", path);
+        }
+        assert_eq!(
+            container_id("/user.slice/user-1000.slice/session-2.scope"),
+            None
+        );
+        assert_eq!(container_id("/"), None);
+    }
+}
@@ -0,0 +1,93 @@
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone)]
+pub struct CpuFrequency {
+    pub core: usize,
+    // All frequencies in MHz
+    pub current: Option<u64>,
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+    pub governor: Option<String>,
+}
+
+// Reads the frequency scaling state of every core from root (normally
+// /sys/devices/system/cpu). Cores without a cpufreq directory, which is the
+// case on most virtual machines, report no values.
+pub fn read_frequencies(root: &Path) -> Vec<CpuFrequency> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut frequencies: Vec<CpuFrequency> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let core = entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("cpu")?
+                .parse()
+                .ok()?;
+            let cpufreq = entry.path().join("cpufreq");
+            Some(CpuFrequency {
+                core,
+                current: read_khz(&cpufreq.join("scaling_cur_freq")),
+                min: read_khz(&cpufreq.join("scaling_min_freq")),
+                max: read_khz(&cpufreq.join("scaling_max_freq")),
+                governor: fs::read_to_string(cpufreq.join("scaling_governor"))
+                    .ok()
+                    .map(|governor| governor.trim().to_string()),
+            })
+        })
+        .collect();
+    frequencies.sort_by_key(|f| f.core);
+    frequencies
+}
+
+// cpufreq reports frequencies in kHz
+fn read_khz(path: &Path) -> Option<u64> {
+    fs::read_to_string(path)
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(|khz| khz / 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn reads_frequencies() {
+        let cpu = Fixture::new();
+        cpu.write("cpu0/cpufreq/scaling_cur_freq", "2400000\n")
+            .write("cpu0/cpufreq/scaling_min_freq", "800000\n")
+            .write("cpu0/cpufreq/scaling_max_freq", "4800000\n")
+            .write("cpu0/cpufreq/scaling_governor", "powersave\n")
+            .write("cpu10/cpufreq/scaling_cur_freq", "3100500\n")
+            // A core without cpufreq, as on most virtual machines
+            .write("cpu2/online", "1\n")
+            // Not cores
+            .write("cpufreq/boost", "1\n")
+            .write("cpuidle/current_driver", "intel_idle\n")
+            .write("online", "0-2,10\n");
+
+        let frequencies = read_frequencies(cpu.path());
+        let cores: Vec<usize> = frequencies.iter().map(|f| f.core).collect();
+        assert_eq!(cores, vec![0, 2, 10]);
+        assert_eq!(frequencies[0].current, Some(2400));
+        assert_eq!(frequencies[0].min, Some(800));
+        assert_eq!(frequencies[0].max, Some(4800));
+        assert_eq!(frequencies[0].governor.as_deref(), Some("powersave"));
+        assert_eq!(frequencies[1].current, None);
+        assert_eq!(frequencies[1].governor, None);
+        assert_eq!(frequencies[2].current, Some(3100));
+    }
+
+    #[test]
+    fn reads_nothing_without_cpus() {
+        let cpu = Fixture::new();
+        assert!(read_frequencies(&cpu.path().join("missing")).is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone)]
+pub struct Temperature {
+    pub label: String,
+    // All values in degrees Celsius
+    pub current: f32,
+    pub max: Option<f32>,
+    pub critical: Option<f32>,
+}
+
+// Reads the temperature sensors of every hwmon device under root (normally
+// /sys/class/hwmon), labelled "<device> <sensor label>". Devices without
+// temperature inputs, e.g. fan controllers, are skipped, and a missing root
+// yields no sensors at all.
+pub fn read_temperatures(root: &Path) -> Vec<Temperature> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut devices: Vec<_> = entries.flatten().map(|e| e.path()).collect();
+    devices.sort();
+
+    let mut temperatures = Vec::new();
+    for device in devices {
+        let name = read_string(&device.join("name")).unwrap_or_else(|| {
+            device
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+        let Ok(files) = fs::read_dir(&device) else {
+            continue;
+        };
+        let mut indices: Vec<u32> = files
+            .flatten()
+            .filter_map(|f| {
+                let file_name = f.file_name();
+                let file_name = file_name.to_str()?;
+                file_name
+                    .strip_prefix("temp")?
+                    .strip_suffix("_input")?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        indices.sort();
+
+        for index in indices {
+            let prefix = format!("temp{}", index);
+            let Some(current) = read_millidegrees(&device.join(format!("{}_input", prefix))) else {
+                continue;
+            };
+            let label = read_string(&device.join(format!("{}_label", prefix))).unwrap_or(prefix);
+            temperatures.push(Temperature {
+                label: format!("{} {}", name, label),
+                current,
+                max: read_millidegrees(&device.join(format!("temp{}_max", index))),
+                critical: read_millidegrees(&device.join(format!("temp{}_crit", index))),
+            });
+        }
+    }
+    temperatures
+}
+
+fn read_string(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let content = content.trim();
+    (!content.is_empty()).then(|| content.to_string())
+}
+
+// hwmon reports temperatures in thousandths of a degree Celsius
+fn read_millidegrees(path: &Path) -> Option<f32> {
+    read_string(path)?
+        .parse::<i64>()
+        .ok()
+        .map(|t| t as f32 / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn reads_temperatures() {
+        let hwmon = Fixture::new();
+        hwmon
+            .write("hwmon0/name", "coretemp\n")
+            .write("hwmon0/temp1_input", "45000\n")
+            .write("hwmon0/temp1_label", "Package id 0\n")
+            .write("hwmon0/temp1_max", "80000\n")
+            .write("hwmon0/temp1_crit", "100000\n")
+            .write("hwmon0/temp10_input", "-1500\n")
+            .write("hwmon0/temp2_input", "43000\n")
+            .write("hwmon0/temp2_label", "Core 0\n")
+            // A sensor that failed to read
+            .write("hwmon0/temp3_input", "")
+            .write("hwmon1/fan1_input", "1200\n")
+            .write("hwmon2/temp1_input", "38500\n");
+
+        let temperatures = read_temperatures(hwmon.path());
+        let labels: Vec<&str> = temperatures.iter().map(|t| t.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "coretemp Package id 0",
+                "coretemp Core 0",
+                "coretemp temp10",
+                "hwmon2 temp1"
+            ]
+        );
+        assert_eq!(temperatures[0].current, 45.0);
+        assert_eq!(temperatures[0].max, Some(80.0));
+        assert_eq!(temperatures[0].critical, Some(100.0));
+        assert_eq!(temperatures[1].max, None);
+        assert_eq!(temperatures[2].current, -1.5);
+        assert_eq!(temperatures[3].current, 38.5);
+    }
+
+    #[test]
+    fn reads_nothing_without_hwmon() {
+        let hwmon = Fixture::new();
+        assert!(read_temperatures(&hwmon.path().join("missing")).is_empty());
+    }
+}
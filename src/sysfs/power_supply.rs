@@ -0,0 +1,180 @@
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone)]
+pub struct PowerSupply {
+    pub name: String,
+    // e.g. "Battery", "Mains" or "USB"
+    pub kind: String,
+    // Whether an adapter is plugged in, not reported by batteries
+    pub online: Option<bool>,
+    // e.g. "Charging", "Discharging", "Full" or "Not charging"
+    pub status: Option<String>,
+    // Charge in percent of the full capacity
+    pub capacity: Option<f32>,
+    // Rate of charge or discharge in watts
+    pub power: Option<f64>,
+    // Estimates in seconds, only while discharging or charging respectively
+    pub time_to_empty: Option<u64>,
+    pub time_to_full: Option<u64>,
+}
+
+// Reads every power supply under root (normally /sys/class/power_supply).
+// Desktops and virtual machines usually have none, which yields no supplies.
+pub fn read_power_supplies(root: &Path) -> Vec<PowerSupply> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut supplies: Vec<PowerSupply> = entries
+        .flatten()
+        .map(|entry| read_power_supply(&entry.path()))
+        .collect();
+    supplies.sort_by(|a, b| a.name.cmp(&b.name));
+    supplies
+}
+
+fn read_power_supply(dir: &Path) -> PowerSupply {
+    let status = read_string(&dir.join("status"));
+    // Drivers report either energy (µWh, µW) or charge (µAh, µA) counters, and
+    // some report a negative current while discharging
+    let power_now = read_number(&dir.join("power_now")).map(f64::abs);
+    let current_now = read_number(&dir.join("current_now")).map(f64::abs);
+    let voltage_now = read_number(&dir.join("voltage_now"));
+    let power = power_now
+        .map(|p| p / 1_000_000.0)
+        .or_else(|| Some(current_now? * voltage_now? / 1_000_000_000_000.0));
+    let (now, full, rate) = match read_number(&dir.join("energy_now")) {
+        Some(energy_now) => (
+            Some(energy_now),
+            read_number(&dir.join("energy_full")),
+            power_now,
+        ),
+        None => (
+            read_number(&dir.join("charge_now")),
+            read_number(&dir.join("charge_full")),
+            current_now,
+        ),
+    };
+    // Hours left at the current rate, in seconds
+    let estimate = |amount: Option<f64>| {
+        let rate = rate.filter(|r| *r > 0.0)?;
+        Some((amount? / rate * 3600.0) as u64)
+    };
+
+    PowerSupply {
+        name: dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        kind: read_string(&dir.join("type")).unwrap_or_default(),
+        online: read_number(&dir.join("online")).map(|online| online != 0.0),
+        capacity: read_number(&dir.join("capacity"))
+            .map(|c| c as f32)
+            .or_else(|| Some((now? / full.filter(|f| *f > 0.0)? * 100.0) as f32)),
+        time_to_empty: match status.as_deref() {
+            Some("Discharging") => read_number(&dir.join("time_to_empty_now"))
+                .map(|t| t as u64)
+                .or_else(|| estimate(now)),
+            _ => None,
+        },
+        time_to_full: match status.as_deref() {
+            Some("Charging") => read_number(&dir.join("time_to_full_now"))
+                .map(|t| t as u64)
+                .or_else(|| estimate(Some(full? - now?))),
+            _ => None,
+        },
+        status,
+        power,
+    }
+}
+
+fn read_string(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let content = content.trim();
+    (!content.is_empty()).then(|| content.to_string())
+}
+
+fn read_number(path: &Path) -> Option<f64> {
+    read_string(path)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    #[test]
+    fn reads_energy_battery() {
+        let power_supply = Fixture::new();
+        power_supply
+            .write("BAT0/type", "Battery\n")
+            .write("BAT0/status", "Discharging\n")
+            .write("BAT0/capacity", "57\n")
+            .write("BAT0/energy_now", "30000000\n")
+            .write("BAT0/energy_full", "52000000\n")
+            .write("BAT0/power_now", "12000000\n")
+            .write("AC/type", "Mains\n")
+            .write("AC/online", "1\n");
+
+        let supplies = read_power_supplies(power_supply.path());
+        assert_eq!(supplies.len(), 2);
+
+        let ac = &supplies[0];
+        assert_eq!(ac.name, "AC");
+        assert_eq!(ac.kind, "Mains");
+        assert_eq!(ac.online, Some(true));
+        assert_eq!(ac.status, None);
+        assert_eq!(ac.power, None);
+
+        let battery = &supplies[1];
+        assert_eq!(battery.kind, "Battery");
+        assert_eq!(battery.status.as_deref(), Some("Discharging"));
+        assert_eq!(battery.capacity, Some(57.0));
+        assert_eq!(battery.power, Some(12.0));
+        // 30 Wh left at 12 W
+        assert_eq!(battery.time_to_empty, Some(9000));
+        assert_eq!(battery.time_to_full, None);
+    }
+
+    #[test]
+    fn reads_charge_battery() {
+        let power_supply = Fixture::new();
+        power_supply
+            .write("BAT1/type", "Battery\n")
+            .write("BAT1/status", "Charging\n")
+            .write("BAT1/charge_now", "2000000\n")
+            .write("BAT1/charge_full", "4000000\n")
+            // Some drivers report the current as negative
+            .write("BAT1/current_now", "-1000000\n")
+            .write("BAT1/voltage_now", "12000000\n");
+
+        let battery = &read_power_supplies(power_supply.path())[0];
+        assert_eq!(battery.capacity, Some(50.0));
+        assert_eq!(battery.power, Some(12.0));
+        // 2 Ah to go at 1 A
+        assert_eq!(battery.time_to_full, Some(7200));
+        assert_eq!(battery.time_to_empty, None);
+    }
+
+    #[test]
+    fn prefers_reported_estimates() {
+        let power_supply = Fixture::new();
+        power_supply
+            .write("BAT0/status", "Discharging\n")
+            .write("BAT0/energy_now", "30000000\n")
+            .write("BAT0/power_now", "12000000\n")
+            .write("BAT0/time_to_empty_now", "600\n");
+
+        let battery = &read_power_supplies(power_supply.path())[0];
+        assert_eq!(battery.time_to_empty, Some(600));
+        // Without energy_full the charge cannot be computed
+        assert_eq!(battery.capacity, None);
+    }
+
+    #[test]
+    fn reads_nothing_without_supplies() {
+        let power_supply = Fixture::new();
+        assert!(read_power_supplies(power_supply.path()).is_empty());
+        assert!(read_power_supplies(&power_supply.path().join("missing")).is_empty());
+    }
+}
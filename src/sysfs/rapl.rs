@@ -0,0 +1,154 @@
+use std::fs;
+use std::path::Path;
+
+pub struct EnergyCounter {
+    // Zone name prefixed with its parent package, e.g. "package-0" or "package-0 core"
+    pub name: String,
+    // Cumulative energy in microjoules
+    pub energy: u64,
+    // Value at which the counter wraps around to zero, when the zone reports it
+    pub max_energy: Option<u64>,
+}
+
+impl EnergyCounter {
+    // Average power in watts since an earlier reading of the same counter, or
+    // none when the counter went back and its range is unknown
+    pub fn power_since(&self, previous: &EnergyCounter, seconds: f64) -> Option<f64> {
+        let delta = match self.energy.checked_sub(previous.energy) {
+            Some(delta) => delta,
+            // The counter wrapped around since the previous reading
+            None => self
+                .max_energy?
+                .checked_sub(previous.energy)?
+                .checked_add(self.energy)?,
+        };
+        (seconds > 0.0).then(|| delta as f64 / 1_000_000.0 / seconds)
+    }
+}
+
+// Reads the RAPL energy counters of every powercap zone under root (normally
+// /sys/class/powercap). Only Intel and recent AMD CPUs have them, and
+// energy_uj is only readable by root on most kernels, so this often yields
+// no counters at all.
+pub fn read_energy_counters(root: &Path) -> Vec<EnergyCounter> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut zones: Vec<_> = entries
+        .flatten()
+        .map(|e| e.path())
+        // Subzones such as intel-rapl:0:0 are listed at the top level too
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("intel-rapl:"))
+        })
+        .collect();
+    zones.sort();
+
+    let mut counters = Vec::new();
+    for zone in zones {
+        let (Some(name), Some(energy)) = (
+            read_string(&zone.join("name")),
+            read_u64(&zone.join("energy_uj")),
+        ) else {
+            continue;
+        };
+        // A subzone is named after its package, e.g. intel-rapl:0:1 belongs to intel-rapl:0
+        let zone_name = zone
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        let name = match zone_name.rsplit_once(':') {
+            Some((parent, _)) if parent.contains(':') => {
+                match read_string(&root.join(parent).join("name")) {
+                    Some(package) => format!("{} {}", package, name),
+                    None => name,
+                }
+            }
+            _ => name,
+        };
+        counters.push(EnergyCounter {
+            name,
+            energy,
+            max_energy: read_u64(&zone.join("max_energy_range_uj")),
+        });
+    }
+    counters
+}
+
+fn read_string(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    let content = content.trim();
+    (!content.is_empty()).then(|| content.to_string())
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    read_string(path)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    fn counter(energy: u64, max_energy: Option<u64>) -> EnergyCounter {
+        EnergyCounter {
+            name: "package-0".to_string(),
+            energy,
+            max_energy,
+        }
+    }
+
+    #[test]
+    fn reads_zones_and_subzones() {
+        let powercap = Fixture::new();
+        powercap
+            .write("intel-rapl:0/name", "package-0\n")
+            .write("intel-rapl:0/energy_uj", "123456789\n")
+            .write("intel-rapl:0/max_energy_range_uj", "262143328850\n")
+            .write("intel-rapl:0:0/name", "core\n")
+            .write("intel-rapl:0:0/energy_uj", "4567\n")
+            // energy_uj is only readable by root on most kernels
+            .write("intel-rapl:1/name", "package-1\n")
+            .write("intel-rapl-mmio:0/name", "package-0\n")
+            .write("intel-rapl-mmio:0/energy_uj", "1\n");
+
+        let counters = read_energy_counters(powercap.path());
+        assert_eq!(counters.len(), 2);
+        assert_eq!(counters[0].name, "package-0");
+        assert_eq!(counters[0].energy, 123456789);
+        assert_eq!(counters[0].max_energy, Some(262143328850));
+        assert_eq!(counters[1].name, "package-0 core");
+        assert_eq!(counters[1].max_energy, None);
+    }
+
+    #[test]
+    fn computes_power() {
+        let then = counter(1_000_000, Some(10_000_000));
+        assert_eq!(
+            counter(21_000_000, None).power_since(&then, 2.0),
+            Some(10.0)
+        );
+        assert_eq!(counter(1_000_000, None).power_since(&then, 0.0), None);
+    }
+
+    #[test]
+    fn handles_wraparound() {
+        let then = counter(9_000_000, Some(10_000_000));
+        assert_eq!(
+            counter(3_000_000, Some(10_000_000)).power_since(&then, 1.0),
+            Some(4.0)
+        );
+        // Without the range, or with a reading beyond it, a drop cannot be explained
+        assert_eq!(
+            counter(3_000_000, None).power_since(&counter(9_000_000, None), 1.0),
+            None
+        );
+        assert_eq!(
+            counter(3_000_000, Some(10_000_000))
+                .power_since(&counter(11_000_000, Some(10_000_000)), 1.0),
+            None
+        );
+    }
+}
@@ -1,87 +1,986 @@
-use sysinfo::{Pid, ProcessStatus, ProcessesToUpdate, System};
+use crate::container;
+use crate::models::alert::AlertDefinition;
+use crate::procfs;
+use crate::remote::{self, client::Client};
+use crate::sampler;
+use crate::sysfs;
+use crate::systemd::{self, Manager};
+use crate::utmp;
+use std::collections::{HashMap, HashSet};
+use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::{
+    Disks, Networks, Pid, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, System, ThreadKind,
+    Users,
+};
+
+const PROC_ROOT: &str = "/proc";
+const HWMON_ROOT: &str = "/sys/class/hwmon";
+const CPU_ROOT: &str = "/sys/devices/system/cpu";
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+const UTMP_PATH: &str = "/var/run/utmp";
+const POWER_SUPPLY_ROOT: &str = "/sys/class/power_supply";
+const POWERCAP_ROOT: &str = "/sys/class/powercap";
+const BLOCK_ROOT: &str = "/sys/block";
+// Filesystem usage changes slowly, and refreshing it takes a statvfs per mount
+const DISK_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+// Stack sampling takes STACK_SAMPLES samples, STACK_SAMPLE_INTERVAL apart
+const STACK_SAMPLES: usize = 20;
+const STACK_SAMPLE_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Clone)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub parent: Option<u32>,
+    pub thread: bool,
+    pub name: String,
+    pub uid: Option<u32>,
+    pub user: String,
+    pub cpu_usage: f32,
+    pub memory: u64,
+    pub run_time: u64,
+    pub status: ProcessStatus,
+    pub disk_read: u64,
+    pub disk_written: u64,
+    pub disk_read_rate: f64,
+    pub disk_write_rate: f64,
+    pub connections: usize,
+}
+
+#[derive(Clone)]
+pub struct DiskInfo {
+    pub name: String,
+    pub file_system: String,
+    pub mount_point: String,
+    pub total_space: u64,
+    pub available_space: u64,
+    pub total_inodes: u64,
+    pub free_inodes: u64,
+}
+
+#[derive(Clone)]
+pub struct NetworkInfo {
+    pub name: String,
+    pub receive_rate: f64,
+    pub transmit_rate: f64,
+    pub total_received: u64,
+    pub total_transmitted: u64,
+    pub packets_received: u64,
+    pub packets_transmitted: u64,
+    pub errors_received: u64,
+    pub errors_transmitted: u64,
+    pub addresses: Vec<String>,
+}
 
 pub struct Monitor {
     sys: System,
+    users: Users,
+    disks: Disks,
+    networks: Networks,
+    // None when no container runtime socket was found
+    container_client: Option<container::Client>,
+    // None when the system was not booted with systemd
+    systemd: Option<Box<dyn Manager>>,
+    // The two latest /proc/stat readings, CPU time shares are computed from their difference
+    cpu_stat: Option<procfs::stat::CpuStat>,
+    previous_cpu_stat: Option<procfs::stat::CpuStat>,
+    // Same for the swap counters of /proc/vmstat
+    swap_activity: Option<procfs::vmstat::SwapActivity>,
+    previous_swap_activity: Option<procfs::vmstat::SwapActivity>,
+    // Same for the (read, written) bytes of the physical disks
+    disk_io: Option<(u64, u64)>,
+    previous_disk_io: Option<(u64, u64)>,
+    // Same for the RAPL energy counters
+    energy_counters: Vec<sysfs::rapl::EnergyCounter>,
+    previous_energy_counters: Vec<sysfs::rapl::EnergyCounter>,
+    // Open socket count of each process, read once per update as it takes a
+    // readlink for every open file
+    connection_counts: HashMap<u32, usize>,
+    // (total, free) inode counts of each mount point, read along with the disk
+    // list as it takes a statvfs per filesystem
+    inode_counts: HashMap<PathBuf, (u64, u64)>,
+    disks_refreshed: Instant,
+    last_update: Instant,
+    interval: Duration,
+    // Set when showing the host of a remote agent instead of this one, all
+    // readings then come from its latest snapshot
+    remote: Option<Client>,
 }
 
 impl Monitor {
     pub fn new() -> Self {
         let mut sys = System::new_all();
         sys.refresh_all();
-        Monitor { sys }
+        let disks = Disks::new_with_refreshed_list();
+        Monitor {
+            sys,
+            users: Users::new_with_refreshed_list(),
+            inode_counts: read_inode_counts(&disks),
+            disks,
+            networks: Networks::new_with_refreshed_list(),
+            container_client: container::Client::discover(),
+            systemd: systemd::Systemctl::discover().map(|m| Box::new(m) as Box<dyn Manager>),
+            cpu_stat: procfs::stat::read_cpu_stat(Path::new(PROC_ROOT)),
+            previous_cpu_stat: None,
+            swap_activity: procfs::vmstat::read_swap_activity(Path::new(PROC_ROOT)),
+            previous_swap_activity: None,
+            disk_io: read_disk_io(),
+            previous_disk_io: None,
+            energy_counters: sysfs::rapl::read_energy_counters(Path::new(POWERCAP_ROOT)),
+            previous_energy_counters: Vec::new(),
+            connection_counts: HashMap::new(),
+            disks_refreshed: Instant::now(),
+            last_update: Instant::now(),
+            interval: Duration::from_secs(1),
+            remote: None,
+        }
+    }
+
+    // Monitors the host of an agent through a connected client
+    pub fn connect(client: Client) -> Self {
+        Monitor {
+            sys: System::new(),
+            users: Users::new(),
+            disks: Disks::new(),
+            networks: Networks::new(),
+            container_client: None,
+            systemd: None,
+            cpu_stat: None,
+            previous_cpu_stat: None,
+            swap_activity: None,
+            previous_swap_activity: None,
+            disk_io: None,
+            previous_disk_io: None,
+            energy_counters: Vec::new(),
+            previous_energy_counters: Vec::new(),
+            connection_counts: HashMap::new(),
+            inode_counts: HashMap::new(),
+            disks_refreshed: Instant::now(),
+            last_update: Instant::now(),
+            interval: Duration::from_secs(1),
+            remote: Some(client),
+        }
+    }
+
+    // The latest snapshot of the remote host, when monitoring one
+    fn snapshot(&self) -> Option<&remote::Snapshot> {
+        self.remote.as_ref().map(|client| client.snapshot())
+    }
+
+    // Returns the address of the agent and the state of the connection to it,
+    // when monitoring a remote host
+    pub fn get_remote_status(&self) -> Option<(String, remote::client::Status)> {
+        self.remote
+            .as_ref()
+            .map(|client| (client.address().to_string(), client.status()))
     }
 
     pub fn update(&mut self) {
-        self.sys.refresh_all();
-        self.sys.refresh_processes(ProcessesToUpdate::All, true);
+        if let Some(client) = self.remote.as_mut() {
+            client.update();
+            return;
+        }
+        self.interval = self.last_update.elapsed();
+        self.last_update = Instant::now();
+        // Counters are read right away, so that their rates cover the interval
+        // just measured rather than also the time taken by the refreshes below
+        self.previous_cpu_stat = std::mem::replace(
+            &mut self.cpu_stat,
+            procfs::stat::read_cpu_stat(Path::new(PROC_ROOT)),
+        );
+        self.previous_swap_activity = std::mem::replace(
+            &mut self.swap_activity,
+            procfs::vmstat::read_swap_activity(Path::new(PROC_ROOT)),
+        );
+        self.previous_disk_io = std::mem::replace(&mut self.disk_io, read_disk_io());
+        self.previous_energy_counters = std::mem::replace(
+            &mut self.energy_counters,
+            sysfs::rapl::read_energy_counters(Path::new(POWERCAP_ROOT)),
+        );
+        self.sys.refresh_memory();
+        self.sys.refresh_cpu_all();
+        // A single process refresh per update, sysinfo reports the disk usage
+        // since the previous one
+        self.sys.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::everything(),
+        );
+        // Threads share the fd table of their process, so only count it once
+        self.connection_counts = self
+            .sys
+            .processes()
+            .values()
+            .filter(|p| p.thread_kind() != Some(ThreadKind::Userland))
+            .map(|p| {
+                let pid = p.pid().as_u32();
+                (
+                    pid,
+                    procfs::net::socket_inodes(Path::new(PROC_ROOT), pid).len(),
+                )
+            })
+            .collect();
+        self.users.refresh_list();
+        // Refreshing the list also picks up filesystems mounted since the last refresh
+        if self.disks_refreshed.elapsed() >= DISK_REFRESH_INTERVAL {
+            self.disks.refresh_list();
+            self.inode_counts = read_inode_counts(&self.disks);
+            self.disks_refreshed = Instant::now();
+        }
+        // Network interfaces come and go with containers and VPNs
+        self.networks.refresh_list();
+        // The container runtime may be started after ptop
+        if self.container_client.is_none() {
+            self.container_client = container::Client::discover();
+        }
+    }
+
+    // Converts a byte count accumulated since the previous update into bytes per second
+    fn rate(&self, bytes: u64) -> f64 {
+        bytes as f64 / self.interval.as_secs_f64().max(0.001)
     }
 
     pub fn get_host_name(&self) -> String {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.host_name.clone();
+        }
         System::host_name().unwrap_or_default()
     }
 
     pub fn get_os_name(&self) -> String {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.os_name.clone();
+        }
         System::long_os_version().unwrap_or_default()
     }
 
     pub fn get_kernel_version(&self) -> String {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.kernel_version.clone();
+        }
         System::kernel_version().unwrap_or_default()
     }
 
     pub fn get_uptime(&self) -> u64 {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.uptime;
+        }
         System::uptime()
     }
 
     pub fn get_cpu_name(&self) -> String {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.cpu_name.clone();
+        }
         self.sys.cpus()[0].brand().to_string()
     }
 
     pub fn get_global_cpu_usage(&self) -> f32 {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.cpu_usage;
+        }
         self.sys.global_cpu_usage()
     }
 
     pub fn get_per_cpu_usage(&self) -> Vec<f32> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.per_cpu_usage.clone();
+        }
         self.sys.cpus().iter().map(|p| p.cpu_usage()).collect()
     }
 
+    // Returns the share of each CPU state over the last interval, over all cores
+    pub fn get_cpu_breakdown(&self) -> Option<procfs::stat::CpuBreakdown> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.cpu_breakdown;
+        }
+        let (now, then) = (self.cpu_stat.as_ref()?, self.previous_cpu_stat.as_ref()?);
+        Some(now.total.breakdown_since(&then.total))
+    }
+
+    pub fn get_per_cpu_breakdown(&self) -> Vec<procfs::stat::CpuBreakdown> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.per_cpu_breakdown.clone();
+        }
+        match (&self.cpu_stat, &self.previous_cpu_stat) {
+            (Some(now), Some(then)) => now
+                .cores
+                .iter()
+                .zip(then.cores.iter())
+                .map(|(now, then)| now.breakdown_since(then))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    // Returns the frequency scaling state of each core, falling back to the
+    // frequency reported by sysinfo where cpufreq is not available
+    pub fn get_cpu_frequencies(&self) -> Vec<sysfs::cpufreq::CpuFrequency> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.cpu_frequencies.clone();
+        }
+        // sysinfo reports 0 when it does not know the frequency either
+        let known = |mhz: u64| (mhz > 0).then_some(mhz);
+        let mut frequencies = sysfs::cpufreq::read_frequencies(Path::new(CPU_ROOT));
+        for (core, cpu) in self.sys.cpus().iter().enumerate() {
+            match frequencies.iter_mut().find(|f| f.core == core) {
+                Some(frequency) => {
+                    frequency.current = frequency.current.or(known(cpu.frequency()));
+                }
+                None => frequencies.push(sysfs::cpufreq::CpuFrequency {
+                    core,
+                    current: known(cpu.frequency()),
+                    min: None,
+                    max: None,
+                    governor: None,
+                }),
+            }
+        }
+        frequencies.sort_by_key(|f| f.core);
+        frequencies
+    }
+
+    pub fn get_cpu_count(&self) -> usize {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.per_cpu_usage.len();
+        }
+        self.sys.cpus().len()
+    }
+
+    pub fn get_load_average(&self) -> Option<procfs::loadavg::LoadAverage> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.load_average.clone();
+        }
+        procfs::loadavg::read_loadavg(Path::new(PROC_ROOT))
+    }
+
+    pub fn get_pressure(
+        &self,
+        resource: procfs::pressure::Resource,
+    ) -> Option<procfs::pressure::Pressure> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot
+                .pressure
+                .iter()
+                .find(|(r, _)| *r == resource)
+                .map(|(_, pressure)| pressure.clone());
+        }
+        procfs::pressure::read_pressure(Path::new(PROC_ROOT), resource)
+    }
+
     pub fn get_total_memory(&self) -> (u64, u64) {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.total_memory;
+        }
         (self.sys.total_memory(), self.sys.total_swap())
     }
 
     pub fn get_used_memory(&self) -> (u64, u64) {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.used_memory;
+        }
         (self.sys.used_memory(), self.sys.used_swap())
     }
 
-    pub fn get_processes(&self) -> Vec<(u32, String, f32, u64, u64, ProcessStatus)> {
+    pub fn get_meminfo(&self) -> Option<procfs::meminfo::MemInfo> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.meminfo;
+        }
+        procfs::meminfo::read_meminfo(Path::new(PROC_ROOT))
+    }
+
+    // Returns the (swap in, swap out) rate in bytes per second
+    pub fn get_swap_rates(&self) -> (f64, f64) {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.swap_rates;
+        }
+        match (self.swap_activity, self.previous_swap_activity) {
+            (Some(now), Some(then)) => {
+                let page_size = procfs::vmstat::page_size();
+                (
+                    self.rate(now.pages_in.saturating_sub(then.pages_in) * page_size),
+                    self.rate(now.pages_out.saturating_sub(then.pages_out) * page_size),
+                )
+            }
+            _ => (0.0, 0.0),
+        }
+    }
+
+    pub fn get_power_supplies(&self) -> Vec<sysfs::power_supply::PowerSupply> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.power_supplies.clone();
+        }
+        sysfs::power_supply::read_power_supplies(Path::new(POWER_SUPPLY_ROOT))
+    }
+
+    // Returns the charge of a battery in percent, and whether it is discharging
+    pub fn get_battery_by_name(&self, name: &str) -> Option<(f32, bool)> {
+        self.get_power_supplies()
+            .into_iter()
+            .find(|s| s.name == name)
+            .and_then(|s| Some((s.capacity?, s.status.as_deref() == Some("Discharging"))))
+    }
+
+    // Returns the power drawn by each RAPL zone in watts
+    pub fn get_rapl_power(&self) -> Vec<(String, f64)> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.rapl_power.clone();
+        }
+        let seconds = self.interval.as_secs_f64();
+        self.energy_counters
+            .iter()
+            .filter_map(|now| {
+                let then = self
+                    .previous_energy_counters
+                    .iter()
+                    .find(|then| then.name == now.name)?;
+                Some((now.name.clone(), now.power_since(then, seconds)?))
+            })
+            .collect()
+    }
+
+    pub fn get_disks(&self) -> Vec<DiskInfo> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.disks.clone();
+        }
+        self.disks
+            .list()
+            .iter()
+            .map(|d| {
+                let (total_inodes, free_inodes) = self
+                    .inode_counts
+                    .get(d.mount_point())
+                    .copied()
+                    .unwrap_or((0, 0));
+                DiskInfo {
+                    name: d.name().to_string_lossy().into_owned(),
+                    file_system: d.file_system().to_string_lossy().into_owned(),
+                    mount_point: d.mount_point().to_string_lossy().into_owned(),
+                    total_space: d.total_space(),
+                    available_space: d.available_space(),
+                    total_inodes,
+                    free_inodes,
+                }
+            })
+            .collect()
+    }
+
+    // Returns the used space of a filesystem in percent
+    pub fn get_disk_usage_by_mount_point(&self, mount_point: &str) -> Option<f32> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot
+                .disks
+                .iter()
+                .find(|d| d.mount_point == mount_point)
+                .filter(|d| d.total_space > 0)
+                .map(|d| {
+                    (d.total_space - d.available_space) as f32 / d.total_space as f32 * 100.0
+                });
+        }
+        self.disks
+            .list()
+            .iter()
+            .find(|d| d.mount_point() == Path::new(mount_point))
+            .filter(|d| d.total_space() > 0)
+            .map(|d| {
+                (d.total_space() - d.available_space()) as f32 / d.total_space() as f32 * 100.0
+            })
+    }
+
+    // Returns the network interfaces sorted by name
+    pub fn get_networks(&self) -> Vec<NetworkInfo> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.networks.clone();
+        }
+        let mut networks: Vec<NetworkInfo> = self
+            .networks
+            .list()
+            .iter()
+            .map(|(name, n)| NetworkInfo {
+                name: name.clone(),
+                receive_rate: self.rate(n.received()),
+                transmit_rate: self.rate(n.transmitted()),
+                total_received: n.total_received(),
+                total_transmitted: n.total_transmitted(),
+                packets_received: n.total_packets_received(),
+                packets_transmitted: n.total_packets_transmitted(),
+                errors_received: n.total_errors_on_received(),
+                errors_transmitted: n.total_errors_on_transmitted(),
+                addresses: n.ip_networks().iter().map(|ip| ip.to_string()).collect(),
+            })
+            .collect();
+        networks.sort_by(|a, b| a.name.cmp(&b.name));
+        networks
+    }
+
+    pub fn get_temperatures(&self) -> Vec<sysfs::hwmon::Temperature> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.temperatures.clone();
+        }
+        sysfs::hwmon::read_temperatures(Path::new(HWMON_ROOT))
+    }
+
+    pub fn get_temperature_by_label(&self, label: &str) -> Option<f32> {
+        self.get_temperatures()
+            .into_iter()
+            .find(|t| t.label == label)
+            .map(|t| t.current)
+    }
+
+    pub fn get_processes(&self) -> Vec<ProcessInfo> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.processes.clone();
+        }
         self.sys
             .processes()
             .values()
-            .map(|p| {
-                (
-                    p.pid().as_u32(),
-                    p.name().to_string_lossy().into_owned(),
-                    p.cpu_usage(),
-                    p.memory(),
-                    p.run_time(),
-                    p.status(),
-                )
+            .map(|p| ProcessInfo {
+                pid: p.pid().as_u32(),
+                parent: p.parent().map(|pid| pid.as_u32()),
+                thread: p.thread_kind() == Some(ThreadKind::Userland),
+                name: p.name().to_string_lossy().into_owned(),
+                uid: p.user_id().map(|uid| **uid),
+                user: p
+                    .user_id()
+                    .map(|uid| {
+                        self.users
+                            .get_user_by_id(uid)
+                            .map(|u| u.name().to_string())
+                            .unwrap_or_else(|| uid.to_string())
+                    })
+                    .unwrap_or_default(),
+                cpu_usage: p.cpu_usage(),
+                memory: p.memory(),
+                run_time: p.run_time(),
+                status: p.status(),
+                disk_read: p.disk_usage().total_read_bytes,
+                disk_written: p.disk_usage().total_written_bytes,
+                disk_read_rate: self.rate(p.disk_usage().read_bytes),
+                disk_write_rate: self.rate(p.disk_usage().written_bytes),
+                connections: self.get_process_connection_count(p.pid().as_u32()),
             })
             .collect()
     }
 
+    // Returns the system-wide (read, write) rate of the physical disks in bytes
+    // per second, which unlike the process counters covers kernel threads and
+    // processes that exited during the interval
+    pub fn get_disk_io_rates(&self) -> (f64, f64) {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.disk_io_rates;
+        }
+        match (self.disk_io, self.previous_disk_io) {
+            (Some((read, written)), Some((then_read, then_written))) => (
+                self.rate(read.saturating_sub(then_read)),
+                self.rate(written.saturating_sub(then_written)),
+            ),
+            _ => (0.0, 0.0),
+        }
+    }
+
+    // Returns the PIDs of a process and all of its descendants
+    pub fn get_process_tree(&self, root: u32) -> HashSet<u32> {
+        let parents: Vec<(u32, Option<u32>)> = match self.snapshot() {
+            Some(snapshot) => snapshot
+                .processes
+                .iter()
+                .map(|p| (p.pid, p.parent))
+                .collect(),
+            None => self
+                .sys
+                .processes()
+                .values()
+                .map(|p| (p.pid().as_u32(), p.parent().map(|pid| pid.as_u32())))
+                .collect(),
+        };
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (pid, parent) in parents {
+            if let Some(parent) = parent {
+                children.entry(parent).or_default().push(pid);
+            }
+        }
+
+        let mut tree = HashSet::new();
+        let mut pending = vec![root];
+        while let Some(pid) = pending.pop() {
+            if tree.insert(pid) {
+                pending.extend(children.get(&pid).into_iter().flatten());
+            }
+        }
+        tree
+    }
+
+    // Returns the login sessions whose leader is still alive, utmp keeps stale
+    // entries around when a session is not closed cleanly
+    pub fn get_sessions(&self) -> Vec<utmp::Session> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.sessions.clone();
+        }
+        utmp::read_sessions(Path::new(UTMP_PATH))
+            .into_iter()
+            .filter(|s| self.sys.process(Pid::from(s.pid as usize)).is_some())
+            .collect()
+    }
+
+    // Returns the cgroup path of a process, preferring the unified (v2) hierarchy
+    // and falling back to the systemd named hierarchy on v1 hosts.
+    pub fn get_process_cgroup(&self, pid: u32) -> Option<String> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.process_cgroups.get(&pid).cloned();
+        }
+        let content = fs::read_to_string(format!("{}/{}/cgroup", PROC_ROOT, pid)).ok()?;
+        let mut fallback = None;
+        for line in content.lines() {
+            let mut parts = line.splitn(3, ':');
+            let (id, controllers, path) = (parts.next()?, parts.next()?, parts.next()?);
+            if id == "0" && controllers.is_empty() {
+                return Some(path.to_string());
+            }
+            if controllers == "name=systemd" {
+                fallback = Some(path.to_string());
+            }
+        }
+        fallback
+    }
+
+    pub fn get_cgroups(&self) -> Vec<sysfs::cgroup::CgroupStat> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.cgroups.clone();
+        }
+        sysfs::cgroup::read_cgroups(&cgroup_root())
+    }
+
+    pub fn has_container_runtime(&self) -> bool {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.containers.is_some();
+        }
+        self.container_client.is_some()
+    }
+
+    // Returns the containers known to the runtime, or none when it cannot be reached
+    pub fn get_containers(&self) -> Vec<container::Container> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.containers.clone().unwrap_or_default();
+        }
+        self.container_client
+            .as_ref()
+            .and_then(|client| client.list_containers().ok())
+            .unwrap_or_default()
+    }
+
+    pub fn has_systemd(&self) -> bool {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.units.is_some();
+        }
+        self.systemd.is_some()
+    }
+
+    // Returns the loaded services, or none when systemd cannot be reached
+    pub fn get_units(&self) -> Vec<systemd::Unit> {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot.units.clone().unwrap_or_default();
+        }
+        self.systemd
+            .as_ref()
+            .and_then(|manager| manager.list_units().ok())
+            .unwrap_or_default()
+    }
+
+    pub fn restart_unit(&self, name: &str) -> io::Result<()> {
+        if let Some(client) = &self.remote {
+            return client.request(remote::Request::RestartUnit(name.to_string()));
+        }
+        self.systemd_manager()?.restart_unit(name)
+    }
+
+    pub fn stop_unit(&self, name: &str) -> io::Result<()> {
+        if let Some(client) = &self.remote {
+            return client.request(remote::Request::StopUnit(name.to_string()));
+        }
+        self.systemd_manager()?.stop_unit(name)
+    }
+
+    fn systemd_manager(&self) -> io::Result<&dyn Manager> {
+        self.systemd
+            .as_deref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "systemd is not running"))
+    }
+
+    pub fn get_process_sockets(&self, pid: u32) -> Vec<procfs::net::Socket> {
+        // Only read on demand, which snapshots do not cover
+        if self.remote.is_some() {
+            return Vec::new();
+        }
+        procfs::net::read_sockets(Path::new(PROC_ROOT), pid)
+    }
+
+    pub fn get_process_connection_count(&self, pid: u32) -> usize {
+        if let Some(snapshot) = self.snapshot() {
+            return snapshot
+                .processes
+                .iter()
+                .find(|p| p.pid == pid)
+                .map_or(0, |p| p.connections);
+        }
+        self.connection_counts.get(&pid).copied().unwrap_or(0)
+    }
+
+    pub fn get_process_open_files(&self, pid: u32) -> Vec<procfs::fd::OpenFile> {
+        // Only read on demand, which snapshots do not cover
+        if self.remote.is_some() {
+            return Vec::new();
+        }
+        procfs::fd::read_open_files(Path::new(PROC_ROOT), pid)
+    }
+
+    pub fn get_process_mappings(&self, pid: u32) -> Vec<procfs::maps::Mapping> {
+        // Only read on demand, which snapshots do not cover
+        if self.remote.is_some() {
+            return Vec::new();
+        }
+        procfs::maps::read_mappings(Path::new(PROC_ROOT), pid)
+    }
+
+    pub fn get_process_memory_rollup(&self, pid: u32) -> Option<procfs::maps::Mapping> {
+        // Only read on demand, which snapshots do not cover
+        if self.remote.is_some() {
+            return None;
+        }
+        procfs::maps::read_rollup(Path::new(PROC_ROOT), pid)
+    }
+
+    pub fn get_process_tasks(&self, pid: u32) -> Vec<procfs::task::TaskStat> {
+        // Only read on demand, which snapshots do not cover
+        if self.remote.is_some() {
+            return Vec::new();
+        }
+        procfs::task::read_tasks(Path::new(PROC_ROOT), pid)
+    }
+
+    // Samples the stacks of a process on a background thread, as it takes the
+    // whole sampling window. The profile is sent once complete.
+    pub fn sample_stacks(&self, pid: u32) -> mpsc::Receiver<sampler::Profile> {
+        let (sender, profile) = mpsc::channel();
+        if self.remote.is_some() {
+            let _ = sender.send(sampler::Profile {
+                samples: 0,
+                stacks: Vec::new(),
+                notes: vec!["Stacks cannot be sampled on a remote host".to_string()],
+            });
+            return profile;
+        }
+        thread::spawn(move || {
+            // Nobody is waiting anymore when the view was closed in the meantime
+            let _ = sender.send(sampler::sample(
+                Path::new(PROC_ROOT),
+                pid,
+                STACK_SAMPLES,
+                STACK_SAMPLE_INTERVAL,
+            ));
+        });
+        profile
+    }
+
     pub fn terminate_process(&mut self, pid: u32) {
+        if let Some(client) = &self.remote {
+            client.send(remote::Request::Terminate(pid));
+            return;
+        }
         if let Some(process) = self.sys.process(Pid::from(pid as usize)) {
             process.kill();
         }
     }
 
-    pub fn get_process_status_by_pid(&self, pid: u32) -> Option<(f32, f32, ProcessStatus)> {
+    // Asks the agent to evaluate an alert as well when monitoring a remote
+    // host, so that it keeps doing so once the client goes away
+    pub fn forward_alert(&self, definition: AlertDefinition) {
+        if let Some(client) = &self.remote {
+            client.send(remote::Request::AddAlert(definition));
+        }
+    }
+
+    pub fn get_process_status_by_pid(&self, pid: u32) -> Option<(f32, f32, f64, ProcessStatus)> {
+        if let Some(snapshot) = self.snapshot() {
+            let total_memory = snapshot.total_memory.0 as f32;
+            return snapshot.processes.iter().find(|p| p.pid == pid).map(|p| {
+                (
+                    p.cpu_usage,
+                    (p.memory as f32 / total_memory) * 100.0,
+                    p.disk_write_rate,
+                    p.status,
+                )
+            });
+        }
         self.sys.process(Pid::from(pid as usize)).map(|p| {
             (
                 p.cpu_usage(),
                 (p.memory() as f32 / self.sys.total_memory() as f32) * 100.0,
+                self.rate(p.disk_usage().written_bytes),
                 p.status(),
             )
         })
     }
 }
+
+// Returns the bytes (read, written) by the physical disks since boot
+fn read_disk_io() -> Option<(u64, u64)> {
+    let disks = sysfs::block::read_physical_disks(Path::new(BLOCK_ROOT));
+    let stats = procfs::diskstats::read_diskstats(Path::new(PROC_ROOT));
+    if stats.is_empty() {
+        return None;
+    }
+    Some(
+        stats
+            .iter()
+            .filter(|s| disks.contains(&s.name))
+            .fold((0, 0), |(read, written), s| {
+                (read + s.read_bytes, written + s.written_bytes)
+            }),
+    )
+}
+
+// Returns the (total, free) inode counts of the filesystem mounted at a path
+fn inode_usage(mount_point: &Path) -> Option<(u64, u64)> {
+    let path = CString::new(mount_point.as_os_str().as_bytes()).ok()?;
+    // SAFETY: statvfs only writes into the provided struct
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    Some((stat.f_files as u64, stat.f_ffree as u64))
+}
+
+// Reads the inode counts of every listed filesystem
+fn read_inode_counts(disks: &Disks) -> HashMap<PathBuf, (u64, u64)> {
+    disks
+        .list()
+        .iter()
+        .filter_map(|d| Some((d.mount_point().to_path_buf(), inode_usage(d.mount_point())?)))
+        .collect()
+}
+
+// Returns the mount point of the cgroup v2 hierarchy, which hosts running the
+// hybrid layout mount under unified/ next to the v1 controllers
+fn cgroup_root() -> PathBuf {
+    let root = Path::new(CGROUP_ROOT);
+    if root.join("cgroup.controllers").exists() {
+        root.to_path_buf()
+    } else {
+        root.join("unified")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::AppState;
+    use crate::models::unit::UnitTable;
+    use crate::update::UpdateableWidgetWithState;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    // Stands in for systemd, answering with captured `systemctl show` output
+    // and recording the requested jobs
+    struct StubManager {
+        jobs: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Manager for StubManager {
+        fn list_units(&self) -> io::Result<Vec<systemd::Unit>> {
+            Ok(systemd::parse_show(
+                "Id=cron.service\nLoadState=loaded\nActiveState=active\nSubState=running\n\
+                 MainPID=812\nMemoryCurrent=1327104\n\n\
+                 Id=foo.service\nLoadState=not-found\nActiveState=inactive\nSubState=dead\n",
+            ))
+        }
+
+        fn restart_unit(&self, name: &str) -> io::Result<()> {
+            self.job("restart", name)
+        }
+
+        fn stop_unit(&self, name: &str) -> io::Result<()> {
+            self.job("stop", name)
+        }
+    }
+
+    impl StubManager {
+        fn job(&self, verb: &str, name: &str) -> io::Result<()> {
+            if name != "cron.service" {
+                return Err(io::Error::other(format!("Unit {} not found.", name)));
+            }
+            self.jobs.borrow_mut().push(format!("{} {}", verb, name));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn drives_units_through_the_manager() {
+        let jobs = Rc::new(RefCell::new(Vec::new()));
+        let mut monitor = Monitor::new();
+        monitor.systemd = Some(Box::new(StubManager { jobs: jobs.clone() }));
+
+        assert!(monitor.has_systemd());
+        assert_eq!(monitor.get_units().len(), 2);
+
+        // Units that failed to load are left out of the table
+        let mut table = UnitTable::new();
+        table.update_with_state(&monitor, &mut AppState::new());
+        assert!(table.is_available());
+        assert_eq!(
+            table.get_unit(0).map(|u| u.name.as_str()),
+            Some("cron.service")
+        );
+        assert!(table.get_unit(1).is_none());
+
+        monitor.restart_unit("cron.service").unwrap();
+        monitor.stop_unit("cron.service").unwrap();
+        assert!(monitor.stop_unit("foo.service").is_err());
+        assert_eq!(
+            *jobs.borrow(),
+            ["restart cron.service", "stop cron.service"]
+        );
+    }
+
+    #[test]
+    fn degrades_without_systemd() {
+        let mut monitor = Monitor::new();
+        monitor.systemd = None;
+
+        assert!(!monitor.has_systemd());
+        assert!(monitor.get_units().is_empty());
+        let mut table = UnitTable::new();
+        table.update_with_state(&monitor, &mut AppState::new());
+        assert!(!table.is_available());
+        assert_eq!(
+            monitor.restart_unit("cron.service").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn reads_inode_counts_with_the_disk_list() {
+        let mut monitor = Monitor::new();
+        let mounts: Vec<PathBuf> = monitor
+            .disks
+            .list()
+            .iter()
+            .map(|d| d.mount_point().to_path_buf())
+            .collect();
+        monitor.inode_counts = mounts.iter().map(|m| (m.clone(), (7, 3))).collect();
+
+        // Updates in between disk refreshes keep the counts read last time
+        monitor.update();
+        for disk in monitor.get_disks() {
+            assert_eq!((disk.total_inodes, disk.free_inodes), (7, 3));
+        }
+        monitor.disks_refreshed -= DISK_REFRESH_INTERVAL;
+        monitor.update();
+        assert!(monitor.inode_counts.values().all(|&c| c != (7, 3)));
+    }
+}
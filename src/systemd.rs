@@ -0,0 +1,239 @@
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+// Unit properties asked from systemd, in the order `systemctl show` prints them back
+const PROPERTIES: &str =
+    "Id,Description,LoadState,ActiveState,SubState,MainPID,TasksCurrent,MemoryCurrent,CPUUsageNSec,ControlGroup";
+
+#[derive(Clone)]
+pub struct Unit {
+    pub name: String,
+    pub description: String,
+    pub load_state: String,
+    // e.g. "active" or "failed"
+    pub active_state: String,
+    // e.g. "running" or "exited"
+    pub sub_state: String,
+    pub main_pid: Option<u32>,
+    pub tasks: Option<u64>,
+    // In bytes
+    pub memory: Option<u64>,
+    // CPU time used since the unit started, in nanoseconds
+    pub cpu_usage: Option<u64>,
+    // e.g. "/system.slice/cron.service"
+    pub control_group: Option<String>,
+}
+
+// The operations ptop needs from the service manager, so that the units view
+// can be driven by a stand-in when systemd is not available
+pub trait Manager {
+    fn list_units(&self) -> io::Result<Vec<Unit>>;
+    fn restart_unit(&self, name: &str) -> io::Result<()>;
+    fn stop_unit(&self, name: &str) -> io::Result<()>;
+}
+
+// Talks to the systemd manager on the system bus through systemctl
+pub struct Systemctl;
+
+impl Systemctl {
+    // Returns a manager only when the system was booted with systemd
+    pub fn discover() -> Option<Self> {
+        Path::new("/run/systemd/system")
+            .exists()
+            .then_some(Systemctl)
+    }
+
+    fn run(args: &[&str]) -> io::Result<String> {
+        let output = Command::new("systemctl")
+            // Never block the UI on a polkit password prompt
+            .arg("--no-ask-password")
+            .args(args)
+            .stdin(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            let message = String::from_utf8_lossy(&output.stderr);
+            let message: Vec<&str> = message.lines().filter(|l| !l.is_empty()).collect();
+            return Err(io::Error::other(message.join("; ")));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+impl Manager for Systemctl {
+    fn list_units(&self) -> io::Result<Vec<Unit>> {
+        let output = Systemctl::run(&["show", "--property", PROPERTIES, "*.service"])?;
+        Ok(parse_show(&output))
+    }
+
+    // --no-block queues the job and returns instead of waiting for the unit
+    fn restart_unit(&self, name: &str) -> io::Result<()> {
+        Systemctl::run(&["restart", "--no-block", name]).map(|_| ())
+    }
+
+    fn stop_unit(&self, name: &str) -> io::Result<()> {
+        Systemctl::run(&["stop", "--no-block", name]).map(|_| ())
+    }
+}
+
+// Parses the output of `systemctl show`, one block of Key=Value lines per unit
+// with blocks separated by empty lines
+pub fn parse_show(content: &str) -> Vec<Unit> {
+    content
+        .split("\n\n")
+        .filter_map(|block| {
+            let mut unit = Unit {
+                name: String::new(),
+                description: String::new(),
+                load_state: String::new(),
+                active_state: String::new(),
+                sub_state: String::new(),
+                main_pid: None,
+                tasks: None,
+                memory: None,
+                cpu_usage: None,
+                control_group: None,
+            };
+            for line in block.lines() {
+                let Some((key, value)) = line.split_once('=') else {
+                    continue;
+                };
+                match key {
+                    "Id" => unit.name = value.to_string(),
+                    "Description" => unit.description = value.to_string(),
+                    "LoadState" => unit.load_state = value.to_string(),
+                    "ActiveState" => unit.active_state = value.to_string(),
+                    "SubState" => unit.sub_state = value.to_string(),
+                    "MainPID" => unit.main_pid = value.parse().ok().filter(|pid| *pid != 0),
+                    "TasksCurrent" => unit.tasks = parse_counter(value),
+                    "MemoryCurrent" => unit.memory = parse_counter(value),
+                    "CPUUsageNSec" => unit.cpu_usage = parse_counter(value),
+                    "ControlGroup" => {
+                        unit.control_group = Some(value.to_string()).filter(|v| !v.is_empty())
+                    }
+                    _ => {}
+                }
+            }
+            (!unit.name.is_empty()).then_some(unit)
+        })
+        .collect()
+}
+
+// systemd prints "[not set]" or u64::MAX for counters it does not track
+fn parse_counter(value: &str) -> Option<u64> {
+    value.parse().ok().filter(|v| *v != u64::MAX)
+}
+
+// Returns the innermost service a cgroup path belongs to, e.g. "cron.service"
+// for "/system.slice/cron.service"
+pub fn unit_name(cgroup: &str) -> Option<&str> {
+    cgroup
+        .split('/')
+        .rev()
+        .find(|component| component.ends_with(".service"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Captured from `systemctl show --property ... '*.service'`
+    const SHOW: &str = "\
+Id=cron.service
+Description=Regular background program processing daemon
+LoadState=loaded
+ActiveState=active
+SubState=running
+MainPID=812
+TasksCurrent=1
+MemoryCurrent=1327104
+CPUUsageNSec=48210000
+ControlGroup=/system.slice/cron.service
+
+Id=apt-daily.service
+Description=Daily apt download activities
+LoadState=loaded
+ActiveState=inactive
+SubState=dead
+MainPID=0
+TasksCurrent=[not set]
+MemoryCurrent=[not set]
+CPUUsageNSec=18446744073709551615
+ControlGroup=
+
+Id=foo.service
+Description=foo.service
+LoadState=not-found
+ActiveState=inactive
+SubState=dead
+MainPID=0
+TasksCurrent=[not set]
+MemoryCurrent=[not set]
+CPUUsageNSec=[not set]
+ControlGroup=
+";
+
+    #[test]
+    fn parses_show_output() {
+        let units = parse_show(SHOW);
+        assert_eq!(units.len(), 3);
+
+        let cron = &units[0];
+        assert_eq!(cron.name, "cron.service");
+        assert_eq!(
+            cron.description,
+            "Regular background program processing daemon"
+        );
+        assert_eq!(cron.load_state, "loaded");
+        assert_eq!(cron.active_state, "active");
+        assert_eq!(cron.sub_state, "running");
+        assert_eq!(cron.main_pid, Some(812));
+        assert_eq!(cron.tasks, Some(1));
+        assert_eq!(cron.memory, Some(1327104));
+        assert_eq!(cron.cpu_usage, Some(48210000));
+        assert_eq!(
+            cron.control_group.as_deref(),
+            Some("/system.slice/cron.service")
+        );
+
+        // Counters systemd does not track and empty values are left unset
+        let apt = &units[1];
+        assert_eq!(apt.sub_state, "dead");
+        assert_eq!(apt.main_pid, None);
+        assert_eq!(apt.tasks, None);
+        assert_eq!(apt.memory, None);
+        assert_eq!(apt.cpu_usage, None);
+        assert_eq!(apt.control_group, None);
+
+        assert_eq!(units[2].load_state, "not-found");
+    }
+
+    #[test]
+    fn skips_blocks_without_an_id() {
+        assert!(parse_show("").is_empty());
+        assert!(parse_show("Description=x\nActiveState=active\n\n").is_empty());
+        // Values may themselves contain '='
+        let units = parse_show("Id=a.service\nDescription=a=b\n");
+        assert_eq!(units[0].description, "a=b");
+    }
+
+    #[test]
+    fn finds_unit_of_cgroup() {
+        assert_eq!(
+            unit_name("/system.slice/cron.service"),
+            Some("cron.service")
+        );
+        assert_eq!(
+            unit_name("/system.slice/docker.service/container/abc"),
+            Some("docker.service")
+        );
+        assert_eq!(
+            unit_name("/user.slice/user-1000.slice/user@1000.service/app.slice/dbus.service"),
+            Some("dbus.service")
+        );
+        assert_eq!(
+            unit_name("/user.slice/user-1000.slice/session-2.scope"),
+            None
+        );
+    }
+}
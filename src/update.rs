@@ -1,4 +1,4 @@
-use crate::app::{AppState, PTop};
+use crate::app::{AppState, PTop, Page};
 use crate::system_monitor;
 
 pub trait UpdateableWidget {
@@ -10,13 +10,72 @@ pub trait UpdateableWidgetWithState {
 }
 
 pub fn update_widgets(app: &mut PTop, monitor: &system_monitor::Monitor) {
-    app.widgets.cpu.update(monitor);
+    app.widgets.cpu.update_with_state(monitor, &mut app.state);
     app.widgets.info.update(monitor);
-    app.widgets.memory.update(monitor);
+    app.widgets
+        .memory
+        .update_with_state(monitor, &mut app.state);
+    app.widgets.memory_breakdown.update(monitor);
+    app.widgets.memory_breakdown_history.update(monitor);
+    app.widgets
+        .disk_io
+        .update_with_state(monitor, &mut app.state);
+    // Reading inode usage takes a statvfs per mount, only do it while shown
+    if app.state.page == Page::Storage {
+        app.widgets.filesystem_table.update(monitor);
+    }
     app.widgets.cpu_per_core.update(monitor);
+    app.widgets.cpu_breakdown.update(monitor);
+    app.widgets.cpu_heatmap.update(monitor);
+    app.widgets.cpu_frequency.update(monitor);
+    app.widgets.load.update_with_state(monitor, &mut app.state);
+    app.widgets
+        .pressure
+        .update_with_state(monitor, &mut app.state);
+    app.widgets.pressure_table.update(monitor);
+    app.widgets
+        .network
+        .update_with_state(monitor, &mut app.state);
+    app.widgets
+        .network_table
+        .update_with_state(monitor, &mut app.state);
+    app.widgets.cgroup_table.update(monitor);
+    // Listing containers is a request to the runtime daemon, only make it while shown
+    if app.state.page == Page::Containers {
+        app.widgets.container_table.update(monitor);
+    }
+    // Listing units runs systemctl, only do it while shown
+    if app.state.page == Page::Units {
+        app.widgets
+            .unit_table
+            .update_with_state(monitor, &mut app.state);
+    }
+    app.widgets.session_table.update(monitor);
+    app.widgets.user_table.update(monitor);
+    app.widgets
+        .sensors
+        .update_with_state(monitor, &mut app.state);
+    app.widgets.sensor_table.update(monitor);
+    app.widgets.power.update_with_state(monitor, &mut app.state);
+    app.widgets.power_table.update(monitor);
+    app.widgets
+        .history
+        .update_with_state(monitor, &mut app.state);
+    // The event log must run before the process table, which consumes its highlights
+    app.widgets
+        .event_log
+        .update_with_state(monitor, &mut app.state);
     app.widgets
         .process_table
         .update_with_state(monitor, &mut app.state);
+    app.widgets.socket_table.update(monitor);
+    app.widgets.stack_view.update(monitor);
+    app.widgets
+        .inspector
+        .update_with_state(monitor, &mut app.state);
+    app.widgets
+        .thread_table
+        .update_with_state(monitor, &mut app.state);
     app.widgets
         .alert_table
         .update_with_state(monitor, &mut app.state);
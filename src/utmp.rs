@@ -0,0 +1,135 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Layout of `struct utmp` on Linux with glibc, which is the same on 32 and 64 bit
+const RECORD_SIZE: usize = 384;
+const TYPE_OFFSET: usize = 0;
+const PID_OFFSET: usize = 4;
+const LINE_OFFSET: usize = 8;
+const LINE_SIZE: usize = 32;
+const USER_OFFSET: usize = 44;
+const USER_SIZE: usize = 32;
+const HOST_OFFSET: usize = 76;
+const HOST_SIZE: usize = 256;
+const TIME_OFFSET: usize = 340;
+// ut_type of a login session
+const USER_PROCESS: i16 = 7;
+
+#[derive(Clone)]
+pub struct Session {
+    pub user: String,
+    // Terminal of the session, e.g. "pts/0"
+    pub line: String,
+    // Remote host for remote logins, empty for local ones
+    pub host: String,
+    // Process of the session leader, usually the login shell
+    pub pid: u32,
+    pub login_time: SystemTime,
+}
+
+// Reads the login sessions recorded in a utmp file, e.g. /var/run/utmp
+pub fn read_sessions(path: &Path) -> Vec<Session> {
+    fs::read(path)
+        .map(|content| parse_utmp(&content))
+        .unwrap_or_default()
+}
+
+pub fn parse_utmp(content: &[u8]) -> Vec<Session> {
+    content
+        .chunks_exact(RECORD_SIZE)
+        .filter(|record| read_i16(record, TYPE_OFFSET) == USER_PROCESS)
+        .map(|record| Session {
+            user: read_string(record, USER_OFFSET, USER_SIZE),
+            line: read_string(record, LINE_OFFSET, LINE_SIZE),
+            host: read_string(record, HOST_OFFSET, HOST_SIZE),
+            pid: read_i32(record, PID_OFFSET) as u32,
+            login_time: UNIX_EPOCH
+                + Duration::from_secs(read_i32(record, TIME_OFFSET) as u32 as u64),
+        })
+        .collect()
+}
+
+fn read_i16(record: &[u8], offset: usize) -> i16 {
+    i16::from_ne_bytes([record[offset], record[offset + 1]])
+}
+
+fn read_i32(record: &[u8], offset: usize) -> i32 {
+    i32::from_ne_bytes([
+        record[offset],
+        record[offset + 1],
+        record[offset + 2],
+        record[offset + 3],
+    ])
+}
+
+// Fields are NUL padded, and not NUL terminated when they fill the whole field
+fn read_string(record: &[u8], offset: usize, size: usize) -> String {
+    let field = &record[offset..offset + size];
+    let end = field.iter().position(|b| *b == 0).unwrap_or(size);
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::Fixture;
+
+    // ut_type of a session that has ended
+    const DEAD_PROCESS: i16 = 8;
+
+    fn record(kind: i16, pid: i32, line: &str, user: &str, host: &str, time: i32) -> Vec<u8> {
+        let mut record = vec![0; RECORD_SIZE];
+        record[TYPE_OFFSET..TYPE_OFFSET + 2].copy_from_slice(&kind.to_ne_bytes());
+        record[PID_OFFSET..PID_OFFSET + 4].copy_from_slice(&pid.to_ne_bytes());
+        record[LINE_OFFSET..LINE_OFFSET + line.len()].copy_from_slice(line.as_bytes());
+        record[USER_OFFSET..USER_OFFSET + user.len()].copy_from_slice(user.as_bytes());
+        record[HOST_OFFSET..HOST_OFFSET + host.len()].copy_from_slice(host.as_bytes());
+        record[TIME_OFFSET..TIME_OFFSET + 4].copy_from_slice(&time.to_ne_bytes());
+        record
+    }
+
+    #[test]
+    fn reads_user_sessions() {
+        // A user name filling its whole field is not NUL terminated
+        let long_user = "u".repeat(USER_SIZE);
+        let mut content = record(USER_PROCESS, 1234, "pts/0", "alice", "10.0.0.2", 1700000000);
+        content.extend(record(DEAD_PROCESS, 999, "pts/1", "", "", 1690000000));
+        content.extend(record(
+            USER_PROCESS,
+            4321,
+            "tty1",
+            &long_user,
+            "",
+            1700000100,
+        ));
+        // A record cut short while being written
+        content.extend(&record(USER_PROCESS, 5, "pts/2", "bob", "", 0)[..100]);
+
+        let fixture = Fixture::new();
+        let path = fixture.path().join("utmp");
+        fs::write(&path, content).unwrap();
+        let sessions = read_sessions(&path);
+        assert_eq!(sessions.len(), 2);
+
+        assert_eq!(sessions[0].user, "alice");
+        assert_eq!(sessions[0].line, "pts/0");
+        assert_eq!(sessions[0].host, "10.0.0.2");
+        assert_eq!(sessions[0].pid, 1234);
+        assert_eq!(
+            sessions[0].login_time,
+            UNIX_EPOCH + Duration::from_secs(1700000000)
+        );
+
+        assert_eq!(sessions[1].user, long_user);
+        assert_eq!(sessions[1].line, "tty1");
+        assert_eq!(sessions[1].host, "");
+        assert_eq!(sessions[1].pid, 4321);
+    }
+
+    #[test]
+    fn reads_nothing_without_utmp() {
+        let fixture = Fixture::new();
+        assert!(read_sessions(&fixture.path().join("utmp")).is_empty());
+    }
+}